@@ -0,0 +1,18 @@
+fn main() {
+    // Regenerate the committed C header from the functions exported by the C
+    // ABI only when asked to, so that builds leave the source tree alone
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-env-changed=PIWI_GENERATE_HEADER");
+        if std::env::var_os("PIWI_GENERATE_HEADER").is_some() {
+            println!("cargo:rerun-if-changed=src/ffi.rs");
+            println!("cargo:rerun-if-changed=cbindgen.toml");
+            let dir = std::env::var("CARGO_MANIFEST_DIR").expect("cargo sets the manifest dir");
+            let config = cbindgen::Config::from_file(format!("{dir}/cbindgen.toml"))
+                .expect("the config is valid");
+            cbindgen::generate_with_config(&dir, config)
+                .expect("the C ABI is supported by cbindgen")
+                .write_to_file(format!("{dir}/include/piwi.h"));
+        }
+    }
+}
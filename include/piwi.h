@@ -0,0 +1,131 @@
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#ifndef PIWI_H
+#define PIWI_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Outcome of a search through the C ABI.
+ */
+typedef enum PiwiStatus {
+  /**
+   * The call succeeded.
+   */
+  PIWI_STATUS_OK = 0,
+  /**
+   * A string argument is not valid UTF-8, or the flags or the prefix are
+   * malformed.
+   */
+  PIWI_STATUS_INVALID_ARGUMENT = 1,
+  /**
+   * The search was cancelled through its cancellation handle.
+   */
+  PIWI_STATUS_CANCELLED = 2,
+  /**
+   * The search failed, e.g. after exhausting the salts.
+   */
+  PIWI_STATUS_FAILED = 3,
+} PiwiStatus;
+
+/**
+ * Opaque handle stopping a search from another thread.
+ */
+typedef struct PiwiCancel PiwiCancel;
+
+/**
+ * Salt found by a miner, with the address it deploys the contract to.
+ */
+typedef struct PiwiResult {
+  /**
+   * Address the contract is deployed at with the salt.
+   */
+  uint8_t address[20];
+  /**
+   * Salt passed to the factory contract.
+   */
+  uint8_t salt[32];
+  /**
+   * Number of salts tried by the miner.
+   */
+  uint64_t attempts;
+} PiwiResult;
+
+/**
+ * Creates a cancellation handle, to be released with `piwi_cancel_free`.
+ */
+struct PiwiCancel *piwi_cancel_new(void);
+
+/**
+ * Stops the searches given the handle, which then return `PiwiStatus::Cancelled`.
+ *
+ * # Safety
+ *
+ * `cancel` must come from `piwi_cancel_new` and not be freed yet.
+ */
+void piwi_cancel(const struct PiwiCancel *cancel);
+
+/**
+ * Releases a cancellation handle once no search uses it anymore.
+ *
+ * # Safety
+ *
+ * `cancel` must come from `piwi_cancel_new` and not be freed yet, or be
+ * null.
+ */
+void piwi_cancel_free(struct PiwiCancel *cancel);
+
+/**
+ * Mines a CREATE2 salt deploying the init code with the given hash to an
+ * address matching the flags and prefix, blocking until it is found.
+ *
+ * # Arguments
+ * * `deployer` - The 20-byte address starting the salt.
+ * * `factory` - The 20-byte address of the CREATE2 factory contract.
+ * * `init_code_hash` - The 32-byte keccak256 hash of the init code.
+ * * `flags` - The flags expected in the lower 14 bits of the address, as a
+ *   hex string.
+ * * `prefix` - The prefix expected at the start of the address, as a hex
+ *   string, or null for none.
+ * * `cancel` - The handle stopping the search, or null for none.
+ * * `result` - Where the salt and the address are written on success.
+ *
+ * # Safety
+ *
+ * The byte arguments must point to that many readable bytes, the strings
+ * must be NUL-terminated, `cancel` must be live if not null, and `result`
+ * must be writable.
+ */
+enum PiwiStatus piwi_mine_create2(const uint8_t *deployer,
+                                  const uint8_t *factory,
+                                  const uint8_t *init_code_hash,
+                                  const char *flags,
+                                  const char *prefix,
+                                  const struct PiwiCancel *cancel,
+                                  struct PiwiResult *result);
+
+/**
+ * Computes the address a CREATE3 factory hashing the salt after the
+ * deployer deploys a contract to.
+ *
+ * # Arguments
+ * * `deployer` - The 20-byte address calling the factory.
+ * * `factory` - The 20-byte address of the CREATE3 factory contract.
+ * * `salt` - The 32-byte salt given to the factory.
+ * * `address` - Where the 20-byte address is written.
+ *
+ * # Safety
+ *
+ * The byte arguments must point to that many readable bytes, and `address`
+ * to 20 writable bytes.
+ */
+void piwi_compute_create3(const uint8_t *deployer,
+                          const uint8_t *factory,
+                          const uint8_t *salt,
+                          uint8_t *address);
+
+#endif  /* PIWI_H */
@@ -0,0 +1,24 @@
+const assert = require("node:assert/strict");
+const { test } = require("node:test");
+
+const { computeCreate2Address, computeCreate3Address, mineCreate2, mineCreate3 } = require("..");
+
+const zero = "0x0000000000000000000000000000000000000000";
+const hash = `0x${"00".repeat(32)}`;
+
+test("mined CREATE2 salts deploy to the returned address", async () => {
+  const { address, salt } = await mineCreate2({ deployer: zero, factory: zero, initCodeHash: hash, flags: "0x0080" });
+  assert.equal(computeCreate2Address(zero, salt, hash), address);
+  assert.equal(parseInt(address.slice(-4), 16) & 0x3fff, 0x0080);
+});
+
+test("mined CREATE3 salts deploy to the returned address", async () => {
+  const { address, salt } = await mineCreate3({ deployer: zero, factory: zero, flags: "0x0080", prefix: "0x00" });
+  assert.equal(computeCreate3Address(zero, zero, salt), address);
+  assert.ok(address.startsWith("0x00"));
+});
+
+test("malformed options throw before the search starts", () => {
+  assert.throws(() => mineCreate2({ deployer: zero, factory: zero, initCodeHash: hash, flags: "0x0080", prefix: "0xzz" }));
+  assert.throws(() => mineCreate3({ deployer: "0x12", factory: zero, flags: "0x0080" }));
+});
@@ -0,0 +1,4 @@
+fn main() {
+    // Let the addon resolve the N-API symbols from the host process
+    napi_build::setup();
+}
@@ -0,0 +1,172 @@
+use std::str::FromStr;
+
+use alloy_primitives::{Address, Bytes};
+use napi::{Env, Error, Result, Task, bindgen_prelude::AsyncTask};
+use napi_derive::napi;
+
+use piwi::{Create2Miner, Create3Miner, Miner, MiningResult, Pattern};
+
+/// Options of `mineCreate2`, with addresses and hashes as hex strings.
+#[napi(object)]
+pub struct Create2Options {
+    /// Address calling the factory, which starts the salt.
+    pub deployer: String,
+    /// Address of the CREATE2 factory contract.
+    pub factory: String,
+    /// Keccak256 hash of the init code of the contract.
+    pub init_code_hash: String,
+    /// Flags expected in the lower 14 bits of the address.
+    pub flags: String,
+    /// Prefix expected at the start of the address.
+    pub prefix: Option<String>,
+    /// Bytes pinned right after the deployer address in the salt.
+    pub salt_prefix: Option<String>,
+}
+
+/// Options of `mineCreate3`, with addresses as hex strings.
+#[napi(object)]
+pub struct Create3Options {
+    /// Address calling the factory, which the factory hashes the salt with.
+    pub deployer: String,
+    /// Address of the CREATE3 factory contract.
+    pub factory: String,
+    /// Flags expected in the lower 14 bits of the address.
+    pub flags: String,
+    /// Prefix expected at the start of the address.
+    pub prefix: Option<String>,
+    /// Bytes pinned at the start of the salt.
+    pub salt_prefix: Option<String>,
+}
+
+/// Salt found by a miner, with the address it deploys the contract to, as
+/// hex strings.
+#[napi(object, js_name = "MiningResult")]
+pub struct JsMiningResult {
+    /// Address the contract is deployed at with the salt.
+    pub address: String,
+    /// Salt passed to the factory contract.
+    pub salt: String,
+}
+
+/// Search run on the libuv thread pool, resolving the promise returned to
+/// JavaScript once a salt is found.
+pub struct Search {
+    /// The miner searching for the salt.
+    miner: Box<dyn Miner + Send>,
+    /// The pattern the address has to match.
+    pattern: Pattern,
+}
+
+#[napi]
+impl Task for Search {
+    type Output = MiningResult;
+    type JsValue = JsMiningResult;
+
+    fn compute(&mut self) -> Result<MiningResult> {
+        self.miner
+            .mine(&self.pattern)
+            .map_err(|err| Error::from_reason(err.to_string()))
+    }
+
+    fn resolve(
+        &mut self,
+        _: Env,
+        MiningResult { address, salt, .. }: MiningResult,
+    ) -> Result<JsMiningResult> {
+        Ok(JsMiningResult {
+            address: address.to_string(),
+            salt: salt.to_string(),
+        })
+    }
+}
+
+/// Mines a CREATE2 salt deploying the init code with the given hash to an
+/// address matching the flags and prefix, without blocking the event loop.
+#[napi(js_name = "mineCreate2")]
+pub fn mine_create2(options: Create2Options) -> Result<AsyncTask<Search>> {
+    let miner = Create2Miner::new(
+        parse("deployer", &options.deployer)?,
+        parse("factory", &options.factory)?,
+        parse("init code hash", &options.init_code_hash)?,
+    )
+    .with_salt_prefix(salt_prefix(options.salt_prefix.as_deref())?);
+    Ok(AsyncTask::new(search(
+        miner,
+        &options.flags,
+        options.prefix.as_deref(),
+    )?))
+}
+
+/// Mines a CREATE3 salt deploying a contract to an address matching the
+/// flags and prefix, with a factory hashing the salt after the deployer,
+/// without blocking the event loop.
+#[napi(js_name = "mineCreate3")]
+pub fn mine_create3(options: Create3Options) -> Result<AsyncTask<Search>> {
+    let miner = Create3Miner::new(
+        parse("deployer", &options.deployer)?,
+        parse("factory", &options.factory)?,
+    )
+    .with_salt_prefix(salt_prefix(options.salt_prefix.as_deref())?);
+    Ok(AsyncTask::new(search(
+        miner,
+        &options.flags,
+        options.prefix.as_deref(),
+    )?))
+}
+
+/// Computes the address a factory deploys the init code with the given hash
+/// to with CREATE2.
+#[napi(js_name = "computeCreate2Address")]
+pub fn compute_create2_address(
+    factory: String,
+    salt: String,
+    init_code_hash: String,
+) -> Result<String> {
+    let miner = Create2Miner::new(
+        Address::ZERO,
+        parse("factory", &factory)?,
+        parse("init code hash", &init_code_hash)?,
+    );
+    Ok(miner.compute_address(&parse("salt", &salt)?).to_string())
+}
+
+/// Computes the address a CREATE3 factory hashing the salt after the
+/// deployer deploys a contract to.
+#[napi(js_name = "computeCreate3Address")]
+pub fn compute_create3_address(deployer: String, factory: String, salt: String) -> Result<String> {
+    let miner = Create3Miner::new(parse("deployer", &deployer)?, parse("factory", &factory)?);
+    Ok(miner.compute_address(&parse("salt", &salt)?).to_string())
+}
+
+/// Builds the search of a miner for the flags and prefix.
+///
+/// # Errors
+///
+/// Returns an error if the flags or the prefix are malformed.
+fn search(miner: impl Miner + Send + 'static, flags: &str, prefix: Option<&str>) -> Result<Search> {
+    let pattern = Pattern::parse(flags, prefix.unwrap_or_default())
+        .map_err(|err| Error::from_reason(err.to_string()))?;
+    Ok(Search {
+        miner: Box::new(miner),
+        pattern,
+    })
+}
+
+/// Parses the optional salt prefix, which defaults to no pinned bytes.
+fn salt_prefix(salt_prefix: Option<&str>) -> Result<Bytes> {
+    salt_prefix
+        .map(|salt_prefix| parse("salt prefix", salt_prefix))
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+/// Parses a hex argument, naming it in the error if malformed.
+fn parse<T>(name: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|err| Error::from_reason(format!("invalid {name} {value:?}: {err}")))
+}
@@ -0,0 +1,145 @@
+use std::{collections::HashMap, fs};
+
+use crate::error::{Error, Result};
+
+/// Returns the logical CPUs the workers are pinned to, in order.
+///
+/// Only the CPUs the process is allowed to run on are used, e.g. under
+/// `taskset` or a container CPU limit.
+///
+/// # Arguments
+/// * `skip_smt` - Whether to keep a single logical CPU per physical core,
+///   leaving out its SMT siblings.
+///
+/// # Errors
+///
+/// Returns an error if the allowed CPUs cannot be read, or on platforms
+/// other than Linux.
+#[cfg(target_os = "linux")]
+pub(super) fn cpus(skip_smt: bool) -> Result<Vec<usize>> {
+    use std::{collections::HashSet, io, mem};
+
+    // SAFETY: the set is a plain bitmask, valid when zeroed, which the call
+    // fills with the allowed CPUs
+    let set = unsafe {
+        let mut set: libc::cpu_set_t = mem::zeroed();
+        if libc::sched_getaffinity(0, mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return Err(Error::Affinity(io::Error::last_os_error()));
+        }
+        set
+    };
+    let allowed = (0..libc::CPU_SETSIZE as usize)
+        // SAFETY: the CPU is within the bounds of the set
+        .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) });
+
+    if !skip_smt {
+        return Ok(allowed.collect());
+    }
+
+    // Keep the first allowed CPU of each group of SMT siblings, assuming no
+    // siblings when the topology is not exposed
+    let mut cores = HashSet::new();
+    Ok(allowed
+        .filter(|cpu| {
+            let siblings =
+                format!("/sys/devices/system/cpu/cpu{cpu}/topology/thread_siblings_list");
+            let core = fs::read_to_string(siblings).unwrap_or_else(|_| cpu.to_string());
+            cores.insert(core.trim().to_string())
+        })
+        .collect())
+}
+
+/// Returns the logical CPUs the workers are pinned to, in order.
+///
+/// # Errors
+///
+/// Always returns an error, as pinning is only supported on Linux.
+#[cfg(not(target_os = "linux"))]
+pub(super) fn cpus(_skip_smt: bool) -> Result<Vec<usize>> {
+    Err(Error::UnsupportedAffinity)
+}
+
+/// Returns the NUMA node of each of the given logical CPUs, as dense group
+/// indices numbered in order of first appearance.
+///
+/// CPUs missing from the topology, e.g. on machines without NUMA support,
+/// are all assigned to the first group.
+pub(super) fn nodes(cpus: &[usize]) -> Vec<usize> {
+    // Map each CPU to the node listing it
+    let mut node_of = HashMap::new();
+    if let Ok(entries) = fs::read_dir("/sys/devices/system/node") {
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let Some(node) = name
+                .to_str()
+                .and_then(|name| name.strip_prefix("node"))
+                .and_then(|id| id.parse::<usize>().ok())
+            else {
+                continue;
+            };
+            if let Ok(list) = fs::read_to_string(entry.path().join("cpulist")) {
+                node_of.extend(parse_cpu_list(&list).into_iter().map(|cpu| (cpu, node)));
+            }
+        }
+    }
+
+    // Number the nodes densely so that every group has workers
+    let mut groups = HashMap::new();
+    cpus.iter()
+        .map(|cpu| {
+            let node = node_of.get(cpu).copied().unwrap_or_default();
+            let next = groups.len();
+            *groups.entry(node).or_insert(next)
+        })
+        .collect()
+}
+
+/// Parses a kernel CPU list such as `0-3,8,10-11`, skipping malformed items.
+fn parse_cpu_list(list: &str) -> Vec<usize> {
+    list.trim()
+        .split(',')
+        .filter_map(|item| match item.split_once('-') {
+            Some((first, last)) => Some(first.parse().ok()?..=last.parse().ok()?),
+            None => item.parse().ok().map(|cpu| cpu..=cpu),
+        })
+        .flatten()
+        .collect()
+}
+
+/// Pins the current thread to a logical CPU.
+///
+/// # Errors
+///
+/// Returns an error if the CPU is not available to the process.
+#[cfg(target_os = "linux")]
+pub(super) fn pin(cpu: usize) -> Result<()> {
+    use std::{io, mem};
+
+    // SAFETY: the set is a plain bitmask, valid when zeroed, and the CPU comes
+    // from the allowed set so it is within its bounds
+    unsafe {
+        let mut set: libc::cpu_set_t = mem::zeroed();
+        libc::CPU_SET(cpu, &mut set);
+        if libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(Error::Affinity(io::Error::last_os_error()));
+        }
+    }
+    Ok(())
+}
+
+/// Pins the current thread to a logical CPU.
+///
+/// # Errors
+///
+/// Always returns an error, as pinning is only supported on Linux.
+#[cfg(not(target_os = "linux"))]
+pub(super) fn pin(_cpu: usize) -> Result<()> {
+    Err(Error::UnsupportedAffinity)
+}
+
+#[test]
+fn test_parse_cpu_list() {
+    assert_eq!(parse_cpu_list("0-3,8,10-11\n"), [0, 1, 2, 3, 8, 10, 11]);
+    assert_eq!(parse_cpu_list("5"), [5]);
+    assert_eq!(parse_cpu_list("\n"), [] as [usize; 0]);
+}
@@ -0,0 +1,84 @@
+use std::fs;
+
+use alloy_primitives::hex;
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+
+/// Flavors of contract artifact JSON files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flavor {
+    /// Produced by `forge build` under `out/<File>.sol/<Contract>.json`.
+    Foundry,
+    /// Produced by `npx hardhat compile` under
+    /// `artifacts/contracts/<File>.sol/<Contract>.json`.
+    Hardhat,
+}
+
+/// Reads the contract creation code from a Foundry or Hardhat artifact JSON
+/// file, detecting the flavor from the shape of the artifact.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, is not a known artifact, or
+/// contains unlinked library placeholders.
+pub(super) fn read_artifact(path: &str) -> Result<Vec<u8>> {
+    let invalid = |reason: &str| Error::InvalidArtifact {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let contents = fs::read(path).map_err(|source| Error::Read {
+        path: path.to_string(),
+        source,
+    })?;
+    let artifact: Value =
+        serde_json::from_slice(&contents).map_err(|err| invalid(&err.to_string()))?;
+
+    parse_artifact(&artifact).map_err(|reason| invalid(&reason))
+}
+
+/// Extracts the creation code from a parsed artifact of either flavor.
+fn parse_artifact(artifact: &Value) -> Result<Vec<u8>, String> {
+    // Foundry nests the creation code under `bytecode.object`, while Hardhat
+    // stores it directly as the `bytecode` string
+    let flavor = match &artifact["bytecode"] {
+        Value::Object(_) => Flavor::Foundry,
+        Value::String(_) => Flavor::Hardhat,
+        _ => return Err("missing `bytecode` field".to_string()),
+    };
+    let bytecode = match flavor {
+        Flavor::Foundry => artifact["bytecode"]["object"].as_str(),
+        Flavor::Hardhat => artifact["bytecode"].as_str(),
+    }
+    .ok_or_else(|| format!("missing creation code in {flavor:?} artifact"))?;
+
+    decode_bytecode(bytecode)
+}
+
+/// Decodes the hex creation code of an artifact.
+fn decode_bytecode(bytecode: &str) -> Result<Vec<u8>, String> {
+    // Unlinked libraries are left as `__$<hash>$__` placeholders, which cannot
+    // be hashed into a meaningful address
+    if bytecode.contains("__") {
+        return Err("bytecode contains unlinked library placeholders".to_string());
+    }
+
+    let bytecode = hex::decode(bytecode).map_err(|err| err.to_string())?;
+    if bytecode.is_empty() {
+        return Err("bytecode is empty, the contract may be abstract or an interface".to_string());
+    }
+    Ok(bytecode)
+}
+
+#[test]
+fn test_parse_artifact() {
+    let foundry = serde_json::json!({ "bytecode": { "object": "0x6080", "linkReferences": {} } });
+    let hardhat = serde_json::json!({ "_format": "hh-sol-artifact-1", "bytecode": "0x6080" });
+    assert_eq!(parse_artifact(&foundry).unwrap(), vec![0x60, 0x80]);
+    assert_eq!(parse_artifact(&hardhat).unwrap(), vec![0x60, 0x80]);
+
+    let unlinked = serde_json::json!({ "bytecode": "0x6080__$1234$__" });
+    assert!(parse_artifact(&unlinked).is_err());
+    assert!(parse_artifact(&serde_json::json!({ "abi": [] })).is_err());
+}
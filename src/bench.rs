@@ -0,0 +1,168 @@
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use alloy_primitives::{Address, FixedBytes, keccak256};
+use rayon::ThreadPoolBuilder;
+
+use crate::{
+    CREATE2_DEFAULT_FACTORY, CREATE3_DEFAULT_FACTORY,
+    cli::Hasher,
+    error::Result,
+    hash::{self, Backend, LANES},
+    mine::{Create2Miner, Create3Miner, Miner},
+};
+
+/// Number of addresses computed between two deadline checks.
+const BATCH_SIZE: u64 = 1024;
+
+/// Measures how many addresses per second a miner computes on this machine.
+///
+/// Every worker thread derives addresses from its own sequence of salts until
+/// the duration has elapsed, and the total number of derivations is divided by
+/// the wall-clock time spent.
+///
+/// # Arguments
+/// * `miner` - The miner whose address derivation is measured.
+/// * `threads` - The number of worker threads to run concurrently.
+/// * `duration` - How long the measurement should run for.
+///
+/// # Errors
+///
+/// Returns an error if the worker thread pool cannot be created.
+pub(super) fn measure_hashrate<M: Miner + Sync>(
+    miner: &M,
+    threads: usize,
+    duration: Duration,
+) -> Result<f64> {
+    // Run the measurement on a dedicated pool sized to the requested threads
+    let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+
+    let start = Instant::now();
+    let deadline = start + duration;
+
+    let attempts: u64 = pool
+        .broadcast(|context| {
+            // Give each worker a distinct salt sequence
+            let mut salt = [0u8; 32];
+            salt[0..8].copy_from_slice(&(context.index() as u64).to_be_bytes());
+
+            let mut attempts = 0u64;
+            while Instant::now() < deadline {
+                for nonce in attempts..attempts + BATCH_SIZE {
+                    salt[24..32].copy_from_slice(&nonce.to_be_bytes());
+                    black_box(miner.compute_address(&FixedBytes(salt)));
+                }
+                attempts += BATCH_SIZE;
+            }
+            attempts
+        })
+        .into_iter()
+        .sum();
+
+    Ok(attempts as f64 / start.elapsed().as_secs_f64())
+}
+
+/// Measures CREATE2 and CREATE3 hashrates across thread counts and prints
+/// them as a table.
+///
+/// Thread counts double from one up to the number of available cores, which
+/// is always included as the last row.
+///
+/// # Arguments
+/// * `max_threads` - The largest thread count to measure. Defaults to the
+///   number of available cores.
+/// * `duration` - How long each individual measurement runs for.
+pub(super) fn bench(max_threads: Option<usize>, duration: Duration) -> Result<()> {
+    let max_threads = max_threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |threads| threads.get()));
+
+    // Collect the doubling thread counts, ending with the maximum
+    let mut thread_counts: Vec<usize> = std::iter::successors(Some(1), |threads| Some(threads * 2))
+        .take_while(|&threads| threads < max_threads)
+        .collect();
+    thread_counts.push(max_threads);
+
+    // Placeholder inputs are fine since the derivation cost does not depend on
+    // their values
+    let create2 = Create2Miner::new(Address::ZERO, CREATE2_DEFAULT_FACTORY, FixedBytes::ZERO);
+    let create3 = Create3Miner::new(Address::ZERO, CREATE3_DEFAULT_FACTORY);
+
+    println!("{:>8} {:>14} {:>14}", "Threads", "CREATE2", "CREATE3");
+    for threads in thread_counts {
+        println!(
+            "{:>8} {:>14} {:>14}",
+            threads,
+            format_hashrate(measure_hashrate(&create2, threads, duration)?),
+            format_hashrate(measure_hashrate(&create3, threads, duration)?)
+        );
+    }
+    Ok(())
+}
+
+/// Measures how many Keccak256 hashes per second each implementation computes
+/// on a single thread and prints them as a table.
+///
+/// Every implementation hashes one-block messages, like the salts of a
+/// mining run, and is compared with the `keccak256` function of alloy.
+///
+/// # Arguments
+/// * `duration` - How long each individual measurement runs for.
+pub(super) fn bench_hashers(duration: Duration) {
+    println!("{:>10} {:>14}", "Hasher", "Hashrate");
+    println!(
+        "{:>10} {:>14}",
+        "alloy",
+        format_hashrate(measure(duration, LANES as u64, || {
+            for lane in 0..LANES as u64 {
+                black_box(keccak256(black_box(lane.to_be_bytes())));
+            }
+        }))
+    );
+    for backend in [Hasher::Portable, Hasher::Asm, Hasher::Simd]
+        .into_iter()
+        .filter_map(Backend::detect)
+    {
+        let mut states = [[0u64; 25]; LANES];
+        println!(
+            "{:>10} {:>14}",
+            backend.to_string(),
+            format_hashrate(measure(duration, LANES as u64, || {
+                hash::f1600_batch_with(backend, black_box(&mut states));
+            }))
+        );
+    }
+}
+
+/// Runs an operation on the current thread until the duration has elapsed,
+/// returning how many hashes per second it computed.
+///
+/// # Arguments
+/// * `duration` - How long the measurement should run for.
+/// * `hashes` - The number of hashes each run of the operation computes.
+/// * `op` - The measured operation.
+fn measure(duration: Duration, hashes: u64, mut op: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    let mut runs = 0;
+    while start.elapsed() < duration {
+        for _ in 0..BATCH_SIZE / hashes {
+            op();
+        }
+        runs += BATCH_SIZE / hashes;
+    }
+    (runs * hashes) as f64 / start.elapsed().as_secs_f64()
+}
+
+/// Formats a hashrate with an SI unit prefix, e.g. `12.34 MH/s`.
+pub(super) fn format_hashrate(hashrate: f64) -> String {
+    const UNITS: [&str; 5] = ["H/s", "kH/s", "MH/s", "GH/s", "TH/s"];
+
+    let mut value = hashrate;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{value:.2} {}", UNITS[unit])
+}
@@ -0,0 +1,96 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How often the watchdog checks for an interrupt or the end of the timeout.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Whether Ctrl-C was pressed since the interrupt handler was installed.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Handle stopping a search from another thread.
+///
+/// Clones share the same flag, so a token given to a miner can be kept to
+/// cancel the search later. A cancelled search stops within a chunk of work
+/// and returns [`Error::Cancelled`](crate::Error::Cancelled) with the number
+/// of salts tried so far.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops the searches using the token, or the next ones if none is
+    /// under way.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the token was cancelled.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Cancels the token once Ctrl-C is pressed or the timeout, if any,
+    /// elapses.
+    ///
+    /// A watchdog thread polls for both until the token is cancelled or
+    /// every other clone of it is dropped. Pressing Ctrl-C a second time
+    /// kills the process as usual.
+    ///
+    /// # Arguments
+    /// * `timeout` - The time after which the token is cancelled, if any.
+    pub(super) fn cancel_on_interrupt(&self, timeout: Option<Duration>) {
+        install_interrupt_handler();
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let token = self.clone();
+        thread::spawn(move || {
+            while !token.is_cancelled() && Arc::strong_count(&token.0) > 1 {
+                thread::sleep(POLL_INTERVAL);
+                if INTERRUPTED.load(Ordering::Relaxed)
+                    || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+                {
+                    token.cancel();
+                }
+            }
+        });
+    }
+}
+
+/// Records Ctrl-C in [`INTERRUPTED`] instead of killing the process, once.
+#[cfg(unix)]
+fn install_interrupt_handler() {
+    extern "C" fn handle(_: libc::c_int) {
+        INTERRUPTED.store(true, Ordering::Relaxed);
+
+        // Let a second Ctrl-C kill the process
+        unsafe { libc::signal(libc::SIGINT, libc::SIG_DFL) };
+    }
+    unsafe { libc::signal(libc::SIGINT, handle as *const () as libc::sighandler_t) };
+}
+
+/// Leaves Ctrl-C killing the process on platforms without signals.
+#[cfg(not(unix))]
+fn install_interrupt_handler() {}
+
+#[test]
+fn test_cancel_on_interrupt() {
+    let token = CancellationToken::new();
+    token.cancel_on_interrupt(Some(Duration::ZERO));
+    thread::sleep(POLL_INTERVAL * 5);
+    assert!(token.is_cancelled());
+
+    // Clones share the flag
+    let token = CancellationToken::new();
+    token.clone().cancel();
+    assert!(token.is_cancelled());
+}
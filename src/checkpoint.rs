@@ -0,0 +1,230 @@
+use std::{fs, time::Duration};
+
+use alloy_primitives::{Address, B256, Bytes, keccak256};
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::{
+    cli::Runtime,
+    error::{Error, Result},
+    mine::Miner,
+    pattern::Pattern,
+};
+
+/// Progress of a mining run, enough to continue it where it stopped.
+///
+/// The random segment under exploration is saved as is, so resuming does not
+/// depend on replaying the generator that drew it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CheckpointState {
+    /// Seed of the generator drawing the next random segments.
+    pub(super) seed: u64,
+    /// Number of random segments fully explored.
+    pub(super) batch: u64,
+    /// Random segment of the salt under exploration.
+    pub(super) segment: Bytes,
+    /// Next nonce to explore with the current random segment.
+    pub(super) nonce: u64,
+    /// Number of salts tried so far.
+    pub(super) attempts: u64,
+    /// Best salt found by the run, once it is over.
+    pub(super) best: Option<BestCandidate>,
+}
+
+/// Salt found by a checkpointed run, returned again when the run is resumed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct BestCandidate {
+    /// Address the salt derives.
+    pub(super) address: Address,
+    /// Salt buffer the miner built the address from.
+    pub(super) salt: Bytes,
+}
+
+/// Periodically persisted progress of a mining run.
+#[derive(Debug, Clone)]
+pub(super) struct Checkpoint {
+    /// Path of the checkpoint file.
+    path: String,
+    /// Minimum time between two writes of the checkpoint file.
+    pub(super) interval: Duration,
+    /// Fingerprint of the derivation inputs and pattern of the run.
+    job: B256,
+    /// State loaded from an earlier run, if the run is resumed.
+    pub(super) resumed: Option<CheckpointState>,
+}
+
+impl Runtime {
+    /// Sets up checkpointing of a mining run, loading the state of the
+    /// resumed run if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checkpoint to resume cannot be read or was
+    /// written by a run with different inputs.
+    pub(super) fn checkpoint<M: Miner>(
+        &self,
+        miner: &M,
+        pattern: &Pattern,
+    ) -> Result<Option<Checkpoint>> {
+        // Keep writing to the resumed checkpoint unless told otherwise
+        let Some(path) = self.checkpoint.clone().or_else(|| self.resume.clone()) else {
+            return Ok(None);
+        };
+        let job = fingerprint(&miner.derivation()?, pattern);
+
+        let resumed = match &self.resume {
+            Some(resume) => Some(load(resume, job)?),
+            None => None,
+        };
+        Ok(Some(Checkpoint {
+            path,
+            interval: Duration::from_secs(self.checkpoint_interval),
+            job,
+            resumed,
+        }))
+    }
+}
+
+impl Checkpoint {
+    /// Writes the state to the checkpoint file.
+    ///
+    /// The file is replaced atomically, so a crash while saving leaves the
+    /// previous checkpoint intact.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub(super) fn save(&self, state: &CheckpointState) -> Result<()> {
+        let best = state.best.as_ref().map(|best| {
+            json!({
+                "address": best.address,
+                "salt": best.salt,
+            })
+        });
+        let contents = json!({
+            "job": self.job,
+            "seed": state.seed,
+            "batch": state.batch,
+            "segment": state.segment,
+            "nonce": state.nonce,
+            "attempts": state.attempts,
+            "best": best,
+        });
+
+        let temporary = format!("{}.tmp", self.path);
+        fs::write(&temporary, format!("{contents:#}\n"))
+            .and_then(|()| fs::rename(&temporary, &self.path))
+            .map_err(|source| Error::Write {
+                path: self.path.clone(),
+                source,
+            })
+    }
+
+    /// Returns the error of a checkpoint whose state does not fit the run.
+    pub(super) fn invalid(&self, reason: &str) -> Error {
+        Error::InvalidCheckpoint {
+            path: self.path.clone(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Loads the state of a checkpoint file written for the given job.
+fn load(path: &str, job: B256) -> Result<CheckpointState> {
+    let invalid = |reason: &str| Error::InvalidCheckpoint {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let contents = fs::read(path).map_err(|source| Error::Read {
+        path: path.to_string(),
+        source,
+    })?;
+    let checkpoint: Value =
+        serde_json::from_slice(&contents).map_err(|err| invalid(&err.to_string()))?;
+
+    // Continuing the exploration only makes sense for the same inputs
+    if checkpoint["job"].as_str() != Some(&job.to_string()) {
+        return Err(invalid(
+            "it was written by a run with different inputs or pattern",
+        ));
+    }
+
+    let field = |name: &str| {
+        checkpoint[name]
+            .as_u64()
+            .ok_or_else(|| invalid(&format!("missing `{name}` field")))
+    };
+    let best = match &checkpoint["best"] {
+        Value::Null => None,
+        best => Some(BestCandidate {
+            address: parse(&best["address"], "best.address").map_err(|err| invalid(&err))?,
+            salt: parse(&best["salt"], "best.salt").map_err(|err| invalid(&err))?,
+        }),
+    };
+    Ok(CheckpointState {
+        seed: field("seed")?,
+        batch: field("batch")?,
+        segment: parse(&checkpoint["segment"], "segment").map_err(|err| invalid(&err))?,
+        nonce: field("nonce")?,
+        attempts: field("attempts")?,
+        best,
+    })
+}
+
+/// Parses a field of a checkpoint, naming it in the error if invalid.
+fn parse<T: DeserializeOwned>(value: &Value, name: &str) -> std::result::Result<T, String> {
+    T::deserialize(value).map_err(|err| format!("invalid `{name}` field: {err}"))
+}
+
+/// Hashes the derivation inputs and pattern of a run into a fingerprint.
+fn fingerprint(derivation: &[(&str, String)], pattern: &Pattern) -> B256 {
+    let mut inputs: Vec<String> = derivation
+        .iter()
+        .map(|(label, value)| format!("{label}={value}"))
+        .collect();
+    inputs.extend([
+        format!("flags={}", pattern.flags),
+        format!("prefix={}", pattern.prefix),
+        format!("prefix_mask={}", pattern.prefix_mask),
+    ]);
+    keccak256(inputs.join("\n"))
+}
+
+#[test]
+fn test_checkpoint() {
+    let pattern = Pattern::parse("0x80", "ab").unwrap();
+    let job = fingerprint(&[("Method", "CREATE3".to_string())], &pattern);
+    let path = std::env::temp_dir().join(format!("piwi-checkpoint-{}.json", std::process::id()));
+    let path = path.to_str().unwrap().to_string();
+
+    let checkpoint = Checkpoint {
+        path: path.clone(),
+        interval: Duration::ZERO,
+        job,
+        resumed: None,
+    };
+    let mut state = CheckpointState {
+        seed: u64::MAX,
+        batch: 2,
+        segment: Bytes::from_static(&[0xc0, 0xff, 0xee]),
+        nonce: 1 << 40,
+        attempts: 12345,
+        best: None,
+    };
+    checkpoint.save(&state).unwrap();
+    assert_eq!(load(&path, job).unwrap(), state);
+
+    // The salt found by a finished run is saved along
+    state.best = Some(BestCandidate {
+        address: Address::repeat_byte(0xab),
+        salt: Bytes::from_static(&[0x12; 32]),
+    });
+    checkpoint.save(&state).unwrap();
+    assert_eq!(load(&path, job).unwrap(), state);
+
+    // A checkpoint cannot be resumed with different inputs
+    let other = fingerprint(&[("Method", "CREATE2".to_string())], &pattern);
+    assert!(load(&path, other).is_err());
+    fs::remove_file(path).unwrap();
+}
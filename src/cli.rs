@@ -1,4 +1,10 @@
-use alloy_primitives::{Address, FixedBytes};
+use std::{env, ffi::OsString, num::NonZeroU8, str::FromStr};
+
+use alloy_primitives::{Address, Bytes, FixedBytes, U256};
+use clap::{ArgMatches, Command, CommandFactory, FromArgMatches, builder::TypedValueParser};
+use clap_complete::Shell;
+
+use crate::salt::SaltTemplate;
 
 /// Command-line interface for the Piwi tool.
 ///
@@ -14,23 +20,119 @@ pub(super) enum Piwi {
     ///
     /// CREATE2 is an opcode in Ethereum that allows contracts to be deployed
     /// at predetermined addresses.
+    #[command(allow_missing_positional = true)]
     Create2 {
         /// Address of the contract deployer.
+        /// Given with `--deployer` or `PIWI_DEPLOYER` instead, the positional
+        /// arguments after it move up.
         deployer: Address,
 
-        /// Address of the Factory contract. Defaults to the Archanid's Factory.
-        #[clap(short, long)]
+        /// Address of the Factory contract. Defaults to the address of the
+        /// preset.
+        #[clap(short, long, env = "PIWI_FACTORY")]
         factory: Option<Address>,
 
-        /// Hash of the initialization code.
-        init_code_hash: FixedBytes<32>,
+        /// Factory whose salt rules and calling convention are followed.
+        /// Defaults to Arachnid's proxy, or to the first factory of `piwi
+        /// factories` deployed on `--chain`.
+        #[clap(long, env = "PIWI_PRESET", value_enum)]
+        preset: Option<FactoryPreset>,
+
+        /// Chain the contract is deployed on, selecting a factory deployed
+        /// there by default and warning about factories that are not.
+        #[clap(long, env = "PIWI_CHAIN", value_enum)]
+        chain: Option<Chain>,
+
+        /// Well-known factory to deploy with, from the list of `piwi
+        /// factories`, instead of a preset.
+        #[clap(long, value_enum, conflicts_with_all = ["preset", "salt_template"])]
+        factory_name: Option<FactoryName>,
+
+        /// Other factories the salt must give a matching address with, e.g.
+        /// the factory deploying on another chain. Each one multiplies the
+        /// expected number of attempts by the difficulty of the pattern.
+        #[clap(long, value_delimiter = ',')]
+        also_factory: Vec<Address>,
+
+        /// Hashes of other init code any of which may give the matching
+        /// address instead, e.g. of the contract compiled with other
+        /// settings or constructor arguments. The printed salt is labeled
+        /// with the hash it matched with.
+        #[clap(long, value_delimiter = ',')]
+        also_init_code_hash: Vec<FixedBytes<32>>,
+
+        /// Chain the salt is restricted to, for factories able to protect
+        /// salts from being replayed on other chains. Defaults to every
+        /// chain.
+        #[clap(
+            long,
+            required_if_eq_any([("create2_salt", "sender-chain"), ("create2_salt", "chain")])
+        )]
+        chain_id: Option<u64>,
+
+        /// Message the factory hashes the salt in to build the CREATE2 salt,
+        /// as hex with `{salt}` standing for the salt, e.g. `0x<OWNER>{salt}`
+        /// for an ERC-4337 account factory hashing the owner with the salt.
+        #[clap(
+            long,
+            conflicts_with_all = ["preset", "chain_id"],
+            value_parser = crate::salt::parse_salt_template
+        )]
+        salt_template: Option<SaltTemplate>,
+
+        /// How the factory derives the CREATE2 salt from the salt it is
+        /// given, for factories protecting salts their own way instead of
+        /// following a preset.
+        #[clap(
+            long,
+            value_enum,
+            conflicts_with_all = ["preset", "factory_name", "salt_template", "sequential"]
+        )]
+        create2_salt: Option<SaltScheme>,
+
+        /// Chain whose CREATE2 address formula is followed.
+        #[clap(long, env = "PIWI_TARGET", value_enum, default_value_t = Target::Evm)]
+        target: Target,
+
+        #[command(flatten)]
+        init_code: Box<InitCode>,
 
         /// Hex string representing the desired flags.
+        #[clap(env = "PIWI_FLAGS")]
         flags: String,
 
         /// Optional prefix for the mined address.
-        #[clap(short, long)]
+        #[clap(short, long, env = "PIWI_PREFIX")]
         prefix: Option<String>,
+
+        /// Do not embed the deployer address in the first 20 bytes of the
+        /// salt, for factories that do not enforce sender-bound salts or
+        /// deployments done by a relayer.
+        #[clap(long)]
+        no_deployer_prefix: bool,
+
+        /// Explore salts as a plain `uint256` counter starting at zero and
+        /// return the first match, reproducing Uniswap v4-periphery's
+        /// `HookMiner.find` exactly.
+        #[clap(long, conflicts_with_all = ["no_deployer_prefix", "salt_prefix"])]
+        sequential: bool,
+
+        /// Watch the init code file or artifact and mine again each time it
+        /// changes, e.g. while iterating on a hook.
+        #[clap(long, conflicts_with_all = ["dry_run", "checkpoint", "resume"])]
+        watch: bool,
+
+        #[command(flatten)]
+        salt: SaltOptions,
+
+        #[command(flatten)]
+        runtime: Runtime,
+
+        #[command(flatten)]
+        gpu: Gpu,
+
+        #[command(flatten)]
+        output: Output,
     },
 
     /// Mines a CREATE3 salt.
@@ -40,18 +142,1141 @@ pub(super) enum Piwi {
     /// initialization code.
     Create3 {
         /// Address of the contract deployer.
+        /// Given with `--deployer` or `PIWI_DEPLOYER` instead, the positional
+        /// arguments after it move up.
         deployer: Address,
 
-        /// Address of the Factory contract. Defaults to the LayerZero's
-        /// Factory.
-        #[clap(short, long)]
+        #[command(flatten)]
+        factory: Create3Factory,
+
+        /// Hex string representing the desired flags.
+        #[clap(env = "PIWI_FLAGS")]
+        flags: String,
+
+        /// Optional prefix for the mined address. Defaults to an empty string.
+        #[clap(short, long, env = "PIWI_PREFIX")]
+        prefix: Option<String>,
+
+        #[command(flatten)]
+        creation_code: CreationCode,
+
+        #[command(flatten)]
+        salt: SaltOptions,
+
+        #[command(flatten)]
+        runtime: Runtime,
+
+        #[command(flatten)]
+        output: Output,
+    },
+
+    /// Mines the salt nonce of a Safe deployed by the SafeProxyFactory.
+    ///
+    /// `createProxyWithNonce` salts the CREATE2 deployment of the proxy with
+    /// the hash of the initializer followed by the salt nonce, so the owners
+    /// and threshold set up by the initializer are part of the address.
+    SafeProxy {
+        /// Address of the Safe singleton the proxy delegates to.
+        singleton: Address,
+
+        /// Calldata of the `setup` call initializing the Safe.
+        initializer: Bytes,
+
+        /// Hex string representing the desired flags.
+        #[clap(env = "PIWI_FLAGS")]
+        flags: String,
+
+        /// Optional prefix for the mined address.
+        #[clap(short, long, env = "PIWI_PREFIX")]
+        prefix: Option<String>,
+
+        /// Address of the SafeProxyFactory. Defaults to the v1.4.1 factory.
+        #[clap(short, long, env = "PIWI_FACTORY")]
+        factory: Option<Address>,
+
+        /// Creation code of the proxy, as returned by `proxyCreationCode()`
+        /// on the factory.
+        #[clap(long)]
+        proxy_creation_code: Bytes,
+
+        #[command(flatten)]
+        salt: SaltOptions,
+
+        #[command(flatten)]
+        runtime: Runtime,
+    },
+
+    /// Mines the conduit key of a Seaport conduit.
+    ///
+    /// The ConduitController deploys each conduit with CREATE2, using the
+    /// conduit key as the salt, and only lets the account the key starts with
+    /// create it.
+    Conduit {
+        /// Address of the account creating the conduit.
+        /// Given with `--deployer` or `PIWI_DEPLOYER` instead, the positional
+        /// arguments after it move up.
+        deployer: Address,
+
+        /// Hex string representing the desired flags.
+        #[clap(env = "PIWI_FLAGS")]
+        flags: String,
+
+        /// Optional prefix for the mined address.
+        #[clap(short, long, env = "PIWI_PREFIX")]
+        prefix: Option<String>,
+
+        /// Creation code hash of the conduits, as returned first by
+        /// `getConduitCodeHashes()` on the controller. Defaults to the one of
+        /// the canonical Seaport controller.
+        #[clap(long)]
+        conduit_code_hash: Option<FixedBytes<32>>,
+
+        /// Address of the ConduitController. Defaults to the canonical Seaport
+        /// controller, and requires `--conduit-code-hash` otherwise.
+        #[clap(long, requires = "conduit_code_hash")]
+        controller: Option<Address>,
+
+        #[command(flatten)]
+        salt: SaltOptions,
+
+        #[command(flatten)]
+        runtime: Runtime,
+    },
+
+    /// Mines the salt of an ERC-6551 token-bound account.
+    ///
+    /// The registry embeds the salt, the chain id and the token in the
+    /// creation code of the account, so the account is bound to the token
+    /// whichever salt is mined.
+    TokenAccount {
+        /// Address of the account implementation.
+        implementation: Address,
+
+        /// Address of the token contract the account is bound to.
+        token_contract: Address,
+
+        /// Id of the token the account is bound to.
+        token_id: U256,
+
+        /// Hex string representing the desired flags.
+        #[clap(env = "PIWI_FLAGS")]
+        flags: String,
+
+        /// Optional prefix for the mined address.
+        #[clap(short, long, env = "PIWI_PREFIX")]
+        prefix: Option<String>,
+
+        /// Chain the token lives on.
+        #[clap(long, default_value_t = 1)]
+        chain_id: u64,
+
+        /// Address of the ERC-6551 registry. Defaults to the canonical v0.3.1
+        /// registry.
+        #[clap(long)]
+        registry: Option<Address>,
+
+        #[command(flatten)]
+        salt: SaltOptions,
+
+        #[command(flatten)]
+        runtime: Runtime,
+    },
+
+    /// Mines a CREATE2 salt for an implementation, then one for the ERC-1967
+    /// proxy delegating to it.
+    ///
+    /// The address of the implementation is a constructor argument of the
+    /// proxy, so the proxy is only mined once the implementation is.
+    ProxyPair {
+        /// Address of the contract deployer.
+        /// Given with `--deployer` or `PIWI_DEPLOYER` instead, the positional
+        /// arguments after it move up.
+        deployer: Address,
+
+        /// Foundry or Hardhat artifact JSON file of the implementation.
+        #[clap(long)]
+        implementation_artifact: String,
+
+        /// Constructor arguments of the implementation, either as a hex string
+        /// of ABI encoded arguments or as a signature followed by the argument
+        /// values. End the values with `;` when positional arguments follow.
+        #[clap(long, num_args = 1.., value_terminator = ";", allow_negative_numbers = true)]
+        implementation_args: Vec<String>,
+
+        /// Foundry or Hardhat artifact JSON file of the proxy, e.g.
+        /// OpenZeppelin's `ERC1967Proxy`.
+        #[clap(long)]
+        proxy_artifact: String,
+
+        /// Calldata of the initializer the proxy calls on its implementation
+        /// when deployed. Defaults to none.
+        #[clap(long, value_parser = Bytes::from_str)]
+        proxy_data: Option<Bytes>,
+
+        /// Hex string representing the desired flags of both addresses.
+        #[clap(env = "PIWI_FLAGS")]
+        flags: String,
+
+        /// Optional prefix of both addresses.
+        #[clap(short, long, env = "PIWI_PREFIX")]
+        prefix: Option<String>,
+
+        /// Flags of the implementation, if they differ from the proxy's.
+        #[clap(long)]
+        implementation_flags: Option<String>,
+
+        /// Prefix of the implementation, if it differs from the proxy's.
+        #[clap(long)]
+        implementation_prefix: Option<String>,
+
+        /// Address of the Factory contract. Defaults to the Arachnid's Factory.
+        #[clap(short, long, env = "PIWI_FACTORY")]
         factory: Option<Address>,
 
+        #[command(flatten)]
+        salt: SaltOptions,
+
+        #[command(flatten)]
+        runtime: Runtime,
+    },
+
+    /// Mines the deployment nonce of a plain CREATE.
+    ///
+    /// The address of a contract deployed with CREATE only depends on the
+    /// deployer and its nonce, so no factory is needed: the deployer burns
+    /// its nonces up to the found one, e.g. with empty transactions, then
+    /// deploys.
+    Create {
+        /// Address of the account or contract deploying the contract.
+        /// Given with `--deployer` or `PIWI_DEPLOYER` instead, the positional
+        /// arguments after it move up.
+        deployer: Address,
+
+        /// Hex string representing the desired flags.
+        #[clap(env = "PIWI_FLAGS")]
+        flags: String,
+
+        /// Optional prefix for the mined address.
+        #[clap(short, long, env = "PIWI_PREFIX")]
+        prefix: Option<String>,
+
+        /// First nonce to try, usually the current nonce of the deployer.
+        #[clap(long, default_value_t = 0)]
+        start_nonce: u64,
+
+        /// Nonce to stop before, bounding how many nonces can be burned.
+        /// Defaults to no bound.
+        #[clap(long)]
+        end_nonce: Option<u64>,
+    },
+
+    /// Mines a deployer key pair whose CREATE address matches the pattern.
+    ///
+    /// Random accounts are tried until the contract one deploys at the given
+    /// nonce has a matching address, which needs no factory on the chain.
+    /// The private key is written to a new file only readable by the current
+    /// user, and never printed.
+    Keypair {
         /// Hex string representing the desired flags.
+        #[clap(env = "PIWI_FLAGS")]
         flags: String,
 
+        /// Optional prefix for the mined address.
+        #[clap(short, long, env = "PIWI_PREFIX")]
+        prefix: Option<String>,
+
+        /// Nonce the new account deploys the contract with. Defaults to its
+        /// first transaction.
+        #[clap(long, default_value_t = 0)]
+        nonce: u64,
+
+        /// File to write the private key to, which must not exist yet.
+        #[clap(long)]
+        key_file: String,
+    },
+
+    /// Mines the salt of a JSON job file and prints the result as JSON.
+    ///
+    /// The job holds the deployment method, its derivation inputs and the
+    /// pattern, e.g. `{"method": "create2", "deployer": "0x…", "factory":
+    /// "0x…", "init_code_hash": "0x…", "flags": "0x0080"}`, and the result
+    /// has the salt, address, attempts, elapsed seconds and seed, like
+    /// `--json` prints them.
+    Job {
+        /// JSON file holding the job, or `-` to read it from stdin.
+        path: String,
+
+        #[command(flatten)]
+        runtime: Runtime,
+    },
+
+    /// Interactively builds a mining command.
+    ///
+    /// Walks through the deployment method, factory, init code, hook flags
+    /// and prefix, then prints the equivalent non-interactive command.
+    Init,
+
+    /// Computes the address produced by a previously mined salt.
+    Compute {
+        #[command(subcommand)]
+        method: Compute,
+    },
+
+    /// Verifies that a salt produces the expected address.
+    ///
+    /// Any mismatch is reported along with the differing parts of the address,
+    /// and the process exits with a non-zero status.
+    Verify {
+        #[command(subcommand)]
+        method: Verify,
+    },
+
+    /// Computes the hash of contract creation bytecode.
+    ///
+    /// The printed hash can be passed as is to the `create2` subcommand.
+    HashInitCode {
+        /// File containing the hex or binary creation bytecode, or `-` to read
+        /// it from stdin.
+        file: String,
+
+        /// Constructor arguments appended to the bytecode, either as a hex
+        /// string of ABI encoded arguments or as a signature followed by the
+        /// argument values, e.g. `'(address,uint24)' 0x... 3000`. End the
+        /// values with `;` when positional arguments follow.
+        #[clap(
+            short,
+            long,
+            num_args = 1..,
+            value_terminator = ";",
+            allow_negative_numbers = true
+        )]
+        constructor_args: Vec<String>,
+    },
+
+    /// Estimates how long mining a pattern will take.
+    ///
+    /// The difficulty of the pattern is combined with a short hashrate
+    /// measurement of each miner type on this machine.
+    Estimate {
+        /// Hex string representing the desired flags.
+        #[clap(short, long)]
+        flags: Option<String>,
+
         /// Optional prefix for the mined address. Defaults to an empty string.
         #[clap(short, long)]
         prefix: Option<String>,
     },
+
+    /// Measures CREATE2 and CREATE3 hashrates across thread counts.
+    Bench {
+        /// Largest number of threads to measure. Defaults to the number of
+        /// available cores.
+        #[clap(short, long, env = "PIWI_THREADS")]
+        threads: Option<usize>,
+
+        /// Duration of each measurement in milliseconds.
+        #[clap(short, long, default_value_t = 1000)]
+        duration: u64,
+
+        /// Only measure the hashrates of the Keccak implementations, on a
+        /// single thread.
+        #[clap(long)]
+        hashers: bool,
+    },
+
+    /// Prints the presigned transaction deploying Arachnid's deterministic
+    /// deployment proxy, and the funding it needs, for chains missing it.
+    ///
+    /// The transaction is not replay protected, so it deploys the proxy at
+    /// the same address on any chain whose nodes accept it.
+    FactoryTx,
+
+    /// Lists the CPU and GPUs available to mine on, with a short CREATE2
+    /// hashrate probe of each.
+    Devices {
+        /// Duration of each probe in milliseconds.
+        #[clap(short, long, default_value_t = 500)]
+        duration: u64,
+    },
+
+    /// Lists the well-known deterministic deployment factories that can be
+    /// selected with `--factory-name`.
+    Factories,
+
+    /// Lists past mining runs or shows the details of one of them.
+    ///
+    /// Every successful run is recorded in `$PIWI_HOME/sessions.jsonl`,
+    /// `~/.piwi/sessions.jsonl` by default.
+    Sessions {
+        #[command(subcommand)]
+        command: Option<Sessions>,
+    },
+
+    /// Generates a shell completion script.
+    ///
+    /// The script is written to stdout and can be sourced by the shell, e.g.
+    /// `piwi completions bash > /etc/bash_completion.d/piwi`.
+    Completions {
+        /// Shell to generate completions for.
+        shell: Shell,
+    },
+}
+
+/// Subcommands taking the deployer as their first positional argument.
+const DEPLOYER_FIRST: [&str; 5] = ["create2", "create3", "conduit", "proxy-pair", "create"];
+
+impl Piwi {
+    /// Parses the command line of the process.
+    pub(super) fn parse_args() -> Self {
+        let args: Vec<OsString> = env::args_os().collect();
+        let matches = Self::matches_from(args, env::var_os("PIWI_DEPLOYER").is_some())
+            .unwrap_or_else(|err| err.exit());
+        Self::from_arg_matches(&matches).unwrap_or_else(|err| err.exit())
+    }
+
+    /// Matches a command line against the parser.
+    ///
+    /// A deployer given with `--deployer` or `PIWI_DEPLOYER` takes the place
+    /// of the `<DEPLOYER>` positional argument, so the positional arguments
+    /// after it move up instead of being read as the deployer. The variable
+    /// only does so when the command line does not parse with a positional
+    /// deployer, which then takes precedence.
+    ///
+    /// # Arguments
+    /// * `args` - The command line, starting with the binary name.
+    /// * `deployer_env` - Whether `PIWI_DEPLOYER` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns the clap error of the command line, to be printed on exit.
+    pub(super) fn matches_from(
+        args: Vec<OsString>,
+        deployer_env: bool,
+    ) -> clap::error::Result<ArgMatches> {
+        let named_deployer = args.iter().any(|arg| {
+            arg.to_str()
+                .is_some_and(|arg| arg == "--deployer" || arg.starts_with("--deployer="))
+        });
+
+        // Keep a deployer given positionally next to the variable
+        if deployer_env
+            && !named_deployer
+            && let Ok(matches) = Self::command_with(false).try_get_matches_from(&args)
+        {
+            return Ok(matches);
+        }
+        Self::command_with(named_deployer || deployer_env).try_get_matches_from(args)
+    }
+
+    /// Builds the command line parser, taking the deployer of the mining
+    /// subcommands with `--deployer` instead of positionally if asked.
+    pub(super) fn command_with(named_deployer: bool) -> Command {
+        let mut command = Self::command();
+        if named_deployer {
+            for name in DEPLOYER_FIRST {
+                command = command.mut_subcommand(name, |subcommand| {
+                    subcommand.mut_arg("deployer", |arg| arg.long("deployer").env("PIWI_DEPLOYER"))
+                });
+            }
+        }
+        command
+    }
+}
+
+/// Initialization code of a CREATE2 deployment.
+#[derive(Clone, Debug, clap::Args)]
+pub(super) struct InitCode {
+    #[command(flatten)]
+    pub(super) source: InitCodeSource,
+
+    /// Constructor arguments appended to the creation code before hashing,
+    /// either as a hex string of ABI encoded arguments or as a signature
+    /// followed by the argument values, e.g. `'(address,uint24)' 0x... 3000`.
+    /// End the values with `;` when positional arguments follow.
+    #[clap(
+        short,
+        long,
+        num_args = 1..,
+        value_terminator = ";",
+        allow_negative_numbers = true,
+        conflicts_with_all = ["init_code_hash", "clone_of"]
+    )]
+    pub(super) constructor_args: Vec<String>,
+
+    /// Implementation of an ERC-1967 proxy, e.g. OpenZeppelin's
+    /// `ERC1967Proxy` given by `--artifact`, encoded with `--proxy-data` as
+    /// the constructor arguments of the proxy.
+    #[clap(
+        long,
+        group = "proxy_target",
+        conflicts_with_all = ["constructor_args", "init_code_hash", "clone_of"]
+    )]
+    pub(super) proxy_implementation: Option<Address>,
+
+    /// Beacon of a beacon proxy, e.g. OpenZeppelin's `BeaconProxy` given by
+    /// `--artifact`, encoded with `--proxy-data` as the constructor arguments
+    /// of the proxy.
+    #[clap(
+        long,
+        group = "proxy_target",
+        conflicts_with_all = ["constructor_args", "init_code_hash", "clone_of"]
+    )]
+    pub(super) proxy_beacon: Option<Address>,
+
+    /// Calldata of the initializer the proxy calls on its implementation
+    /// when deployed. Defaults to none.
+    #[clap(long, requires = "proxy_target", value_parser = Bytes::from_str)]
+    pub(super) proxy_data: Option<Bytes>,
+}
+
+/// CREATE3 factory and the derivation it follows.
+#[derive(Clone, Debug, clap::Args)]
+pub(super) struct Create3Factory {
+    /// Address of the Factory contract. Defaults to the factory of the
+    /// variant.
+    #[clap(short, long, env = "PIWI_FACTORY")]
+    pub(super) factory: Option<Address>,
+
+    /// Library or factory whose CREATE3 derivation is followed. Defaults to
+    /// LayerZero's factory, or to the first CREATE3 factory of `piwi
+    /// factories` deployed on `--chain`.
+    #[clap(long, env = "PIWI_CREATE3_VARIANT", value_enum)]
+    pub(super) create3_variant: Option<Create3Variant>,
+
+    /// Chain the contract is deployed on, selecting a factory deployed there
+    /// by default and warning about factories that are not.
+    #[clap(long, env = "PIWI_CHAIN", value_enum)]
+    pub(super) chain: Option<Chain>,
+
+    /// Well-known factory to deploy with, from the list of `piwi factories`,
+    /// instead of a variant.
+    #[clap(long, value_enum, conflicts_with = "create3_variant")]
+    pub(super) factory_name: Option<FactoryName>,
+
+    /// How the factory derives the proxy salt from the salt it is given.
+    /// Defaults to the scheme of the variant.
+    #[clap(long, value_enum)]
+    pub(super) create3_salt: Option<SaltScheme>,
+
+    /// Chain id hashed into the proxy salt by `--create3-salt sender-chain`
+    /// or `chain`.
+    #[clap(
+        long,
+        required_if_eq_any([("create3_salt", "sender-chain"), ("create3_salt", "chain")])
+    )]
+    pub(super) chain_id: Option<u64>,
+
+    /// Hash of the init code of the proxy deployed with CREATE2, for
+    /// factories with a nonstandard proxy. Defaults to the one of the
+    /// variant.
+    #[clap(long, env = "PIWI_PROXY_INIT_CODE_HASH")]
+    pub(super) proxy_init_code_hash: Option<FixedBytes<32>>,
+
+    /// Nonce the proxy deploys the contract at with CREATE. Fresh proxies
+    /// start at 1.
+    #[clap(long, default_value_t = 1)]
+    pub(super) proxy_nonce: u64,
+}
+
+/// Chain whose CREATE2 address formula a known init code hash follows.
+#[derive(Clone, Debug, clap::Args)]
+pub(super) struct TargetChain {
+    /// Chain whose CREATE2 address formula is followed.
+    #[clap(long, env = "PIWI_TARGET", value_enum, default_value_t = Target::Evm)]
+    pub(super) target: Target,
+
+    /// Keccak256 hash of the ABI encoded constructor arguments on zkSync Era.
+    /// Defaults to the hash of no arguments.
+    #[clap(long)]
+    pub(super) constructor_input_hash: Option<FixedBytes<32>>,
+}
+
+/// Creation code of a CREATE3 deployment, which does not affect the address
+/// but is needed by the emitted deployment files.
+#[derive(Clone, Debug, clap::Args)]
+pub(super) struct CreationCode {
+    /// Foundry or Hardhat artifact JSON file of the deployed contract.
+    #[clap(short, long)]
+    pub(super) artifact: Option<String>,
+
+    /// Constructor arguments appended to the creation code, either as a hex
+    /// string of ABI encoded arguments or as a signature followed by the
+    /// argument values. End the values with `;` when positional arguments
+    /// follow.
+    #[clap(
+        short,
+        long,
+        num_args = 1..,
+        value_terminator = ";",
+        allow_negative_numbers = true,
+        requires = "artifact"
+    )]
+    pub(super) constructor_args: Vec<String>,
+}
+
+/// Options controlling which salt bytes are explored while mining.
+#[derive(Clone, Debug, clap::Args)]
+pub(super) struct SaltOptions {
+    /// Bytes pinned right after the deployer address in the salt, given as a
+    /// `0x` prefixed hex string or as text, e.g. a project tag.
+    #[clap(long, env = "PIWI_SALT_PREFIX", value_parser = crate::salt::parse_salt_prefix)]
+    pub(super) salt_prefix: Option<Bytes>,
+
+    /// First nonce explored for each random segment of the salt.
+    #[clap(long, default_value_t = 0)]
+    pub(super) start_nonce: u64,
+
+    /// Nonce the exploration of each random segment stops before. Defaults to
+    /// the whole nonce segment.
+    #[clap(long)]
+    pub(super) end_nonce: Option<u64>,
+
+    /// Explore the random segments of the salt in order instead of drawing
+    /// them at random, so that every salt is tried at most once and mining
+    /// stops with an error once none is left.
+    #[clap(long)]
+    pub(super) exhaustive: bool,
+}
+
+/// Options controlling how mining is run.
+#[derive(Clone, Debug, clap::Args)]
+pub(super) struct Runtime {
+    /// Number of mining threads. Defaults to the number of available cores.
+    #[clap(short, long, env = "PIWI_THREADS")]
+    pub(super) threads: Option<usize>,
+
+    /// Print the derivation inputs, salt layout and pattern difficulty, then
+    /// exit without mining.
+    #[clap(long)]
+    pub(super) dry_run: bool,
+
+    /// Periodically write the mining progress to this file, so that the run
+    /// can be continued with `--resume` after a crash or reboot.
+    #[clap(long)]
+    pub(super) checkpoint: Option<String>,
+
+    /// Continue the run saved in this checkpoint file, which keeps being
+    /// updated unless `--checkpoint` points elsewhere.
+    #[clap(long)]
+    pub(super) resume: Option<String>,
+
+    /// Minimum number of seconds between two checkpoint writes.
+    #[clap(long, default_value_t = 60)]
+    pub(super) checkpoint_interval: u64,
+
+    /// Do not record the run in the sessions history.
+    #[clap(long, env = "PIWI_NO_HISTORY")]
+    pub(super) no_history: bool,
+
+    /// Percentage of the time each mining thread spends hashing, from 1 to
+    /// 100. Lower values leave CPU time to other programs, e.g. to mine in the
+    /// background on a workstation.
+    #[clap(
+        long,
+        default_value = "100",
+        value_parser = clap::value_parser!(u8).range(1..=100).try_map(NonZeroU8::try_from)
+    )]
+    pub(super) max_cpu: NonZeroU8,
+
+    /// Keccak implementation to hash with.
+    #[clap(long, env = "PIWI_HASHER", value_enum, default_value_t = Hasher::Auto)]
+    pub(super) hasher: Hasher,
+
+    /// Pin each mining thread to its own CPU core, which steadies the
+    /// hashrate. Only supported on Linux.
+    #[clap(long)]
+    pub(super) pin_cores: bool,
+
+    /// Only use one logical CPU per physical core, leaving SMT siblings idle.
+    /// Defaults the number of threads to the number of physical cores.
+    #[clap(long, requires = "pin_cores")]
+    pub(super) skip_smt: bool,
+
+    /// Group the pinned threads by NUMA node, each group searching its own
+    /// share of the salts first, to limit the traffic between sockets.
+    #[clap(long, requires = "pin_cores")]
+    pub(super) numa: bool,
+
+    /// Periodically print the number of attempts and the hashrate to stderr.
+    #[clap(long)]
+    pub(super) stats: bool,
+
+    /// Number of seconds between two stats reports.
+    #[clap(long, default_value_t = 5, requires = "stats")]
+    pub(super) stats_interval: u64,
+
+    /// Also report the hashrate of each mining thread, e.g. to spot throttled
+    /// cores or an over-subscribed machine.
+    #[clap(long, requires = "stats")]
+    pub(super) stats_per_thread: bool,
+
+    /// Do not log the Keccak implementation picked by `--hasher auto` at
+    /// startup. Implied by `--json`.
+    #[clap(long)]
+    pub(super) quiet: bool,
+
+    /// Give up mining after this many seconds, reporting the number of
+    /// attempts made, as Ctrl-C does.
+    #[clap(long)]
+    pub(super) timeout: Option<u64>,
+}
+
+/// Options selecting the GPUs to mine on.
+#[derive(Clone, Debug, clap::Args)]
+pub(super) struct Gpu {
+    /// Mine on every GPU found through OpenCL instead of the CPU, unless
+    /// `--hybrid` is set, streaming only the matching salts back.
+    #[clap(long)]
+    pub(super) gpu: bool,
+
+    /// Mine only on the GPUs with these indices, in the order they are
+    /// listed when mining starts, e.g. `0,2`. Implies `--gpu`.
+    #[clap(long, value_delimiter = ',')]
+    pub(super) gpu_devices: Vec<usize>,
+
+    /// Keep the CPU workers mining alongside the GPUs, each claiming its own
+    /// nonces of the chunks. Has no effect without GPUs.
+    #[clap(long)]
+    pub(super) hybrid: bool,
+}
+
+/// Options controlling how mining results are printed.
+#[derive(Clone, Debug, clap::Args)]
+pub(super) struct Output {
+    /// Format of the printed salt.
+    #[clap(long, env = "PIWI_SALT_FORMAT", value_enum, default_value_t = SaltFormat::Factory)]
+    pub(super) salt_format: SaltFormat,
+
+    /// Solidity file the salt and address are written to as constants of a
+    /// library named after the file, e.g. `Salts.sol`. Constants from other
+    /// runs are kept.
+    #[clap(long)]
+    pub(super) emit_solidity: Option<String>,
+
+    /// Name of the emitted address constant, suffixed with `_SALT` for the
+    /// salt. Defaults to the contract name of the artifact, e.g. `MY_HOOK`.
+    #[clap(long, requires = "emit_solidity")]
+    pub(super) solidity_name: Option<String>,
+
+    /// Foundry script deploying the contract through the factory and checking
+    /// that it lands at the mined address, e.g. `script/Deploy.s.sol`.
+    #[clap(long)]
+    pub(super) emit_foundry_script: Option<String>,
+
+    /// Print the `cast send` command performing the deployment, or write it
+    /// to the given file.
+    #[clap(long, num_args = 0..=1, default_missing_value = "-", value_name = "FILE")]
+    pub(super) emit_cast: Option<String>,
+
+    /// Safe{Wallet} Transaction Builder JSON file with the factory call
+    /// performing the deployment, to queue it from a multisig.
+    #[clap(long)]
+    pub(super) emit_safe_tx: Option<String>,
+
+    /// Chain id of the emitted Safe transaction batch.
+    #[clap(long, default_value_t = 1, requires = "emit_safe_tx")]
+    pub(super) safe_chain_id: u64,
+
+    /// Print the result as a JSON object with the salt, address, attempts,
+    /// elapsed seconds and seed, instead of aligned fields.
+    #[clap(long)]
+    pub(super) json: bool,
+}
+
+/// Implementations of Keccak-f[1600] the miner can hash with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(super) enum Hasher {
+    /// The fastest implementation available on this machine.
+    #[default]
+    Auto,
+    /// Several states at once with AVX-512, AVX2 or NEON.
+    Simd,
+    /// One state at a time with the CRYPTOGAMS assembly, in builds with the
+    /// `asm` feature.
+    Asm,
+    /// One state at a time in portable Rust.
+    Portable,
+}
+
+/// CREATE2 factories with built-in support.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(super) enum FactoryPreset {
+    /// Arachnid's deterministic deployment proxy, called with the salt
+    /// followed by the creation code.
+    Arachnid,
+    /// CreateX, whose `deployCreate2` hashes the salt with the caller, and
+    /// with the chain id given by `--chain-id`.
+    #[value(name = "createx")]
+    CreateX,
+    /// 0age's ImmutableCreate2Factory, whose `safeCreate2` only accepts
+    /// salts starting with the caller or with 20 zero bytes.
+    #[value(name = "immutable")]
+    ImmutableCreate2,
+    /// Safe's singleton factory, deployed with the same code as Arachnid's
+    /// proxy on chains where that one cannot be.
+    #[value(name = "safe")]
+    SafeSingleton,
+    /// The ERC-2470 SingletonFactory, whose `deploy` takes the init code
+    /// before the salt and uses the salt as is.
+    #[value(name = "eip2470")]
+    Eip2470,
+}
+
+/// CREATE3 derivations with built-in support.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(super) enum Create3Variant {
+    /// LayerZero's factory, which salts the proxy with the hash of the
+    /// caller followed by the salt.
+    #[value(name = "layerzero")]
+    LayerZero,
+    /// The original create3-factory by zeframlou, which LayerZero's is
+    /// derived from and whose derivation it shares.
+    Zeframlou,
+    /// Solady's CREATE3 library, which salts the proxy with the salt as is,
+    /// leaving it to the factory using it to protect salts.
+    Solady,
+    /// Axelar's `Create3Deployer`, which salts its `CreateDeploy` proxy with
+    /// the hash of the ABI encoded caller and salt.
+    Axelar,
+    /// Metamorphic contract factories, which deploy a transient contract
+    /// with a salt starting with the caller, and have it deploy the contract
+    /// with CREATE.
+    Metamorphic,
+}
+
+/// Well-known deterministic deployment factories, each with its address and
+/// the derivation and salt rules it follows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(super) enum FactoryName {
+    /// Arachnid's deterministic deployment proxy.
+    Arachnid,
+    /// Safe's singleton factory.
+    SafeSingleton,
+    /// 0age's ImmutableCreate2Factory.
+    #[value(name = "immutable-create2")]
+    ImmutableCreate2,
+    /// CreateX.
+    #[value(name = "createx")]
+    CreateX,
+    /// The ERC-2470 SingletonFactory.
+    #[value(name = "eip2470")]
+    Eip2470,
+    /// LayerZero's CREATE3 factory.
+    #[value(name = "layerzero-create3")]
+    LayerZeroCreate3,
+    /// zeframlou's CREATE3 factory.
+    ZeframlouCreate3,
+    /// A CREATE3 factory built on Solady's library, deployed by the user.
+    SoladyCreate3,
+    /// Axelar's `Create3Deployer`.
+    AxelarCreate3,
+}
+
+/// Chains whose factory deployments are known to the registry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(super) enum Chain {
+    /// Ethereum mainnet.
+    Mainnet,
+    /// The Sepolia testnet.
+    Sepolia,
+    /// Base.
+    Base,
+    /// Arbitrum One.
+    Arbitrum,
+    /// OP Mainnet.
+    Optimism,
+    /// Polygon PoS.
+    Polygon,
+    /// BNB Smart Chain.
+    Bsc,
+}
+
+/// Chains with their own CREATE2 address formula.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(super) enum Target {
+    /// Ethereum and the chains following its formula.
+    Evm,
+    /// zkSync Era, which hashes the bytecode hash and the hash of the
+    /// constructor input separately.
+    Zksync,
+}
+
+/// Schemes deriving the salt a factory deploys with, the CREATE2 salt or the
+/// CREATE3 proxy salt, from the salt given to the factory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(super) enum SaltScheme {
+    /// The salt is used as is.
+    Raw,
+    /// `keccak256(abi.encodePacked(msg.sender, salt))`.
+    Sender,
+    /// `keccak256(abi.encode(msg.sender, salt))`.
+    SenderAbi,
+    /// `keccak256(abi.encodePacked(msg.sender, block.chainid, salt))`, with
+    /// the chain id given by `--chain-id`.
+    SenderChain,
+    /// `keccak256(abi.encodePacked(block.chainid, salt))`, with the chain id
+    /// given by `--chain-id`, for factories scoping salts to a chain but not
+    /// to the caller.
+    Chain,
+    /// The salt is used as is, and must start with `msg.sender`.
+    Caller,
+}
+
+/// Formats in which a mined salt can be printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(super) enum SaltFormat {
+    /// The 32-byte salt passed to the factory contract.
+    Factory,
+    /// The 32-byte salt seen by the CREATE2 opcode, after any hashing done by
+    /// the factory.
+    Raw,
+    /// The buffer the factory builds from the salt before hashing it, e.g. the
+    /// 52-byte deployer and salt concatenation of CREATE3.
+    Internal,
+    /// A `bytes32` Solidity literal of the factory salt.
+    Solidity,
+}
+
+/// Sources of the initialization code of a CREATE2 deployment.
+///
+/// Exactly one source has to be given.
+#[derive(Clone, Debug, clap::Args)]
+#[group(required = true, multiple = false)]
+pub(super) struct InitCodeSource {
+    /// Hash of the initialization code.
+    pub(super) init_code_hash: Option<FixedBytes<32>>,
+
+    /// Hex string of the contract creation code, hashed internally instead of
+    /// passing its hash.
+    #[clap(short, long, value_parser = Bytes::from_str)]
+    pub(super) init_code: Option<Bytes>,
+
+    /// File containing the hex or binary contract creation code, or `-` to
+    /// read it from stdin.
+    #[clap(long)]
+    pub(super) init_code_file: Option<String>,
+
+    /// Foundry or Hardhat artifact JSON file, e.g.
+    /// `out/MyHook.sol/MyHook.json`, whose creation code is hashed.
+    #[clap(short, long)]
+    pub(super) artifact: Option<String>,
+
+    /// Address of an implementation contract whose ERC-1167 minimal proxy is
+    /// deployed, e.g. by OpenZeppelin's `Clones.cloneDeterministic` in the
+    /// factory.
+    #[clap(long)]
+    pub(super) clone_of: Option<Address>,
+}
+
+/// Actions on the recorded mining sessions.
+#[derive(Clone, Debug, clap::Subcommand)]
+pub(super) enum Sessions {
+    /// Lists the recorded sessions.
+    List,
+
+    /// Shows the inputs and result of a recorded session.
+    Show {
+        /// Id of the session, as listed by `piwi sessions`.
+        id: u64,
+    },
+}
+
+/// Deployment methods whose resulting address can be computed from a salt.
+#[derive(Clone, Debug, clap::Subcommand)]
+pub(super) enum Compute {
+    /// Computes a CREATE2 address.
+    Create2 {
+        /// Address of the Factory contract. Defaults to the Archanid's Factory.
+        #[clap(short, long, env = "PIWI_FACTORY")]
+        factory: Option<Address>,
+
+        /// Hash of the initialization code, or of the bytecode on zkSync Era.
+        init_code_hash: FixedBytes<32>,
+
+        /// Salt passed to the Factory contract.
+        #[clap(short, long)]
+        salt: FixedBytes<32>,
+
+        #[command(flatten)]
+        target: TargetChain,
+    },
+
+    /// Computes a CREATE3 address.
+    Create3 {
+        /// Address of the contract deployer.
+        #[clap(env = "PIWI_DEPLOYER")]
+        deployer: Address,
+
+        #[command(flatten)]
+        factory: Create3Factory,
+
+        /// Salt passed to the Factory contract.
+        #[clap(short, long)]
+        salt: FixedBytes<32>,
+    },
+}
+
+/// Deployment methods whose salts can be verified.
+#[derive(Clone, Debug, clap::Subcommand)]
+pub(super) enum Verify {
+    /// Verifies a CREATE2 salt.
+    Create2 {
+        /// Address of the Factory contract. Defaults to the Archanid's Factory.
+        #[clap(short, long, env = "PIWI_FACTORY")]
+        factory: Option<Address>,
+
+        /// Hash of the initialization code, or of the bytecode on zkSync Era.
+        init_code_hash: FixedBytes<32>,
+
+        /// Salt passed to the Factory contract.
+        #[clap(short, long)]
+        salt: FixedBytes<32>,
+
+        #[command(flatten)]
+        target: TargetChain,
+
+        #[command(flatten)]
+        expected: Expected,
+    },
+
+    /// Verifies a CREATE3 salt.
+    Create3 {
+        /// Address of the contract deployer.
+        #[clap(env = "PIWI_DEPLOYER")]
+        deployer: Address,
+
+        #[command(flatten)]
+        factory: Create3Factory,
+
+        /// Salt passed to the Factory contract.
+        #[clap(short, long)]
+        salt: FixedBytes<32>,
+
+        #[command(flatten)]
+        expected: Expected,
+    },
+}
+
+/// Expected properties of the address produced by a salt.
+#[derive(Clone, Debug, clap::Args)]
+pub(super) struct Expected {
+    /// Address the salt is expected to produce.
+    #[clap(short, long)]
+    pub(super) address: Address,
+
+    /// Hex string representing the flags the address should carry.
+    #[clap(long)]
+    pub(super) flags: Option<String>,
+
+    /// Prefix the address should start with.
+    #[clap(short, long)]
+    pub(super) prefix: Option<String>,
+}
+
+#[test]
+fn test_cli() {
+    let deployer = "0x1111111111111111111111111111111111111111";
+    let hash = "0x2222222222222222222222222222222222222222222222222222222222222222";
+
+    // A named deployer moves the init code hash and flags up
+    for named_deployer in [false, true] {
+        let args = if named_deployer {
+            vec!["piwi", "create2", "--deployer", deployer, hash, "0x2fff"]
+        } else {
+            vec!["piwi", "create2", deployer, hash, "0x2fff"]
+        };
+        let matches = Piwi::command_with(named_deployer).get_matches_from(args);
+        let Piwi::Create2 {
+            deployer: parsed,
+            init_code,
+            flags,
+            ..
+        } = Piwi::from_arg_matches(&matches).unwrap()
+        else {
+            panic!("the subcommand is create2");
+        };
+        assert_eq!(parsed, deployer.parse::<Address>().unwrap());
+        assert_eq!(init_code.source.init_code_hash, Some(hash.parse().unwrap()));
+        assert_eq!(flags, "0x2fff");
+    }
+
+    // A deployer given positionally is kept when `PIWI_DEPLOYER` is set
+    let args = ["piwi", "create2", deployer, hash, "0x2fff"].map(OsString::from);
+    let matches = Piwi::matches_from(args.to_vec(), true).unwrap();
+    let Piwi::Create2 {
+        deployer: parsed,
+        init_code,
+        flags,
+        ..
+    } = Piwi::from_arg_matches(&matches).unwrap()
+    else {
+        panic!("the subcommand is create2");
+    };
+    assert_eq!(parsed, deployer.parse::<Address>().unwrap());
+    assert_eq!(init_code.source.init_code_hash, Some(hash.parse().unwrap()));
+    assert_eq!(flags, "0x2fff");
+
+    // Constructor arguments end at `;` or the next option, leaving the
+    // positional arguments after them alone
+    let args = [
+        "piwi",
+        "create2",
+        "--artifact",
+        "a.json",
+        "-c",
+        "(address,int24)",
+        deployer,
+        "-60",
+        ";",
+        deployer,
+        "0x2fff",
+        "--dry-run",
+    ];
+    let matches = Piwi::command_with(false).get_matches_from(args);
+    let Piwi::Create2 {
+        deployer: parsed,
+        init_code,
+        flags,
+        ..
+    } = Piwi::from_arg_matches(&matches).unwrap()
+    else {
+        panic!("the subcommand is create2");
+    };
+    assert_eq!(
+        init_code.constructor_args,
+        ["(address,int24)", deployer, "-60"]
+    );
+    assert_eq!(parsed, deployer.parse::<Address>().unwrap());
+    assert_eq!(flags, "0x2fff");
+    let args = [
+        "piwi",
+        "create3",
+        "--artifact",
+        "a.json",
+        "-c",
+        "0x12",
+        "--dry-run",
+        deployer,
+        "0x2fff",
+    ];
+    let matches = Piwi::command_with(false).get_matches_from(args);
+    let Piwi::Create3 {
+        creation_code,
+        flags,
+        ..
+    } = Piwi::from_arg_matches(&matches).unwrap()
+    else {
+        panic!("the subcommand is create3");
+    };
+    assert_eq!(creation_code.constructor_args, ["0x12"]);
+    assert_eq!(flags, "0x2fff");
+
+    // Only controllers other than Seaport's need their conduit code hash
+    let conduit = |args: &[&str]| {
+        let args = [&["piwi", "conduit", deployer, "0x2fff"], args].concat();
+        Piwi::command_with(false).try_get_matches_from(args)
+    };
+    assert!(conduit(&[]).is_ok());
+    assert!(conduit(&["--controller", deployer]).is_err());
+    assert!(conduit(&["--controller", deployer, "--conduit-code-hash", hash]).is_ok());
 }
@@ -0,0 +1,291 @@
+//! Address derivations and pattern matching, free of the standard library.
+//!
+//! Everything here is plain computation over fixed-size values, so it also
+//! builds for `no_std` targets, e.g. embedded devices or WASM, with the
+//! default `std` feature disabled. The miners check their results with the
+//! same functions.
+
+extern crate alloc;
+
+use ::core::{error, fmt};
+use alloc::string::{String, ToString};
+
+use alloy_primitives::{Address, B256, address};
+
+/// Bitmask that isolates the lower 14 bits of an Ethereum address.
+pub(super) const FLAGS_MASK: Address = address!("0x0000000000000000000000000000000000003fFF");
+
+/// Number of hex characters in an Ethereum address.
+const ADDRESS_NIBBLES: usize = 40;
+
+/// Computes the address a factory deploys a contract to with CREATE2.
+///
+/// # Arguments
+/// * `factory` - The address of the contract running CREATE2.
+/// * `salt` - The salt given to CREATE2.
+/// * `init_code_hash` - The keccak256 hash of the init code of the contract.
+pub fn create2_address(factory: Address, salt: B256, init_code_hash: B256) -> Address {
+    factory.create2(salt, init_code_hash)
+}
+
+/// Computes the address a factory deploys a contract to with CREATE3, where
+/// the factory deploys a proxy with CREATE2, which in turn deploys the
+/// contract with CREATE.
+///
+/// # Arguments
+/// * `factory` - The address of the contract deploying the proxy.
+/// * `proxy_salt` - The salt the proxy is deployed with.
+/// * `proxy_init_code_hash` - The keccak256 hash of the init code of the
+///   proxy.
+/// * `proxy_nonce` - The nonce of the proxy when it deploys the contract.
+pub fn create3_address(
+    factory: Address,
+    proxy_salt: B256,
+    proxy_init_code_hash: B256,
+    proxy_nonce: u64,
+) -> Address {
+    create2_address(factory, proxy_salt, proxy_init_code_hash).create(proxy_nonce)
+}
+
+/// Validated flags and prefix that a mined address has to match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pattern {
+    /// Flags expected in the lower 14 bits of the address.
+    pub(super) flags: Address,
+    /// Prefix expected in the upper bits of the address, padded with zeros.
+    pub(super) prefix: Address,
+    /// Bitmask that isolates the bits covered by the prefix.
+    pub(super) prefix_mask: Address,
+}
+
+impl Pattern {
+    /// Parses and validates a pattern from the flags and prefix hex strings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either string is malformed, or if the prefix
+    /// overlaps the flag bits with a different value, which would make the
+    /// pattern impossible to mine.
+    pub fn parse(flags: &str, prefix: &str) -> Result<Self, PatternError> {
+        let flags = parse_flags(flags)?;
+        let (prefix, prefix_mask) = parse_prefix(prefix)?;
+
+        // A long prefix reaches into the flag bits, where it must agree with
+        // the flags
+        if prefix.bit_and(FLAGS_MASK) != flags.bit_and(prefix_mask) {
+            return Err(PatternError::ConflictingPattern);
+        }
+
+        Ok(Self {
+            flags,
+            prefix,
+            prefix_mask,
+        })
+    }
+
+    /// Compiles the pattern to the words checked against candidate addresses.
+    pub fn matcher(&self) -> Matcher {
+        Matcher::new(
+            FLAGS_MASK.bit_or(self.prefix_mask),
+            self.flags.bit_or(self.prefix),
+        )
+    }
+}
+
+/// Offsets of the 64-bit words covering an address, the last one overlapping
+/// the middle one so that every word is a single load.
+const MATCHER_WORDS: [usize; 3] = [0, 8, 12];
+
+/// Pattern compiled to masked 64-bit words of the address.
+///
+/// Checking a candidate takes three word loads and comparisons, instead of
+/// building and comparing masked copies of the address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Matcher {
+    /// Bits of each word constrained by the flags or the prefix.
+    masks: [u64; 3],
+    /// Expected value of the constrained bits of each word.
+    values: [u64; 3],
+}
+
+impl Matcher {
+    /// Creates a matcher accepting the addresses whose bits set in the mask
+    /// equal those of the value.
+    ///
+    /// # Arguments
+    /// * `mask` - The bits of the address constrained by the pattern.
+    /// * `value` - The expected value of the constrained bits.
+    pub fn new(mask: Address, value: Address) -> Self {
+        Self {
+            masks: MATCHER_WORDS.map(|start| load_word(&mask, start)),
+            values: MATCHER_WORDS.map(|start| load_word(&value, start)),
+        }
+    }
+
+    /// Returns whether a candidate address carries the flags and prefix.
+    #[inline]
+    pub fn matches(&self, candidate: &Address) -> bool {
+        // Combine the comparisons without branching on each word
+        (0..3).fold(true, |matches, word| {
+            matches
+                & (load_word(candidate, MATCHER_WORDS[word]) & self.masks[word]
+                    == self.values[word])
+        })
+    }
+}
+
+/// Loads 8 bytes of an address as a native-endian word.
+#[inline]
+fn load_word(address: &Address, start: usize) -> u64 {
+    u64::from_ne_bytes(
+        address[start..start + 8]
+            .try_into()
+            .expect("words are 8 bytes"),
+    )
+}
+
+/// Parses a flags hex string, with an optional `0x` prefix, into an address
+/// holding the flags in its lower 14 bits.
+///
+/// # Errors
+///
+/// Returns an error if the string contains non-hex characters or the flags do
+/// not fit in the flags mask.
+pub(super) fn parse_flags(flags: &str) -> Result<Address, PatternError> {
+    let digits = strip_hex_prefix("flags", flags)?;
+
+    // Leading zeros do not change the value, so only the significant digits
+    // have to fit in the mask
+    let significant = digits.trim_start_matches('0');
+    let value = match u16::from_str_radix(significant, 16) {
+        Ok(value) => value,
+        Err(_) if significant.is_empty() => 0,
+        Err(_) => return Err(PatternError::FlagsTooWide(flags.to_string())),
+    };
+
+    let mut address = Address::ZERO;
+    address[18..20].copy_from_slice(&value.to_be_bytes());
+    if address.bit_and(FLAGS_MASK) != address {
+        return Err(PatternError::FlagsTooWide(flags.to_string()));
+    }
+    Ok(address)
+}
+
+/// Parses a prefix hex string, with an optional `0x` prefix, into the prefix
+/// padded with trailing zeros and the bitmask covering its nibbles.
+///
+/// # Errors
+///
+/// Returns an error if the string contains non-hex characters or is longer
+/// than an address.
+pub(super) fn parse_prefix(prefix: &str) -> Result<(Address, Address), PatternError> {
+    let digits = strip_hex_prefix("prefix", prefix)?;
+    if digits.len() > ADDRESS_NIBBLES {
+        return Err(PatternError::PrefixTooLong(digits.len()));
+    }
+
+    let mut address = Address::ZERO;
+    let mut mask = Address::ZERO;
+    for (index, digit) in digits.bytes().enumerate() {
+        // Fill the high nibble of each byte first, then the low one
+        let value = (digit as char).to_digit(16).expect("digits are validated") as u8;
+        let shift = if index % 2 == 0 { 4 } else { 0 };
+        address[index / 2] |= value << shift;
+        mask[index / 2] |= 0xf << shift;
+    }
+    Ok((address, mask))
+}
+
+/// Strips the optional `0x` prefix of a hex string and checks that the
+/// remaining characters are all hex digits.
+fn strip_hex_prefix<'a>(name: &'static str, value: &'a str) -> Result<&'a str, PatternError> {
+    let offset = if value.starts_with("0x") || value.starts_with("0X") {
+        2
+    } else {
+        0
+    };
+
+    // Point at the first offending character in the original string
+    match value[offset..]
+        .char_indices()
+        .find(|(_, c)| !c.is_ascii_hexdigit())
+    {
+        Some((index, _)) => Err(PatternError::InvalidHexCharacter {
+            name,
+            value: value.to_string(),
+            index: offset + index,
+        }),
+        None => Ok(&value[offset..]),
+    }
+}
+
+/// Reasons flags and a prefix do not make a valid pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// A hex argument contains a character that is not a hex digit.
+    InvalidHexCharacter {
+        /// Name of the argument, e.g. `prefix`.
+        name: &'static str,
+        /// Value given by the user.
+        value: String,
+        /// Byte offset of the offending character.
+        index: usize,
+    },
+    /// The prefix has more nibbles than an address.
+    PrefixTooLong(usize),
+    /// The flags do not fit in the flags mask.
+    FlagsTooWide(String),
+    /// The prefix overlaps the flag bits with a different value.
+    ConflictingPattern,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHexCharacter { name, value, index } => write!(
+                f,
+                "{name} contains the non-hex character {:?}\n  {value}\n  {}^",
+                value[*index..].chars().next().unwrap_or_default(),
+                " ".repeat(*index)
+            ),
+            Self::PrefixTooLong(nibbles) => write!(
+                f,
+                "prefix is {nibbles} nibbles long, but addresses only have 40"
+            ),
+            Self::FlagsTooWide(flags) => write!(
+                f,
+                "flags {flags:?} do not fit in the 14-bit flags mask 0x3fff"
+            ),
+            Self::ConflictingPattern => {
+                f.write_str("prefix overlaps the flag bits with a different value than the flags")
+            }
+        }
+    }
+}
+
+impl error::Error for PatternError {}
+
+#[test]
+fn test_core() {
+    use alloy_primitives::{address, keccak256};
+
+    // Example 1 of EIP-1014
+    let init_code_hash = keccak256([0x00]);
+    let deployed = create2_address(Address::ZERO, B256::ZERO, init_code_hash);
+    assert_eq!(
+        deployed,
+        address!("0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38")
+    );
+    assert_eq!(
+        create3_address(Address::ZERO, B256::ZERO, init_code_hash, 1),
+        deployed.create(1)
+    );
+
+    // Only the masked bits are compared
+    let matcher = Matcher::new(
+        address!("0x00000000000000000000000000000000000000ff"),
+        address!("0x0000000000000000000000000000000000000038"),
+    );
+    assert!(matcher.matches(&deployed));
+    assert!(!matcher.matches(&Address::ZERO));
+}
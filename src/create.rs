@@ -0,0 +1,48 @@
+use std::ops::Range;
+
+use alloy_primitives::Address;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::pattern::Pattern;
+
+/// Number of nonces searched in parallel before moving on to the next ones,
+/// so that the first match is found without exploring far past it.
+const CHUNK_SIZE: u64 = 1 << 16;
+
+/// Finds the first deployment nonce of a range whose CREATE address matches
+/// a pattern.
+///
+/// # Arguments
+/// * `deployer` - The address of the account or contract deploying.
+/// * `nonces` - The nonces to explore.
+/// * `pattern` - The pattern the address has to match.
+pub(super) fn find_nonce(
+    deployer: Address,
+    nonces: Range<u64>,
+    pattern: &Pattern,
+) -> Option<(u64, Address)> {
+    let matcher = pattern.matcher();
+    nonces
+        .clone()
+        .step_by(CHUNK_SIZE as usize)
+        .find_map(|start| {
+            (start..nonces.end.min(start.saturating_add(CHUNK_SIZE)))
+                .into_par_iter()
+                .map(|nonce| (nonce, deployer.create(nonce)))
+                .find_first(|(_, address)| matcher.matches(address))
+        })
+}
+
+#[test]
+fn test_find_nonce() {
+    use alloy_primitives::address;
+
+    let deployer = address!("0x000000000000000000000000000000000000dEaD");
+    let pattern = Pattern::parse("0x0080", "").unwrap();
+    let (nonce, address) = find_nonce(deployer, 5..1 << 20, &pattern).unwrap();
+    assert_eq!(address, deployer.create(nonce));
+    assert!(pattern.matcher().matches(&address));
+
+    // No earlier nonce of the range matches
+    assert_eq!(find_nonce(deployer, 5..nonce, &pattern), None);
+}
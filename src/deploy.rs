@@ -0,0 +1,710 @@
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::{Address, B256, Bytes, FixedBytes, U256, address, hex, keccak256};
+use serde_json::{Value, json};
+
+use crate::{
+    CREATE2_DEFAULT_FACTORY, CREATE3_DEFAULT_FACTORY, CREATEX_FACTORY, EIP2470_SINGLETON_FACTORY,
+    IMMUTABLE_CREATE2_FACTORY, SAFE_SINGLETON_FACTORY, ZEFRAMLOU_CREATE3_FACTORY,
+    cli::{Create3Factory, Create3Variant, FactoryPreset, Output, SaltScheme, Target, TargetChain},
+    error::{Error, Result},
+    mine::{Create2Miner, Create3Miner, ProxySalt, SaltGuard},
+    registry,
+    salt::SaltTemplate,
+};
+
+/// Interfaces through which factories deploy a contract with a salt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FactoryAbi {
+    /// The calldata is the salt followed by the creation code, and the
+    /// deployed address is returned as 20 raw bytes, e.g. Arachnid's
+    /// deterministic deployment proxy.
+    Raw,
+    /// The factory exposes `deploy(bytes32 salt, bytes creationCode)` and
+    /// returns the deployed address, e.g. the CREATE3 factory.
+    Deploy,
+    /// The factory exposes `deployCreate2(bytes32 salt, bytes initCode)` and
+    /// returns the deployed address, e.g. CreateX.
+    DeployCreate2,
+    /// The factory exposes `safeCreate2(bytes32 salt, bytes initCode)` and
+    /// returns the deployed address, e.g. 0age's ImmutableCreate2Factory.
+    SafeCreate2,
+    /// The factory exposes `deploy(bytes initCode, bytes32 salt)` and
+    /// returns the deployed address, e.g. the ERC-2470 SingletonFactory.
+    DeploySaltLast,
+}
+
+impl FactoryAbi {
+    /// Solidity signature of the deployment function, if the factory has one.
+    pub(super) fn signature(self) -> Option<&'static str> {
+        match self {
+            Self::Raw => None,
+            Self::Deploy => Some("deploy(bytes32,bytes)"),
+            Self::DeployCreate2 => Some("deployCreate2(bytes32,bytes)"),
+            Self::SafeCreate2 => Some("safeCreate2(bytes32,bytes)"),
+            Self::DeploySaltLast => Some("deploy(bytes,bytes32)"),
+        }
+    }
+
+    /// Returns whether the deployment function takes the salt before the
+    /// creation code.
+    fn salt_first(self) -> bool {
+        self != Self::DeploySaltLast
+    }
+}
+
+impl FactoryPreset {
+    /// Returns the name of the preset, as given to `--preset`.
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::Arachnid => "arachnid",
+            Self::CreateX => "createx",
+            Self::ImmutableCreate2 => "immutable",
+            Self::SafeSingleton => "safe",
+            Self::Eip2470 => "eip2470",
+        }
+    }
+
+    /// Returns the address the factory is deployed at.
+    pub(super) fn factory(self) -> Address {
+        match self {
+            Self::Arachnid => CREATE2_DEFAULT_FACTORY,
+            Self::CreateX => CREATEX_FACTORY,
+            Self::ImmutableCreate2 => IMMUTABLE_CREATE2_FACTORY,
+            Self::SafeSingleton => SAFE_SINGLETON_FACTORY,
+            Self::Eip2470 => EIP2470_SINGLETON_FACTORY,
+        }
+    }
+
+    /// Returns the interface through which the factory deploys.
+    pub(super) fn abi(self) -> FactoryAbi {
+        match self {
+            Self::Arachnid | Self::SafeSingleton => FactoryAbi::Raw,
+            Self::CreateX => FactoryAbi::DeployCreate2,
+            Self::ImmutableCreate2 => FactoryAbi::SafeCreate2,
+            Self::Eip2470 => FactoryAbi::DeploySaltLast,
+        }
+    }
+
+    /// Returns how the factory turns the salt into the CREATE2 salt.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain_id` - Chain the salt is restricted to, if any
+    /// * `deployer_prefix` - Whether the salt starts with the deployer
+    /// * `sequential` - Whether salts are counted up from zero
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option cannot be honoured by the factory.
+    pub(super) fn salt_guard(
+        self,
+        chain_id: Option<u64>,
+        deployer_prefix: bool,
+        sequential: bool,
+    ) -> Result<SaltGuard> {
+        let conflict = |option| Error::PresetConflict {
+            preset: self.name(),
+            option,
+        };
+        match self {
+            Self::Arachnid | Self::ImmutableCreate2 | Self::SafeSingleton | Self::Eip2470
+                if chain_id.is_some() =>
+            {
+                Err(conflict("--chain-id"))
+            }
+            Self::Arachnid | Self::SafeSingleton | Self::Eip2470 => Ok(SaltGuard::None),
+            // The ImmutableCreate2Factory rejects salts starting with anything
+            // but the caller or zeros, which sequential salts start with
+            Self::ImmutableCreate2 if !deployer_prefix && !sequential => {
+                Err(conflict("--no-deployer-prefix"))
+            }
+            Self::ImmutableCreate2 => Ok(SaltGuard::None),
+            // CreateX only guards salts starting with the caller
+            Self::CreateX if !deployer_prefix => Err(conflict("--no-deployer-prefix")),
+            Self::CreateX if sequential => Err(conflict("--sequential")),
+            Self::CreateX => Ok(SaltGuard::CreateX { chain_id }),
+        }
+    }
+}
+
+impl Create3Variant {
+    /// Returns the name of the variant, as given to `--create3-variant`.
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::LayerZero => "layerzero",
+            Self::Zeframlou => "zeframlou",
+            Self::Solady => "solady",
+            Self::Axelar => "axelar",
+            Self::Metamorphic => "metamorphic",
+        }
+    }
+
+    /// Returns the address the factory of the variant is deployed at, if it
+    /// has a canonical deployment.
+    pub(super) fn factory(self) -> Option<Address> {
+        match self {
+            Self::LayerZero => Some(CREATE3_DEFAULT_FACTORY),
+            Self::Zeframlou => Some(ZEFRAMLOU_CREATE3_FACTORY),
+            Self::Solady | Self::Axelar | Self::Metamorphic => None,
+        }
+    }
+
+    /// Returns how the factories of the variant derive the proxy salt.
+    fn salt_scheme(self) -> SaltScheme {
+        match self {
+            Self::LayerZero | Self::Zeframlou => SaltScheme::Sender,
+            Self::Solady => SaltScheme::Raw,
+            Self::Axelar => SaltScheme::SenderAbi,
+            Self::Metamorphic => SaltScheme::Caller,
+        }
+    }
+}
+
+impl SaltScheme {
+    /// Returns the name of the scheme, as given to `--create2-salt` or
+    /// `--create3-salt`.
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Sender => "sender",
+            Self::SenderAbi => "sender-abi",
+            Self::SenderChain => "sender-chain",
+            Self::Chain => "chain",
+            Self::Caller => "caller",
+        }
+    }
+
+    /// Returns how a CREATE2 factory following the scheme turns the salt
+    /// into the CREATE2 salt.
+    ///
+    /// # Arguments
+    ///
+    /// * `deployer` - Address of the account calling the factory
+    /// * `chain_id` - Chain id hashed into the salt by `sender-chain` and
+    ///   `chain`
+    /// * `deployer_prefix` - Whether the salt starts with the deployer
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option cannot be honoured by the scheme.
+    pub(super) fn salt_guard(
+        self,
+        deployer: Address,
+        chain_id: Option<u64>,
+        deployer_prefix: bool,
+    ) -> Result<SaltGuard> {
+        let conflict = |option| Error::SaltSchemeConflict {
+            scheme: self.name(),
+            option,
+        };
+        if chain_id.is_some() && !matches!(self, Self::SenderChain | Self::Chain) {
+            return Err(conflict("--chain-id"));
+        }
+        // The factory reverts unless the salt starts with the caller
+        if self == Self::Caller && !deployer_prefix {
+            return Err(conflict("--no-deployer-prefix"));
+        }
+
+        // The chain id is required by clap along with its schemes
+        let chain_id = U256::from(chain_id.unwrap_or_default()).to_be_bytes::<32>();
+        let before = match self {
+            Self::Raw | Self::Caller => return Ok(SaltGuard::None),
+            // `abi.encodePacked(msg.sender, salt)`
+            Self::Sender => deployer.to_vec(),
+            // `abi.encode(msg.sender, salt)`
+            Self::SenderAbi => deployer.into_word().to_vec(),
+            // `abi.encodePacked(msg.sender, block.chainid, salt)`
+            Self::SenderChain => [deployer.as_slice(), &chain_id].concat(),
+            // `abi.encodePacked(block.chainid, salt)`
+            Self::Chain => chain_id.to_vec(),
+        };
+        Ok(SaltGuard::Template(SaltTemplate {
+            before: before.into(),
+            after: Bytes::new(),
+        }))
+    }
+}
+
+impl Create3Factory {
+    /// Builds a miner following the variant, with the given factory, salt
+    /// scheme and proxy or the ones of the variant.
+    ///
+    /// # Arguments
+    ///
+    /// * `deployer` - Address of the account calling the factory
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the variant has no default for a value that was
+    /// not given, or if the named factory is not a CREATE3 one.
+    pub(super) fn miner(&self, deployer: Address) -> Result<Create3Miner> {
+        let variant = match self.factory_name {
+            Some(name) => name.create3_variant()?,
+            None => self
+                .create3_variant
+                .or_else(|| self.chain.and_then(registry::default_create3_variant))
+                .unwrap_or(Create3Variant::LayerZero),
+        };
+        let missing = |option| Error::MissingVariantDefault {
+            variant: variant.name(),
+            option,
+        };
+        let factory = self
+            .factory
+            .or(variant.factory())
+            .ok_or_else(|| missing("--factory"))?;
+        if let Some(chain) = self.chain {
+            registry::check_deployed(factory, chain);
+        }
+
+        // The chain id is required by clap along with its schemes
+        let proxy_salt = match self.create3_salt.unwrap_or(variant.salt_scheme()) {
+            SaltScheme::Raw => ProxySalt::Raw,
+            SaltScheme::Sender => ProxySalt::Sender,
+            SaltScheme::SenderAbi => ProxySalt::SenderAbi,
+            SaltScheme::SenderChain => ProxySalt::SenderChain(self.chain_id.unwrap_or_default()),
+            SaltScheme::Chain => ProxySalt::Chain(self.chain_id.unwrap_or_default()),
+            SaltScheme::Caller => ProxySalt::Caller,
+        };
+        let miner = Create3Miner::new(deployer, factory)
+            .with_proxy_salt(proxy_salt)
+            .with_proxy_nonce(self.proxy_nonce);
+
+        // Axelar's proxy hash depends on how its `CreateDeploy` contract was
+        // compiled, and metamorphic factories each have their own transient
+        // contract, so they have no default
+        Ok(match (self.proxy_init_code_hash, variant) {
+            (Some(hash), _) => miner.with_proxy_init_code_hash(hash),
+            (None, Create3Variant::Axelar | Create3Variant::Metamorphic) => {
+                return Err(missing("--proxy-init-code-hash"));
+            }
+            (None, _) => miner,
+        })
+    }
+}
+
+impl TargetChain {
+    /// Builds a miner following the CREATE2 formula of the target chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `factory` - Address of the factory contract
+    /// * `init_code_hash` - Hash of the init code, or of the bytecode on
+    ///   zkSync Era
+    pub(super) fn miner(&self, factory: Address, init_code_hash: B256) -> Create2Miner {
+        // The deployer is not part of the CREATE2 derivation, as it is already
+        // encoded in the salt
+        let miner = Create2Miner::new(Address::ZERO, factory, init_code_hash);
+        match self.target {
+            Target::Evm => miner,
+            Target::Zksync => {
+                miner.with_zksync(self.constructor_input_hash.unwrap_or_else(|| keccak256([])))
+            }
+        }
+    }
+}
+
+/// Mined deployment, with everything needed to perform it on chain.
+#[derive(Debug, Clone)]
+pub(super) struct Deployment {
+    /// Interface of the factory.
+    pub(super) abi: FactoryAbi,
+    /// Address of the factory contract.
+    pub(super) factory: Address,
+    /// Salt passed to the factory contract.
+    pub(super) salt: FixedBytes<32>,
+    /// Address the contract is deployed at.
+    pub(super) address: Address,
+    /// Creation code with the constructor arguments appended, if known.
+    pub(super) creation_code: Option<Vec<u8>>,
+}
+
+impl Deployment {
+    /// Returns the creation code of the deployed contract.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if only the init code hash is known.
+    pub(super) fn creation_code(&self) -> Result<&[u8]> {
+        self.creation_code
+            .as_deref()
+            .ok_or(Error::MissingCreationCode)
+    }
+
+    /// Returns the calldata of the factory call performing the deployment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if only the init code hash is known.
+    pub(super) fn calldata(&self) -> Result<Bytes> {
+        let creation_code = self.creation_code()?;
+        Ok(match self.abi.signature() {
+            None => [self.salt.as_slice(), creation_code].concat().into(),
+            Some(signature) => {
+                let salt = DynSolValue::FixedBytes(self.salt, 32);
+                let creation_code = DynSolValue::Bytes(creation_code.to_vec());
+                let args = DynSolValue::Tuple(if self.abi.salt_first() {
+                    vec![salt, creation_code]
+                } else {
+                    vec![creation_code, salt]
+                });
+                [&keccak256(signature)[..4], &args.abi_encode_params()]
+                    .concat()
+                    .into()
+            }
+        })
+    }
+
+    /// Renders the `cast send` command performing the deployment from the
+    /// shell.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if only the init code hash is known.
+    pub(super) fn cast_command(&self) -> Result<String> {
+        Ok(match self.abi.signature() {
+            None => format!("cast send {} {}", self.factory, self.calldata()?),
+            Some(signature) => {
+                let (salt, creation_code) = (
+                    self.salt.to_string(),
+                    hex::encode_prefixed(self.creation_code()?),
+                );
+                let args = if self.abi.salt_first() {
+                    [salt, creation_code]
+                } else {
+                    [creation_code, salt]
+                };
+                format!(
+                    "cast send {} '{signature}' {}",
+                    self.factory,
+                    args.join(" ")
+                )
+            }
+        })
+    }
+
+    /// Builds a Safe{Wallet} Transaction Builder batch with the factory call
+    /// performing the deployment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if only the init code hash is known.
+    pub(super) fn safe_batch(&self, chain_id: u64) -> Result<Value> {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        Ok(json!({
+            "version": "1.0",
+            "chainId": chain_id.to_string(),
+            "createdAt": created_at,
+            "meta": {
+                "name": format!("Deploy {}", self.address),
+                "description": format!("Deploys a contract mined with piwi at {}", self.address),
+            },
+            "transactions": [{
+                "to": self.factory.to_string(),
+                "value": "0",
+                "data": self.calldata()?,
+                "contractMethod": null,
+                "contractInputsValues": null,
+            }],
+        }))
+    }
+
+    /// Renders a Foundry script deploying the contract through the factory
+    /// and checking that it lands at the mined address.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if only the init code hash is known.
+    pub(super) fn foundry_script(&self) -> Result<String> {
+        let creation_code = hex::encode(self.creation_code()?);
+        let (calldata, deployed) = match self.abi.signature() {
+            None => (
+                "abi.encodePacked(SALT, creationCode)".to_string(),
+                "address(bytes20(result))",
+            ),
+            Some(signature) => (
+                format!(
+                    "abi.encodeWithSignature(\"{signature}\", {})",
+                    if self.abi.salt_first() {
+                        "SALT, creationCode"
+                    } else {
+                        "creationCode, SALT"
+                    }
+                ),
+                "abi.decode(result, (address))",
+            ),
+        };
+
+        Ok(format!(
+            r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.13;
+
+import {{Script}} from "forge-std/Script.sol";
+
+/// @notice Deploys the contract mined with piwi at its mined address.
+contract DeployScript is Script {{
+    address internal constant FACTORY = {factory};
+    bytes32 internal constant SALT = {salt};
+    address internal constant EXPECTED = {address};
+
+    function run() external returns (address deployed) {{
+        bytes memory creationCode = hex"{creation_code}";
+
+        vm.startBroadcast();
+        (bool success, bytes memory result) = FACTORY.call({calldata});
+        vm.stopBroadcast();
+
+        require(success, "factory call failed");
+        deployed = {deployed};
+        require(deployed == EXPECTED, "deployed address does not match the mined address");
+    }}
+}}
+"#,
+            factory = self.factory,
+            salt = self.salt,
+            address = self.address,
+        ))
+    }
+}
+
+impl Output {
+    /// Returns whether a file or command performing the deployment was
+    /// requested.
+    pub(super) fn emits_deployment(&self) -> bool {
+        self.emit_foundry_script.is_some()
+            || self.emit_cast.is_some()
+            || self.emit_safe_tx.is_some()
+    }
+
+    /// Checks that the creation code is known if a deployment file has to be
+    /// emitted, before any time is spent mining.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a deployment file is requested without the
+    /// creation code.
+    pub(super) fn check_creation_code(&self, creation_code: Option<&[u8]>) -> Result<()> {
+        if self.emits_deployment() && creation_code.is_none() {
+            return Err(Error::MissingCreationCode);
+        }
+        Ok(())
+    }
+
+    /// Writes the deployment files requested on the command line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be written, or if only the init code
+    /// hash is known.
+    pub(super) fn emit_deployment(&self, deployment: &Deployment) -> Result<()> {
+        if let Some(path) = &self.emit_foundry_script {
+            fs::write(path, deployment.foundry_script()?).map_err(|source| Error::Write {
+                path: path.clone(),
+                source,
+            })?;
+        }
+
+        if let Some(path) = &self.emit_safe_tx {
+            let batch = deployment.safe_batch(self.safe_chain_id)?;
+            fs::write(path, format!("{batch:#}\n")).map_err(|source| Error::Write {
+                path: path.clone(),
+                source,
+            })?;
+        }
+
+        // Print the command unless a file is given
+        match self.emit_cast.as_deref() {
+            None => {}
+            Some("-") => println!("{}", deployment.cast_command()?),
+            Some(path) => fs::write(path, deployment.cast_command()? + "\n").map_err(|source| {
+                Error::Write {
+                    path: path.to_string(),
+                    source,
+                }
+            })?,
+        }
+        Ok(())
+    }
+}
+
+/// Account signing the presigned transaction deploying Arachnid's proxy.
+const ARACHNID_DEPLOYMENT_SIGNER: Address = address!("0x3fAB184622Dc19b6109349B94811493BF2a45362");
+
+/// Gas price of the presigned transaction, 100 gwei.
+const ARACHNID_DEPLOYMENT_GAS_PRICE: u64 = 100_000_000_000;
+
+/// Gas limit of the presigned transaction.
+const ARACHNID_DEPLOYMENT_GAS_LIMIT: u64 = 100_000;
+
+/// Presigned transaction deploying Arachnid's proxy at nonce 0 of its signer.
+///
+/// It has no chain id, and a signature whose `r` and `s` are both `0x22..22`
+/// so that nobody knows the key of its signer, which can then only ever
+/// deploy the proxy.
+const ARACHNID_DEPLOYMENT_TX: [u8; 167] = hex!(
+    "f8a58085174876e800830186a08080b853604580600e600039806000f350fe7fffffffff"
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082"
+    "378035828234f58015156039578182fd5b8082525050506014600cf31ba0222222222222"
+    "2222222222222222222222222222222222222222222222222222a0222222222222222222"
+    "2222222222222222222222222222222222222222222222"
+);
+
+/// Prints how to deploy Arachnid's deterministic deployment proxy on a chain
+/// missing it: funding its signer, then publishing its presigned
+/// transaction.
+pub(super) fn factory_tx() {
+    let funding =
+        U256::from(ARACHNID_DEPLOYMENT_GAS_PRICE) * U256::from(ARACHNID_DEPLOYMENT_GAS_LIMIT);
+    println!("Factory  {CREATE2_DEFAULT_FACTORY}");
+    println!("Signer   {ARACHNID_DEPLOYMENT_SIGNER}");
+    println!(
+        "Funding  {funding} wei, {ARACHNID_DEPLOYMENT_GAS_LIMIT} gas at {} gwei",
+        ARACHNID_DEPLOYMENT_GAS_PRICE / 1_000_000_000
+    );
+    println!();
+    println!("# Fund the signer, unless it already holds {funding} wei");
+    println!("cast send --value {funding} {ARACHNID_DEPLOYMENT_SIGNER}");
+    println!("# Publish the presigned deployment");
+    println!("cast publish 0x{}", hex::encode(ARACHNID_DEPLOYMENT_TX));
+}
+
+#[test]
+fn test_foundry_script() {
+    let mut deployment = Deployment {
+        abi: FactoryAbi::Deploy,
+        factory: Address::ZERO,
+        salt: FixedBytes::repeat_byte(0x11),
+        address: Address::ZERO,
+        creation_code: Some(vec![0x60, 0x80]),
+    };
+    let script = deployment.foundry_script().unwrap();
+    assert!(script.contains(r#"bytes memory creationCode = hex"6080";"#));
+    assert!(script.contains(
+        r#"FACTORY.call(abi.encodeWithSignature("deploy(bytes32,bytes)", SALT, creationCode))"#
+    ));
+
+    deployment.creation_code = None;
+    assert!(deployment.foundry_script().is_err());
+}
+
+#[test]
+fn test_calldata() {
+    let mut deployment = Deployment {
+        abi: FactoryAbi::Raw,
+        factory: Address::ZERO,
+        salt: FixedBytes::repeat_byte(0x11),
+        address: Address::ZERO,
+        creation_code: Some(vec![0x60, 0x80]),
+    };
+    assert_eq!(
+        hex::encode(deployment.calldata().unwrap()),
+        format!("{}6080", "11".repeat(32))
+    );
+
+    // The selector of `deploy(bytes32,bytes)` is 0xcdcb760a
+    deployment.abi = FactoryAbi::Deploy;
+    assert_eq!(
+        hex::encode(deployment.calldata().unwrap()),
+        format!(
+            "cdcb760a{}{:064x}{:064x}6080{}",
+            "11".repeat(32),
+            0x40,
+            2,
+            "0".repeat(60)
+        )
+    );
+
+    let batch = deployment.safe_batch(10).unwrap();
+    assert_eq!(batch["chainId"], "10");
+    assert_eq!(
+        batch["transactions"][0]["data"],
+        hex::encode_prefixed(deployment.calldata().unwrap())
+    );
+    assert_eq!(
+        deployment.cast_command().unwrap(),
+        format!(
+            "cast send {} 'deploy(bytes32,bytes)' 0x{} 0x6080",
+            Address::ZERO,
+            "11".repeat(32)
+        )
+    );
+
+    // The ERC-2470 SingletonFactory takes the creation code first, with the
+    // selector of `deploy(bytes,bytes32)`, 0x4af63f02
+    deployment.abi = FactoryAbi::DeploySaltLast;
+    assert_eq!(
+        hex::encode(deployment.calldata().unwrap()),
+        format!(
+            "4af63f02{:064x}{}{:064x}6080{}",
+            0x40,
+            "11".repeat(32),
+            2,
+            "0".repeat(60)
+        )
+    );
+}
+
+#[test]
+fn test_factory_tx() {
+    // The signer deploys the proxy with its first transaction
+    assert_eq!(
+        ARACHNID_DEPLOYMENT_SIGNER.create(0),
+        CREATE2_DEFAULT_FACTORY
+    );
+
+    // The transaction pays the advertised gas price and limit
+    let fields = hex::encode(&ARACHNID_DEPLOYMENT_TX[..13]);
+    assert!(fields.contains(&format!("{ARACHNID_DEPLOYMENT_GAS_PRICE:x}")));
+    assert!(fields.contains(&format!("{ARACHNID_DEPLOYMENT_GAS_LIMIT:06x}")));
+}
+
+#[test]
+fn test_create2_salt_scheme() {
+    let deployer = Address::repeat_byte(0x22);
+    let SaltGuard::Template(template) = SaltScheme::SenderChain
+        .salt_guard(deployer, Some(10), true)
+        .unwrap()
+    else {
+        panic!("sender-chain hashes the salt");
+    };
+    assert_eq!(
+        template.fill(&[0x11; 32]),
+        [
+            deployer.as_slice(),
+            &U256::from(10).to_be_bytes::<32>(),
+            &[0x11; 32]
+        ]
+        .concat()
+    );
+
+    assert_eq!(
+        SaltScheme::Chain
+            .salt_guard(deployer, Some(10), false)
+            .unwrap(),
+        SaltGuard::Template(SaltTemplate {
+            before: U256::from(10).to_be_bytes::<32>().to_vec().into(),
+            after: Bytes::new(),
+        })
+    );
+
+    // Only the chain-scoped schemes take a chain id, and caller needs the
+    // deployer
+    assert!(
+        SaltScheme::Sender
+            .salt_guard(deployer, Some(10), true)
+            .is_err()
+    );
+    assert!(
+        SaltScheme::Caller
+            .salt_guard(deployer, None, false)
+            .is_err()
+    );
+}
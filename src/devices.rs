@@ -0,0 +1,140 @@
+use std::{thread, time::Duration};
+
+use alloy_primitives::{Address, FixedBytes};
+
+use crate::{
+    CREATE2_DEFAULT_FACTORY,
+    bench::{format_hashrate, measure_hashrate},
+    cli::Hasher,
+    error::Result,
+    gpu,
+    hash::Backend,
+    mine::Create2Miner,
+};
+
+/// Describes the CPU and every GPU Piwi can mine on, with a CREATE2 hashrate
+/// probe of each, so that users can pick what to mine on.
+///
+/// The CPU is probed on every available core. A missing GPU driver is
+/// reported rather than failing, as is a GPU that cannot be probed.
+///
+/// # Arguments
+/// * `duration` - How long each probe runs for.
+///
+/// # Errors
+///
+/// Returns an error if the CPU probe cannot create its thread pool.
+pub(super) fn devices(duration: Duration) -> Result<()> {
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let hasher = Backend::detect(Hasher::Auto).expect("the automatic hasher is always available");
+    let features = simd_features();
+
+    // Placeholder inputs are fine since the derivation cost does not depend on
+    // their values
+    let create2 = Create2Miner::new(Address::ZERO, CREATE2_DEFAULT_FACTORY, FixedBytes::ZERO);
+    print_device(
+        "CPU",
+        &[
+            ("Threads", threads.to_string()),
+            (
+                "SIMD",
+                if features.is_empty() {
+                    "none".to_string()
+                } else {
+                    features.join(" ")
+                },
+            ),
+            ("Keccak", hasher.to_string()),
+            (
+                "CREATE2",
+                format_hashrate(measure_hashrate(&create2, threads, duration)?),
+            ),
+        ],
+    );
+
+    let devices = match gpu::devices() {
+        Ok(devices) => devices,
+        Err(err) => {
+            println!("\nNo GPU: {err}");
+            return Ok(());
+        }
+    };
+    for (index, device) in devices.iter().enumerate() {
+        let name = device
+            .name()
+            .unwrap_or_else(|_| "unknown device".to_string());
+        let memory = device
+            .memory()
+            .map_or_else(|err| err.to_string(), format_memory);
+        let hashrate = gpu::measure_hashrate(device, duration)
+            .map_or_else(|err| err.to_string(), format_hashrate);
+        println!();
+        print_device(
+            &format!("GPU {index}: {name}"),
+            &[
+                ("Backend", device.backend().to_string()),
+                ("Memory", memory),
+                ("CREATE2", hashrate),
+            ],
+        );
+    }
+    Ok(())
+}
+
+/// Prints a device title followed by its indented, aligned fields.
+fn print_device(title: &str, fields: &[(&str, String)]) {
+    println!("{title}");
+    let width = fields
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or_default();
+    for (label, value) in fields {
+        println!("  {label:<width$}  {value}");
+    }
+}
+
+/// Returns the SIMD extensions of the CPU that matter for hashing.
+#[cfg(target_arch = "x86_64")]
+fn simd_features() -> Vec<&'static str> {
+    [
+        ("sse4.1", is_x86_feature_detected!("sse4.1")),
+        ("avx", is_x86_feature_detected!("avx")),
+        ("avx2", is_x86_feature_detected!("avx2")),
+        ("bmi2", is_x86_feature_detected!("bmi2")),
+        ("avx512f", is_x86_feature_detected!("avx512f")),
+        ("avx512vl", is_x86_feature_detected!("avx512vl")),
+    ]
+    .into_iter()
+    .filter_map(|(name, detected)| detected.then_some(name))
+    .collect()
+}
+
+/// Returns the SIMD extensions of the CPU that matter for hashing.
+#[cfg(target_arch = "aarch64")]
+fn simd_features() -> Vec<&'static str> {
+    [
+        ("neon", std::arch::is_aarch64_feature_detected!("neon")),
+        ("sha3", std::arch::is_aarch64_feature_detected!("sha3")),
+    ]
+    .into_iter()
+    .filter_map(|(name, detected)| detected.then_some(name))
+    .collect()
+}
+
+/// Returns the SIMD extensions of the CPU that matter for hashing.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn simd_features() -> Vec<&'static str> {
+    Vec::new()
+}
+
+/// Formats a memory size in bytes as GiB, e.g. `24.0 GiB`.
+fn format_memory(bytes: u64) -> String {
+    format!("{:.1} GiB", bytes as f64 / f64::from(1u32 << 30))
+}
+
+#[test]
+fn test_format_memory() {
+    assert_eq!(format_memory(24 << 30), "24.0 GiB");
+    assert_eq!(format_memory(3 << 29), "1.5 GiB");
+}
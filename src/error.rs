@@ -0,0 +1,235 @@
+use std::io;
+
+use alloy_primitives::{FixedBytes, hex::FromHexError};
+use rayon::ThreadPoolBuildError;
+
+use crate::core::PatternError;
+
+/// Errors surfaced to the user by the Piwi CLI and library.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A hex argument could not be decoded.
+    #[error("invalid {name} {value:?}: {source}")]
+    InvalidHex {
+        /// Name of the argument, e.g. `prefix`.
+        name: &'static str,
+        /// Value given by the user.
+        value: String,
+        /// Underlying decoding error.
+        source: FromHexError,
+    },
+
+    /// The flags or the prefix do not make a valid pattern.
+    #[error(transparent)]
+    Pattern(#[from] PatternError),
+
+    /// No source of initialization code was given.
+    #[error("no init code or init code hash was given")]
+    MissingInitCode,
+
+    /// The fixed salt bytes leave nothing to mine.
+    #[error("the pinned salt bytes leave no salt byte free to mine")]
+    NoFreeSaltBytes,
+
+    /// The nonce range is empty or exceeds the nonce segment of the salt.
+    #[error("invalid nonce range {start}..{end}, the salt layout allows nonces below {count}")]
+    InvalidNonceRange {
+        /// First nonce of the range.
+        start: u64,
+        /// Nonce the range stops before.
+        end: u64,
+        /// Number of distinct nonce values.
+        count: u64,
+    },
+
+    /// Every salt allowed by the salt layout was tried without a match.
+    #[error("no matching salt exists in the explored salt space")]
+    SaltSpaceExhausted,
+
+    /// No deployment nonce of the range gives a matching CREATE address.
+    #[error("no nonce in {start}..{end} gives a matching address")]
+    NonceRangeExhausted {
+        /// First nonce tried.
+        start: u64,
+        /// Nonce the range stops before.
+        end: u64,
+    },
+
+    /// An option does not apply to the selected factory preset.
+    #[error("--preset {preset} cannot be combined with {option}")]
+    PresetConflict {
+        /// Name of the preset.
+        preset: &'static str,
+        /// The conflicting option.
+        option: &'static str,
+    },
+
+    /// An option does not apply to the selected salt scheme.
+    #[error("--create2-salt {scheme} cannot be combined with {option}")]
+    SaltSchemeConflict {
+        /// Name of the scheme.
+        scheme: &'static str,
+        /// The conflicting option.
+        option: &'static str,
+    },
+
+    /// A registered factory was selected for the other deployment method.
+    #[error("--factory-name {name} is a {method} factory, mine it with `piwi {method}`")]
+    FactoryMethodMismatch {
+        /// Name of the factory.
+        name: &'static str,
+        /// Deployment method of the factory.
+        method: &'static str,
+    },
+
+    /// The selected CREATE3 variant has no default value for an option.
+    #[error("--create3-variant {variant} has no default {option}, give one explicitly")]
+    MissingVariantDefault {
+        /// Name of the variant.
+        variant: &'static str,
+        /// The option to give.
+        option: &'static str,
+    },
+
+    /// Bytecode cannot be deployed on zkSync Era.
+    #[error("invalid zkSync Era bytecode: {0}")]
+    InvalidZksyncBytecode(String),
+
+    /// Deployment files were requested for zkSync Era, which deploys through
+    /// its system contracts instead of the factory call they perform.
+    #[error("deployment files cannot be emitted for --target zksync")]
+    ZksyncDeployment,
+
+    /// Init code is over the EIP-3860 limit, so no chain would deploy it.
+    #[error("init code is {size} bytes, over the EIP-3860 limit of {limit} bytes")]
+    InitCodeTooLarge {
+        /// Size of the init code in bytes.
+        size: usize,
+        /// Largest init code deployable.
+        limit: usize,
+    },
+
+    /// An input file or stdin could not be read.
+    #[error("could not read {path}: {source}")]
+    Read {
+        /// Path of the file, or `-` for stdin.
+        path: String,
+        /// Underlying I/O error.
+        source: io::Error,
+    },
+
+    /// A deployment file was requested without the creation code.
+    #[error("emitting a deployment needs the creation code of the contract, e.g. from --artifact")]
+    MissingCreationCode,
+
+    /// An output file could not be written.
+    #[error("could not write {path}: {source}")]
+    Write {
+        /// Path of the file.
+        path: String,
+        /// Underlying I/O error.
+        source: io::Error,
+    },
+
+    /// A checkpoint file cannot be resumed.
+    #[error("cannot resume checkpoint {path}: {reason}")]
+    InvalidCheckpoint {
+        /// Path of the checkpoint file.
+        path: String,
+        /// Description of what is wrong with the checkpoint.
+        reason: String,
+    },
+
+    /// A mining job file is not a valid job.
+    #[error("invalid mining job {path}: {reason}")]
+    InvalidJob {
+        /// Path of the job file, or `-` for stdin.
+        path: String,
+        /// Description of what is wrong with the job.
+        reason: String,
+    },
+
+    /// No recorded session has the requested id.
+    #[error("no session with id {0}, run `piwi sessions` to list them")]
+    UnknownSession(u64),
+
+    /// The directory Piwi keeps its state in cannot be determined.
+    #[error("neither PIWI_HOME nor HOME is set")]
+    MissingHome,
+
+    /// An answer to an interactive prompt could not be read.
+    #[error("could not read the answer: {0}")]
+    Prompt(io::Error),
+
+    /// Constructor arguments could not be ABI encoded.
+    #[error("invalid constructor arguments: {0}")]
+    InvalidConstructorArgs(String),
+
+    /// A contract artifact does not have the expected shape.
+    #[error("invalid artifact {path}: {reason}")]
+    InvalidArtifact {
+        /// Path of the artifact file.
+        path: String,
+        /// Description of what is wrong with the artifact.
+        reason: String,
+    },
+
+    /// The worker thread pool could not be created.
+    #[error("could not build the thread pool: {0}")]
+    ThreadPool(#[from] ThreadPoolBuildError),
+
+    /// The CPU affinity of the process or a worker could not be read or set.
+    #[error("could not pin the workers to CPU cores: {0}")]
+    Affinity(io::Error),
+
+    /// Pinning workers to cores is not available on this platform.
+    #[cfg(not(target_os = "linux"))]
+    #[error("--pin-cores is only supported on Linux")]
+    UnsupportedAffinity,
+
+    /// The requested Keccak implementation cannot run on this machine or was
+    /// not built in.
+    #[error("--hasher {name} is unavailable: {reason}")]
+    HasherUnavailable {
+        /// Name of the hasher, e.g. `asm`.
+        name: &'static str,
+        /// Why the hasher cannot be used.
+        reason: &'static str,
+    },
+
+    /// A GPU could not be listed, or a kernel could not be built or run on
+    /// it.
+    #[error("GPU error: {0}")]
+    Gpu(String),
+
+    /// A mined salt does not derive an address matching the pattern.
+    #[error("mined salt {salt} failed verification: {reason}")]
+    UnverifiedSalt {
+        /// The mined salt.
+        salt: FixedBytes<32>,
+        /// Description of the mismatch.
+        reason: String,
+    },
+
+    /// Mining was stopped before finding a salt.
+    #[error("mining was cancelled after {attempts} attempts")]
+    Cancelled {
+        /// Number of salts tried by the miner before it stopped.
+        attempts: u64,
+    },
+
+    /// An option does not apply to mining a proxy and implementation pair.
+    #[error("{0} cannot be used when mining a proxy and implementation pair")]
+    UnsupportedInPair(&'static str),
+
+    /// Watch mode was requested without a file to watch.
+    #[error("--watch needs the init code to come from --init-code-file or --artifact")]
+    NothingToWatch,
+
+    /// A salt did not produce the expected address.
+    #[error("verification failed")]
+    VerificationFailed,
+}
+
+/// Result type used throughout the Piwi CLI and library.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
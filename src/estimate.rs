@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use alloy_primitives::{Address, FixedBytes};
+
+use crate::{
+    CREATE2_DEFAULT_FACTORY, CREATE3_DEFAULT_FACTORY,
+    bench::{format_hashrate, measure_hashrate},
+    error::Result,
+    mine::{Create2Miner, Create3Miner},
+    pattern::{FLAGS_MASK, Pattern},
+};
+
+/// How long the hashrate of each miner type is measured for.
+const BENCH_DURATION: Duration = Duration::from_millis(500);
+
+/// Success probabilities for which the mining time is reported.
+const PROBABILITIES: [f64; 3] = [0.5, 0.9, 0.99];
+
+/// Prints the difficulty of a pattern along with the time each miner type is
+/// expected to need to find a matching salt on this machine.
+///
+/// # Arguments
+/// * `flags` - The desired flags. Every flag bit is constrained regardless of
+///   their value, so they are only validated against the prefix.
+/// * `prefix` - The desired address prefix.
+///
+/// # Errors
+///
+/// Returns an error if the pattern is invalid or the hashrate cannot be
+/// measured.
+pub(super) fn estimate(flags: Option<&str>, prefix: &str) -> Result<()> {
+    let bits = Pattern::parse(flags.unwrap_or_default(), prefix)?.difficulty_bits();
+    let flags_bits: u32 = FLAGS_MASK.iter().map(|byte| byte.count_ones()).sum();
+    let expected_attempts = 2f64.powi(bits as i32);
+
+    println!(
+        "Pattern:           prefix {prefix:?}, flags {:?}",
+        flags.unwrap_or("")
+    );
+    println!(
+        "Difficulty:        {bits} bits ({} prefix bits + {flags_bits} flag bits)",
+        bits - flags_bits
+    );
+    println!("Expected attempts: {expected_attempts:.3e}");
+    println!();
+
+    // Measure the hashrate of both miners with placeholder inputs, since the
+    // derivation cost does not depend on their values
+    let threads = rayon::current_num_threads();
+    let create2 = Create2Miner::new(Address::ZERO, CREATE2_DEFAULT_FACTORY, FixedBytes::ZERO);
+    let create3 = Create3Miner::new(Address::ZERO, CREATE3_DEFAULT_FACTORY);
+    let hashrates = [
+        (
+            "CREATE2",
+            measure_hashrate(&create2, threads, BENCH_DURATION)?,
+        ),
+        (
+            "CREATE3",
+            measure_hashrate(&create3, threads, BENCH_DURATION)?,
+        ),
+    ];
+
+    println!(
+        "{:<8} {:>14} {:>14} {:>14} {:>14} {:>14}",
+        "Miner", "Hashrate", "Expected", "50%", "90%", "99%"
+    );
+    for (name, hashrate) in hashrates {
+        // The number of attempts until the first match is geometrically
+        // distributed, so P(found after n attempts) = 1 - exp(-n / expected)
+        let quantiles = PROBABILITIES.map(|probability| {
+            format_duration(-(1.0 - probability).ln() * expected_attempts / hashrate)
+        });
+        println!(
+            "{:<8} {:>14} {:>14} {:>14} {:>14} {:>14}",
+            name,
+            format_hashrate(hashrate),
+            format_duration(expected_attempts / hashrate),
+            quantiles[0],
+            quantiles[1],
+            quantiles[2]
+        );
+    }
+    println!();
+    println!("Measured with {threads} worker thread(s).");
+    Ok(())
+}
+
+/// Formats a number of seconds as a short human-readable duration.
+fn format_duration(seconds: f64) -> String {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+    const YEAR: f64 = 365.0 * DAY;
+
+    if seconds < MINUTE {
+        format!("{seconds:.1}s")
+    } else if seconds < HOUR {
+        format!(
+            "{}m {}s",
+            (seconds / MINUTE) as u64,
+            (seconds % MINUTE) as u64
+        )
+    } else if seconds < DAY {
+        format!(
+            "{}h {}m",
+            (seconds / HOUR) as u64,
+            (seconds % HOUR / MINUTE) as u64
+        )
+    } else if seconds < YEAR {
+        format!(
+            "{}d {}h",
+            (seconds / DAY) as u64,
+            (seconds % DAY / HOUR) as u64
+        )
+    } else {
+        format!("{:.1} years", seconds / YEAR)
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    ffi::{CStr, c_char},
+    ptr, slice,
+};
+
+use alloy_primitives::{Address, B256};
+
+use crate::{
+    cancel::CancellationToken,
+    core::Pattern,
+    error::Error,
+    mine::{Create2Miner, Create3Miner, Miner},
+};
+
+/// Outcome of a search through the C ABI.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PiwiStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A string argument is not valid UTF-8, or the flags or the prefix are
+    /// malformed.
+    InvalidArgument = 1,
+    /// The search was cancelled through its cancellation handle.
+    Cancelled = 2,
+    /// The search failed, e.g. after exhausting the salts.
+    Failed = 3,
+}
+
+/// Salt found by a miner, with the address it deploys the contract to.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PiwiResult {
+    /// Address the contract is deployed at with the salt.
+    pub address: [u8; 20],
+    /// Salt passed to the factory contract.
+    pub salt: [u8; 32],
+    /// Number of salts tried by the miner.
+    pub attempts: u64,
+}
+
+/// Opaque handle stopping a search from another thread.
+pub struct PiwiCancel(CancellationToken);
+
+/// Creates a cancellation handle, to be released with `piwi_cancel_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn piwi_cancel_new() -> *mut PiwiCancel {
+    Box::into_raw(Box::new(PiwiCancel(CancellationToken::new())))
+}
+
+/// Stops the searches given the handle, which then return `PiwiStatus::Cancelled`.
+///
+/// # Safety
+///
+/// `cancel` must come from `piwi_cancel_new` and not be freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn piwi_cancel(cancel: *const PiwiCancel) {
+    // SAFETY: the handle is live, as required of the caller
+    unsafe { &*cancel }.0.cancel();
+}
+
+/// Releases a cancellation handle once no search uses it anymore.
+///
+/// # Safety
+///
+/// `cancel` must come from `piwi_cancel_new` and not be freed yet, or be
+/// null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn piwi_cancel_free(cancel: *mut PiwiCancel) {
+    if !cancel.is_null() {
+        // SAFETY: the handle was boxed by `piwi_cancel_new`
+        drop(unsafe { Box::from_raw(cancel) });
+    }
+}
+
+/// Mines a CREATE2 salt deploying the init code with the given hash to an
+/// address matching the flags and prefix, blocking until it is found.
+///
+/// # Arguments
+/// * `deployer` - The 20-byte address starting the salt.
+/// * `factory` - The 20-byte address of the CREATE2 factory contract.
+/// * `init_code_hash` - The 32-byte keccak256 hash of the init code.
+/// * `flags` - The flags expected in the lower 14 bits of the address, as a
+///   hex string.
+/// * `prefix` - The prefix expected at the start of the address, as a hex
+///   string, or null for none.
+/// * `cancel` - The handle stopping the search, or null for none.
+/// * `result` - Where the salt and the address are written on success.
+///
+/// # Safety
+///
+/// The byte arguments must point to that many readable bytes, the strings
+/// must be NUL-terminated, `cancel` must be live if not null, and `result`
+/// must be writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn piwi_mine_create2(
+    deployer: *const u8,
+    factory: *const u8,
+    init_code_hash: *const u8,
+    flags: *const c_char,
+    prefix: *const c_char,
+    cancel: *const PiwiCancel,
+    result: *mut PiwiResult,
+) -> PiwiStatus {
+    // SAFETY: the arguments are valid, as required of the caller
+    let (flags, prefix, cancel) = unsafe {
+        (
+            CStr::from_ptr(flags).to_str(),
+            if prefix.is_null() {
+                Ok("")
+            } else {
+                CStr::from_ptr(prefix).to_str()
+            },
+            cancel.as_ref(),
+        )
+    };
+    let (Ok(flags), Ok(prefix)) = (flags, prefix) else {
+        return PiwiStatus::InvalidArgument;
+    };
+    let Ok(pattern) = Pattern::parse(flags, prefix) else {
+        return PiwiStatus::InvalidArgument;
+    };
+
+    // SAFETY: the byte arguments are readable, as required of the caller
+    let miner = unsafe {
+        Create2Miner::new(
+            address(deployer),
+            address(factory),
+            B256::from_slice(slice::from_raw_parts(init_code_hash, 32)),
+        )
+    };
+    let miner = match cancel {
+        Some(PiwiCancel(cancel)) => miner.with_cancel(cancel.clone()),
+        None => miner,
+    };
+    match miner.mine(&pattern) {
+        Ok(mined) => {
+            let mined = PiwiResult {
+                address: mined.address.into(),
+                salt: mined.salt.into(),
+                attempts: mined.attempts,
+            };
+            // SAFETY: the result is writable, as required of the caller
+            unsafe { ptr::write(result, mined) };
+            PiwiStatus::Ok
+        }
+        Err(Error::Cancelled { .. }) => PiwiStatus::Cancelled,
+        Err(_) => PiwiStatus::Failed,
+    }
+}
+
+/// Computes the address a CREATE3 factory hashing the salt after the
+/// deployer deploys a contract to.
+///
+/// # Arguments
+/// * `deployer` - The 20-byte address calling the factory.
+/// * `factory` - The 20-byte address of the CREATE3 factory contract.
+/// * `salt` - The 32-byte salt given to the factory.
+/// * `address` - Where the 20-byte address is written.
+///
+/// # Safety
+///
+/// The byte arguments must point to that many readable bytes, and `address`
+/// to 20 writable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn piwi_compute_create3(
+    deployer: *const u8,
+    factory: *const u8,
+    salt: *const u8,
+    address: *mut u8,
+) {
+    // SAFETY: the arguments are valid, as required of the caller
+    unsafe {
+        let miner = Create3Miner::new(self::address(deployer), self::address(factory));
+        let computed = miner.compute_address(&B256::from_slice(slice::from_raw_parts(salt, 32)));
+        ptr::copy_nonoverlapping(computed.as_ptr(), address, 20);
+    }
+}
+
+/// Reads a 20-byte address.
+///
+/// # Safety
+///
+/// `bytes` must point to 20 readable bytes.
+unsafe fn address(bytes: *const u8) -> Address {
+    // SAFETY: the bytes are readable, as required of the caller
+    Address::from_slice(unsafe { slice::from_raw_parts(bytes, 20) })
+}
+
+#[test]
+fn test_ffi() {
+    let zero = [0u8; 32];
+    let flags = c"0x0080";
+    let mut result = PiwiResult::default();
+    let status = unsafe {
+        piwi_mine_create2(
+            zero.as_ptr(),
+            zero.as_ptr(),
+            zero.as_ptr(),
+            flags.as_ptr(),
+            ptr::null(),
+            ptr::null(),
+            &mut result,
+        )
+    };
+    assert_eq!(status, PiwiStatus::Ok);
+    let miner = Create2Miner::new(Address::ZERO, Address::ZERO, B256::ZERO);
+    let address = miner.compute_address(&result.salt.into());
+    assert_eq!(address, Address::from(result.address));
+    assert!(result.attempts > 0);
+
+    let mut address = [0u8; 20];
+    unsafe {
+        piwi_compute_create3(
+            zero.as_ptr(),
+            zero.as_ptr(),
+            zero.as_ptr(),
+            address.as_mut_ptr(),
+        )
+    };
+    let miner = Create3Miner::new(Address::ZERO, Address::ZERO);
+    assert_eq!(Address::from(address), miner.compute_address(&B256::ZERO));
+
+    // A cancelled handle stops the search, and malformed flags are rejected
+    let cancel = piwi_cancel_new();
+    let [cancelled, invalid] = [c"0x0080", c"0xzz"].map(|flags| unsafe {
+        piwi_cancel(cancel);
+        piwi_mine_create2(
+            zero.as_ptr(),
+            zero.as_ptr(),
+            zero.as_ptr(),
+            flags.as_ptr(),
+            c"0xffff".as_ptr(),
+            cancel,
+            &mut result,
+        )
+    });
+    unsafe { piwi_cancel_free(cancel) };
+    assert_eq!(cancelled, PiwiStatus::Cancelled);
+    assert_eq!(invalid, PiwiStatus::InvalidArgument);
+}
@@ -0,0 +1,676 @@
+use std::{
+    ffi::{CStr, CString, c_void},
+    fmt, mem,
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+use alloy_primitives::{Address, B256};
+
+use crate::{
+    cli,
+    error::{Error, Result},
+    pattern::Pattern,
+};
+
+#[cfg(feature = "cuda")]
+mod cuda;
+mod opencl;
+mod tune;
+#[cfg(feature = "wgpu")]
+mod webgpu;
+
+/// Sources of a kernel, in the language of each driver.
+struct Program {
+    /// The OpenCL C source, also built by CUDA.
+    opencl: &'static str,
+    /// The WGSL source, built by wgpu.
+    #[cfg(feature = "wgpu")]
+    wgsl: &'static str,
+    /// The name of the kernel function in every source.
+    name: &'static str,
+}
+
+/// The CREATE2 search kernel.
+const CREATE2_PROGRAM: Program = Program {
+    opencl: include_str!("gpu/create2.cl"),
+    #[cfg(feature = "wgpu")]
+    wgsl: include_str!("gpu/create2.wgsl"),
+    name: "create2_search",
+};
+
+/// Number of bytes absorbed by each Keccak256 permutation.
+const RATE: usize = 136;
+
+/// Offset of the salt in the CREATE2 preimage.
+const SALT_OFFSET: usize = 1 + 20;
+
+/// A shared library of a GPU driver, loaded at runtime so that mining on the
+/// CPU does not depend on it.
+#[derive(Debug, Clone, Copy)]
+struct Library(*mut c_void);
+
+impl Library {
+    /// Loads the first library found at one of the given paths, which stays
+    /// loaded for the rest of the process.
+    fn open(paths: &[&str]) -> Option<Self> {
+        paths.iter().find_map(|path| {
+            let path = CString::new(*path).expect("library paths have no NUL byte");
+            let handle = load_library(&path);
+            (!handle.is_null()).then_some(Self(handle))
+        })
+    }
+
+    /// Resolves a function of the library by its C name, if it exports it.
+    ///
+    /// # Safety
+    ///
+    /// `F` must be a function pointer type with the signature of the function.
+    unsafe fn function<F>(self, name: &str) -> Option<F> {
+        let name = CString::new(name).expect("function names have no NUL byte");
+        let symbol = find_symbol(self.0, &name);
+        // SAFETY: the caller guarantees the type of the symbol
+        (!symbol.is_null()).then(|| unsafe { mem::transmute_copy::<*mut c_void, F>(&symbol) })
+    }
+}
+
+/// Loads a shared library with the dynamic loader, or returns null.
+#[cfg(unix)]
+fn load_library(path: &CStr) -> *mut c_void {
+    // SAFETY: the path is a valid C string
+    unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) }
+}
+
+/// Loads no library, as GPU drivers are only loaded on Unix.
+#[cfg(not(unix))]
+fn load_library(_: &CStr) -> *mut c_void {
+    std::ptr::null_mut()
+}
+
+/// Resolves a symbol of a loaded library, or returns null.
+#[cfg(unix)]
+fn find_symbol(handle: *mut c_void, name: &CStr) -> *mut c_void {
+    // SAFETY: the handle was returned by `dlopen` and the name is a valid C
+    // string
+    unsafe { libc::dlsym(handle, name.as_ptr()) }
+}
+
+/// Resolves no symbol, as GPU drivers are only loaded on Unix.
+#[cfg(not(unix))]
+fn find_symbol(_: *mut c_void, _: &CStr) -> *mut c_void {
+    std::ptr::null_mut()
+}
+
+/// A GPU mining kernels can be built for.
+#[derive(Debug, Clone)]
+pub(super) struct Device {
+    /// The driver the GPU is used through, with its handle.
+    driver: Driver,
+}
+
+/// Drivers GPUs can be used through.
+#[derive(Debug, Clone)]
+enum Driver {
+    /// The OpenCL driver of the GPU vendor.
+    OpenCl(opencl::Device),
+    /// The CUDA driver of an NVIDIA GPU.
+    #[cfg(feature = "cuda")]
+    Cuda(cuda::Device),
+    /// The graphics API of the GPU, through wgpu.
+    #[cfg(feature = "wgpu")]
+    WebGpu(webgpu::Device),
+}
+
+impl Device {
+    /// Returns the name of the device, as reported by its driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the driver cannot be queried.
+    pub(super) fn name(&self) -> Result<String> {
+        match &self.driver {
+            Driver::OpenCl(device) => device.name(),
+            #[cfg(feature = "cuda")]
+            Driver::Cuda(device) => device.name(),
+            #[cfg(feature = "wgpu")]
+            Driver::WebGpu(device) => device.name(),
+        }
+    }
+
+    /// Returns the size of the memory of the device, in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the driver cannot be queried.
+    pub(super) fn memory(&self) -> Result<u64> {
+        match &self.driver {
+            Driver::OpenCl(device) => device.memory(),
+            #[cfg(feature = "cuda")]
+            Driver::Cuda(device) => device.memory(),
+            #[cfg(feature = "wgpu")]
+            Driver::WebGpu(device) => device.memory(),
+        }
+    }
+
+    /// Returns the name of the driver the device is used through.
+    pub(super) fn backend(&self) -> &'static str {
+        match &self.driver {
+            Driver::OpenCl(_) => "OpenCL",
+            #[cfg(feature = "cuda")]
+            Driver::Cuda(_) => "CUDA",
+            #[cfg(feature = "wgpu")]
+            Driver::WebGpu(device) => device.backend(),
+        }
+    }
+
+    /// Builds a kernel for the device from the source of its driver.
+    ///
+    /// # Arguments
+    /// * `program` - The sources of the kernel.
+    /// * `definitions` - The values of the constants the source leaves
+    ///   open, passed as `-D` definitions or pipeline constants.
+    /// * `args` - The sizes of the buffers passed to the kernel, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program does not build, with the build log.
+    fn kernel(
+        &self,
+        program: &Program,
+        definitions: &[(&str, usize)],
+        args: &[usize],
+    ) -> Result<Launcher> {
+        let options = definitions
+            .iter()
+            .map(|(name, value)| format!("-D {name}={value}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let (source, name) = (program.opencl, program.name);
+        let kind = match &self.driver {
+            Driver::OpenCl(device) => {
+                LauncherKind::OpenCl(device.kernel(source, &options, name, args)?)
+            }
+            #[cfg(feature = "cuda")]
+            Driver::Cuda(device) => {
+                LauncherKind::Cuda(device.kernel(source, &options, name, args)?)
+            }
+            #[cfg(feature = "wgpu")]
+            Driver::WebGpu(device) => LauncherKind::WebGpu(Box::new(device.kernel(
+                program.wgsl,
+                definitions,
+                name,
+                args,
+            )?)),
+        };
+        let (max_group_size, simd_width) = match &kind {
+            LauncherKind::OpenCl(kernel) => kernel.group_sizes()?,
+            #[cfg(feature = "cuda")]
+            LauncherKind::Cuda(kernel) => kernel.group_sizes()?,
+            #[cfg(feature = "wgpu")]
+            LauncherKind::WebGpu(kernel) => kernel.group_sizes()?,
+        };
+        Ok(Launcher {
+            kind,
+            max_group_size,
+            simd_width,
+        })
+    }
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.name().unwrap_or_else(|_| "unknown device".to_string());
+        write!(f, "{name} ({})", self.backend())
+    }
+}
+
+/// Returns the GPUs available to mine on.
+///
+/// NVIDIA GPUs are driven through CUDA when built in and installed, as their
+/// OpenCL driver is slower, and through OpenCL otherwise. GPUs are only
+/// driven through wgpu when built in and neither driver reports any, so that
+/// no GPU is listed twice. OpenCL and CUDA are only loaded on Unix, so other
+/// platforms only find GPUs through wgpu, as does macOS when it is built in,
+/// to use Metal rather than OpenCL.
+///
+/// # Errors
+///
+/// Returns an error if no GPU driver is installed or none reports a GPU, or
+/// on platforms without any GPU driver built in.
+pub(super) fn devices() -> Result<Vec<Device>> {
+    let mut drivers = Vec::new();
+    let mut error = None;
+    // Only load the OpenCL and CUDA drivers where the loader is supported,
+    // and prefer Metal to the deprecated OpenCL framework of macOS
+    let metal = cfg!(all(target_os = "macos", feature = "wgpu"));
+    if cfg!(unix) && !metal {
+        #[cfg(feature = "cuda")]
+        match cuda::devices() {
+            Ok(devices) => drivers.extend(devices.into_iter().map(Driver::Cuda)),
+            Err(err) => error = Some(err),
+        }
+
+        // Skip the GPUs already found through CUDA
+        let cuda = !drivers.is_empty();
+        match opencl::devices() {
+            Ok(devices) => drivers.extend(
+                devices
+                    .into_iter()
+                    .filter(|device| !cuda || !device.is_nvidia())
+                    .map(Driver::OpenCl),
+            ),
+            Err(err) => error = error.or(Some(err)),
+        }
+    } else if cfg!(not(feature = "wgpu")) {
+        error = Some(Error::Gpu("no GPU driver on this platform".to_string()));
+    }
+
+    // Fall back to the graphics APIs without OpenCL or CUDA GPUs
+    #[cfg(feature = "wgpu")]
+    if drivers.is_empty() {
+        match webgpu::devices() {
+            Ok(devices) => drivers.extend(devices.into_iter().map(Driver::WebGpu)),
+            Err(err) => error = error.or(Some(err)),
+        }
+    }
+
+    if drivers.is_empty() {
+        return Err(error.unwrap_or_else(|| Error::Gpu("no GPU found".to_string())));
+    }
+    Ok(drivers
+        .into_iter()
+        .map(|driver| Device { driver })
+        .collect())
+}
+
+/// Measures how many CREATE2 addresses per second a GPU derives and checks.
+///
+/// The search kernel runs on placeholder inputs, with a pattern no address
+/// realistically matches, until the duration has elapsed. Untuned GPUs are
+/// tuned along the way, so short measurements may miss their best sizes.
+///
+/// # Arguments
+/// * `device` - The GPU to measure.
+/// * `duration` - How long the measurement should run for.
+///
+/// # Errors
+///
+/// Returns an error if the kernel does not build or fails to run.
+pub(super) fn measure_hashrate(device: &Device, duration: Duration) -> Result<f64> {
+    let pattern = Pattern::parse("0", &"0".repeat(40))?;
+    let mut kernel = Create2Kernel::new(device, Address::ZERO, B256::ZERO, 24..32, &pattern)?;
+    let salt_base = [0u8; 32];
+
+    // Leave the first dispatch, which pays for the warm-up of the driver,
+    // out of the measurement
+    let mut nonces = kernel.batch_size();
+    kernel.search(&salt_base, 0..nonces)?;
+
+    let start = Instant::now();
+    let mut attempts = 0;
+    while start.elapsed() < duration {
+        let batch = kernel.batch_size();
+        kernel.search(&salt_base, nonces..nonces + batch)?;
+        nonces += batch;
+        attempts += batch;
+    }
+    Ok(attempts as f64 / start.elapsed().as_secs_f64())
+}
+
+/// A kernel built by the driver of a GPU, with the limits of its work groups.
+#[derive(Debug)]
+struct Launcher {
+    /// The kernel, as built by the driver.
+    kind: LauncherKind,
+    /// The largest number of work items per work group.
+    max_group_size: usize,
+    /// The number of work items the GPU runs in lockstep.
+    simd_width: usize,
+}
+
+/// Kernels built by each driver.
+#[derive(Debug)]
+enum LauncherKind {
+    /// A kernel built by OpenCL.
+    OpenCl(opencl::Kernel),
+    /// A kernel compiled by NVRTC and loaded by CUDA.
+    #[cfg(feature = "cuda")]
+    Cuda(cuda::Kernel),
+    /// A compute pipeline built by wgpu.
+    #[cfg(feature = "wgpu")]
+    WebGpu(Box<webgpu::Kernel>),
+}
+
+impl Launcher {
+    /// Copies bytes to the buffer of an argument, blocking until done.
+    fn write(&mut self, arg: usize, bytes: &[u8]) -> Result<()> {
+        match &mut self.kind {
+            LauncherKind::OpenCl(kernel) => kernel.write(arg, bytes),
+            #[cfg(feature = "cuda")]
+            LauncherKind::Cuda(kernel) => kernel.write(arg, bytes),
+            #[cfg(feature = "wgpu")]
+            LauncherKind::WebGpu(kernel) => kernel.write(arg, bytes),
+        }
+    }
+
+    /// Copies the buffer of an argument to bytes, blocking until done.
+    fn read(&mut self, arg: usize, bytes: &mut [u8]) -> Result<()> {
+        match &mut self.kind {
+            LauncherKind::OpenCl(kernel) => kernel.read(arg, bytes),
+            #[cfg(feature = "cuda")]
+            LauncherKind::Cuda(kernel) => kernel.read(arg, bytes),
+            #[cfg(feature = "wgpu")]
+            LauncherKind::WebGpu(kernel) => kernel.read(arg, bytes),
+        }
+    }
+
+    /// Runs the kernel over a number of work items, rounded up to whole work
+    /// groups of the given size, and waits for it.
+    fn run(&mut self, items: usize, group_size: usize) -> Result<()> {
+        let items = items.next_multiple_of(group_size);
+        match &mut self.kind {
+            LauncherKind::OpenCl(kernel) => kernel.run(items, group_size),
+            #[cfg(feature = "cuda")]
+            LauncherKind::Cuda(kernel) => kernel.run(items, group_size),
+            #[cfg(feature = "wgpu")]
+            LauncherKind::WebGpu(kernel) => kernel.run(items, group_size),
+        }
+    }
+}
+
+impl cli::Gpu {
+    /// Returns the GPUs selected to mine on, or none to mine on the CPU.
+    ///
+    /// Each GPU gets its own kernel and queue, and claims dispatches from the
+    /// chunks independently, so faster GPUs search more nonces.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if GPUs were requested but none is available, or a
+    /// selected index has no GPU.
+    pub(super) fn devices(&self) -> Result<Vec<Device>> {
+        if !self.gpu && self.gpu_devices.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut devices: Vec<_> = devices()?.into_iter().enumerate().collect();
+
+        // Keep the selected GPUs, in the order they were given
+        if !self.gpu_devices.is_empty() {
+            let found = devices.len();
+            devices =
+                self.gpu_devices
+                    .iter()
+                    .map(|&index| {
+                        devices.get(index).cloned().ok_or_else(|| {
+                            Error::Gpu(format!("no GPU {index}, only {found} found"))
+                        })
+                    })
+                    .collect::<Result<_>>()?;
+        }
+
+        for (index, device) in &devices {
+            eprintln!("Mining on GPU {index}: {device}");
+        }
+        Ok(devices.into_iter().map(|(_, device)| device).collect())
+    }
+}
+
+/// A search kernel built for a GPU, exploring the nonces of salt buffers of
+/// `N` bytes.
+pub(super) trait Kernel<const N: usize>: Send {
+    /// Returns the number of nonces the next call of [`Kernel::search`]
+    /// searches, which changes while the kernel is tuned.
+    fn batch_size(&self) -> u64;
+
+    /// Searches a range of nonces of the salt buffers sharing a random
+    /// segment, returning the smallest matching nonce, if any.
+    ///
+    /// # Arguments
+    /// * `salt_base` - The salt buffer with the random segment of the batch.
+    /// * `nonces` - The nonces to explore, at most [`Kernel::batch_size`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the kernel fails to run.
+    fn search(&mut self, salt_base: &[u8; N], nonces: Range<u64>) -> Result<Option<u64>>;
+}
+
+/// CREATE2 search kernel, deriving the address of each nonce and checking it
+/// against the pattern on the GPU.
+#[derive(Debug)]
+pub(super) struct Create2Kernel {
+    /// The kernel built for the device.
+    kernel: Launcher,
+    /// The address of the CREATE2 factory contract.
+    factory: Address,
+    /// The keccak256 hash of the contract initialization code.
+    init_code_hash: B256,
+    /// The nonce segment of the salt.
+    nonce: Range<usize>,
+    /// The masks and values of the pattern over the address lanes.
+    pattern: ([u64; 3], [u64; 3]),
+    /// The GPU model and kernel the launch sizes are tuned for.
+    model: String,
+    /// The tuner of the launch sizes.
+    tuner: tune::Tuner,
+}
+
+impl Create2Kernel {
+    /// Builds the CREATE2 search kernel for a device, launched with the
+    /// sizes saved for its model if it was tuned before.
+    ///
+    /// # Arguments
+    /// * `device` - The GPU to run the kernel on.
+    /// * `factory` - The address of the CREATE2 factory contract.
+    /// * `init_code_hash` - The keccak256 hash of the contract initialization
+    ///   code.
+    /// * `nonce` - The nonce segment of the salt, at most 8 bytes.
+    /// * `pattern` - The pattern addresses have to match.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the kernel does not build on the device.
+    pub(super) fn new(
+        device: &Device,
+        factory: Address,
+        init_code_hash: B256,
+        nonce: Range<usize>,
+        pattern: &Pattern,
+    ) -> Result<Self> {
+        // Bake the position of the nonce into the kernel, so that its bytes
+        // are written with constant shifts
+        let kernel = device.kernel(
+            &CREATE2_PROGRAM,
+            &[
+                ("NONCE_OFFSET", SALT_OFFSET + nonce.start),
+                ("NONCE_LENGTH", nonce.len()),
+            ],
+            &[RATE, size_of::<[u64; 8]>(), size_of::<u32>()],
+        )?;
+        let model = format!("{device}: create2_search");
+        let tuner = tune::Tuner::new(
+            tune::load_profile(&model),
+            kernel.max_group_size,
+            kernel.simd_width,
+        );
+        Ok(Self {
+            kernel,
+            factory,
+            init_code_hash,
+            nonce,
+            pattern: pattern.hash_lanes(),
+            model,
+            tuner,
+        })
+    }
+}
+
+impl Kernel<32> for Create2Kernel {
+    fn batch_size(&self) -> u64 {
+        self.tuner.sizes().batch
+    }
+
+    fn search(&mut self, salt_base: &[u8; 32], nonces: Range<u64>) -> Result<Option<u64>> {
+        let started = Instant::now();
+        let sizes = self.tuner.sizes();
+        let block = create2_block(self.factory, salt_base, self.init_code_hash, &self.nonce);
+        let (masks, values) = self.pattern;
+        let params = [
+            [nonces.start, nonces.end - nonces.start].as_slice(),
+            &masks,
+            &values,
+        ]
+        .concat();
+        self.kernel.write(0, &lanes_to_bytes(&block))?;
+        self.kernel.write(1, &lanes_to_bytes(&params))?;
+        self.kernel.write(2, &u32::MAX.to_ne_bytes())?;
+
+        // The work items past the nonces, up to a whole group, do nothing
+        self.kernel
+            .run((nonces.end - nonces.start) as usize, sizes.group)?;
+
+        let mut result = [0u8; 4];
+        self.kernel.read(2, &mut result)?;
+
+        // Save the fastest sizes for the next searches once calibrated
+        if let Some(tuned) = self
+            .tuner
+            .record(sizes, nonces.end - nonces.start, started.elapsed())
+        {
+            eprintln!(
+                "Tuned {} to work groups of {} and {} nonces per dispatch",
+                self.model, tuned.group, tuned.batch
+            );
+            if let Err(err) = tune::save_profile(&self.model, tuned) {
+                eprintln!("warning: could not save the GPU tuning profile: {err}");
+            }
+        }
+
+        Ok(match u32::from_ne_bytes(result) {
+            u32::MAX => None,
+            id => Some(nonces.start + u64::from(id)),
+        })
+    }
+}
+
+/// Builds the padded Keccak256 block of the CREATE2 preimage of a salt, with
+/// the bytes of its nonce segment cleared, as little-endian lanes.
+fn create2_block(
+    factory: Address,
+    salt: &[u8; 32],
+    init_code_hash: B256,
+    nonce: &Range<usize>,
+) -> [u64; RATE / 8] {
+    let mut salt = *salt;
+    salt[nonce.clone()].fill(0);
+    let preimage = [
+        &[0xff],
+        factory.as_slice(),
+        &salt,
+        init_code_hash.as_slice(),
+    ]
+    .concat();
+
+    // Pad the preimage with the Keccak 0x01 ... 0x80 padding
+    let mut block = [0u8; RATE];
+    block[..preimage.len()].copy_from_slice(&preimage);
+    block[preimage.len()] ^= 0x01;
+    block[RATE - 1] ^= 0x80;
+    std::array::from_fn(|lane| {
+        u64::from_le_bytes(
+            block[8 * lane..8 * lane + 8]
+                .try_into()
+                .expect("lanes are 8 bytes"),
+        )
+    })
+}
+
+/// Lays out lanes as the little-endian bytes the kernels read them as.
+fn lanes_to_bytes(lanes: &[u64]) -> Vec<u8> {
+    lanes.iter().flat_map(|lane| lane.to_le_bytes()).collect()
+}
+
+#[test]
+fn test_create2_block() {
+    use alloy_primitives::{address, b256};
+
+    let factory = address!("0x4e59b44847b379578588920cA78FbF26c0B4956C");
+    let init_code_hash =
+        b256!("0x1111111111111111111111111111111111111111111111111111111111111111");
+    let (nonce, value) = (26..32, 0x0102_0304_0506u64);
+    let mut salt = [7u8; 32];
+    salt[nonce.clone()].copy_from_slice(&value.to_be_bytes()[2..]);
+
+    // Write the nonce bytes into the block as the kernel does
+    let mut state = [0u64; 25];
+    state[..RATE / 8].copy_from_slice(&create2_block(factory, &salt, init_code_hash, &nonce));
+    for byte in 0..nonce.len() {
+        let position = SALT_OFFSET + nonce.start + byte;
+        let shifted = value >> (8 * (nonce.len() - 1 - byte)) & 0xff;
+        state[position / 8] |= shifted << (8 * (position % 8));
+    }
+    keccak::f1600(&mut state);
+
+    let hash = lanes_to_bytes(&state[..4]);
+    let address = factory.create2(salt, init_code_hash);
+    assert_eq!(Address::from_slice(&hash[12..]), address);
+
+    // The pattern lanes select the flag bits of the address
+    let flags = crate::pattern::flags_value(address) & 0x3fff;
+    let (masks, values) = Pattern::parse(&format!("{flags:x}"), "")
+        .unwrap()
+        .hash_lanes();
+    assert_eq!(masks, [0, 0, 0xff3f << 48]);
+    assert!((0..3).all(|lane| state[lane + 1] & masks[lane] == values[lane]));
+}
+
+#[cfg(feature = "wgpu")]
+#[test]
+fn test_wgpu_kernel() {
+    use alloy_primitives::{address, b256};
+
+    use crate::core::create2_address;
+
+    // Software rasterizers such as llvmpipe stand in for a GPU, if any
+    let Some(device) = webgpu::adapters(true).into_iter().next() else {
+        eprintln!("skipping test_wgpu_kernel: wgpu found no adapter");
+        return;
+    };
+    let device = Device {
+        driver: Driver::WebGpu(device),
+    };
+
+    // Cross the 32-bit boundary of the nonce, which WGSL carries by hand
+    let factory = address!("0x4e59b44847b379578588920cA78FbF26c0B4956C");
+    let init_code_hash =
+        b256!("0x1111111111111111111111111111111111111111111111111111111111111111");
+    let pattern = Pattern::parse("0", "").unwrap();
+    let mut kernel =
+        Create2Kernel::new(&device, factory, init_code_hash, 24..32, &pattern).unwrap();
+    let salt_base = [7u8; 32];
+    let address = |nonce: u64| {
+        let mut salt = salt_base;
+        salt[24..32].copy_from_slice(&nonce.to_be_bytes());
+        create2_address(factory, salt.into(), init_code_hash)
+    };
+    let matcher = pattern.matcher();
+    for nonces in [
+        (1 << 32) - 100_000..1 << 32,
+        (1 << 32) - 10..(1 << 32) + 100_000,
+    ] {
+        // The kernel returns the smallest matching nonce, as derived on the
+        // CPU
+        let found = kernel.search(&salt_base, nonces.clone()).unwrap();
+        assert_eq!(
+            found,
+            nonces
+                .clone()
+                .find(|&nonce| matcher.matches(&address(nonce)))
+        );
+        let found = found.expect("one in 16384 addresses matches");
+        assert_eq!(
+            kernel.search(&salt_base, nonces.start..found).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,120 @@
+// Searches CREATE2 salts on the GPU, one nonce per work item.
+//
+// The host absorbs the fixed bytes of the preimage `0xff ++ factory ++ salt ++
+// init_code_hash` into a padded Keccak256 block with the nonce bytes cleared,
+// so each work item only writes its nonce, runs the permutation and checks
+// the address against the pattern. The smallest matching work item of the
+// dispatch is the only thing written back.
+//
+// Build definitions:
+// - NONCE_OFFSET: offset of the first nonce byte in the block.
+// - NONCE_LENGTH: number of nonce bytes, written big-endian.
+
+#define RATE_LANES 17
+
+__constant ulong ROUND_CONSTANTS[24] = {
+    0x0000000000000001UL, 0x0000000000008082UL, 0x800000000000808aUL,
+    0x8000000080008000UL, 0x000000000000808bUL, 0x0000000080000001UL,
+    0x8000000080008081UL, 0x8000000000008009UL, 0x000000000000008aUL,
+    0x0000000000000088UL, 0x0000000080008009UL, 0x000000008000000aUL,
+    0x000000008000808bUL, 0x800000000000008bUL, 0x8000000000008089UL,
+    0x8000000000008003UL, 0x8000000000008002UL, 0x8000000000000080UL,
+    0x000000000000800aUL, 0x800000008000000aUL, 0x8000000080008081UL,
+    0x8000000000008080UL, 0x0000000080000001UL, 0x8000000080008008UL,
+};
+
+// Rotations of the lanes in the order they are visited by the pi step.
+__constant uint RHO[24] = {
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14,
+    27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+};
+
+// Lanes visited by the pi step, each moved to the place of the next one.
+__constant uint PI[24] = {
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4,
+    15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+};
+
+// Applies Keccak-f[1600] to a state held in registers.
+inline void keccak_f1600(ulong *state) {
+    for (uint round = 0; round < 24; round++) {
+        // Theta: mix each column with its two neighbours
+        ulong columns[5];
+#pragma unroll
+        for (uint x = 0; x < 5; x++) {
+            columns[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+#pragma unroll
+        for (uint x = 0; x < 5; x++) {
+            ulong mix = columns[(x + 4) % 5] ^ rotate(columns[(x + 1) % 5], 1UL);
+#pragma unroll
+            for (uint y = 0; y < 25; y += 5) {
+                state[y + x] ^= mix;
+            }
+        }
+
+        // Rho and pi: rotate the lanes and move them to their new place
+        ulong last = state[1];
+#pragma unroll
+        for (uint step = 0; step < 24; step++) {
+            ulong next = state[PI[step]];
+            state[PI[step]] = rotate(last, (ulong)RHO[step]);
+            last = next;
+        }
+
+        // Chi: combine each lane with the next two of its row
+#pragma unroll
+        for (uint y = 0; y < 25; y += 5) {
+            ulong row[5];
+#pragma unroll
+            for (uint x = 0; x < 5; x++) {
+                row[x] = state[y + x];
+            }
+#pragma unroll
+            for (uint x = 0; x < 5; x++) {
+                state[y + x] = row[x] ^ (~row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // Iota: break the symmetry of the rounds
+        state[0] ^= ROUND_CONSTANTS[round];
+    }
+}
+
+// Arguments:
+// - block: the padded block, as 17 little-endian lanes.
+// - params: the first nonce, the number of nonces, then the masks and the
+//   values of the pattern over hash lanes 1 to 3, which cover the address.
+// - result: the smallest matching work item, left untouched without match.
+__kernel void create2_search(
+    __global const ulong *block,
+    __global const ulong *params,
+    __global uint *result
+) {
+    uint id = get_global_id(0);
+    if (id >= params[1]) {
+        return;
+    }
+    ulong nonce = params[0] + id;
+
+    // Load the block and write the nonce bytes into their lanes
+    ulong state[25];
+#pragma unroll
+    for (uint lane = 0; lane < 25; lane++) {
+        state[lane] = lane < RATE_LANES ? block[lane] : 0;
+    }
+#pragma unroll
+    for (uint byte = 0; byte < NONCE_LENGTH; byte++) {
+        uint position = NONCE_OFFSET + byte;
+        ulong value = (nonce >> (8 * (NONCE_LENGTH - 1 - byte))) & 0xff;
+        state[position / 8] |= value << (8 * (position % 8));
+    }
+
+    keccak_f1600(state);
+
+    // The address is the last 20 bytes of the hash
+    if ((state[1] & params[2]) == params[5] && (state[2] & params[3]) == params[6] &&
+        (state[3] & params[4]) == params[7]) {
+        atomic_min(result, id);
+    }
+}
@@ -0,0 +1,158 @@
+// WGSL port of the CREATE2 search kernel in create2.cl, built by wgpu.
+//
+// WGSL has no 64-bit integers, so each Keccak lane is held as a vec2<u32> of
+// its low and high halves. The buffers have the same little-endian layout as
+// the OpenCL kernel arguments, viewed as 32-bit words, so the host fills them
+// the same way.
+//
+// Dispatches of more than 65535 workgroups spread them over a second
+// dimension, so invocations are numbered from their workgroup instead of
+// their global position.
+//
+// Pipeline constants:
+// - NONCE_OFFSET: offset of the first nonce byte in the block.
+// - NONCE_LENGTH: number of nonce bytes, written big-endian.
+// - GROUP_SIZE: number of invocations per workgroup.
+
+override NONCE_OFFSET: u32;
+override NONCE_LENGTH: u32;
+override GROUP_SIZE: u32 = 256;
+
+// The padded block, as 17 little-endian lanes.
+@group(0) @binding(0) var<storage, read> preimage: array<vec2<u32>, 17>;
+// The first nonce, the number of nonces, then the masks and the values of the
+// pattern over hash lanes 1 to 3, which cover the address.
+@group(0) @binding(1) var<storage, read> params: array<vec2<u32>, 8>;
+// The smallest matching invocation, left untouched without match.
+@group(0) @binding(2) var<storage, read_write> result: atomic<u32>;
+
+var<private> ROUND_CONSTANTS: array<vec2<u32>, 24> = array(
+    vec2(0x00000001u, 0x00000000u), vec2(0x00008082u, 0x00000000u),
+    vec2(0x0000808au, 0x80000000u), vec2(0x80008000u, 0x80000000u),
+    vec2(0x0000808bu, 0x00000000u), vec2(0x80000001u, 0x00000000u),
+    vec2(0x80008081u, 0x80000000u), vec2(0x00008009u, 0x80000000u),
+    vec2(0x0000008au, 0x00000000u), vec2(0x00000088u, 0x00000000u),
+    vec2(0x80008009u, 0x00000000u), vec2(0x8000000au, 0x00000000u),
+    vec2(0x8000808bu, 0x00000000u), vec2(0x0000008bu, 0x80000000u),
+    vec2(0x00008089u, 0x80000000u), vec2(0x00008003u, 0x80000000u),
+    vec2(0x00008002u, 0x80000000u), vec2(0x00000080u, 0x80000000u),
+    vec2(0x0000800au, 0x00000000u), vec2(0x8000000au, 0x80000000u),
+    vec2(0x80008081u, 0x80000000u), vec2(0x00008080u, 0x80000000u),
+    vec2(0x80000001u, 0x00000000u), vec2(0x80008008u, 0x80000000u),
+);
+
+// Rotations of the lanes in the order they are visited by the pi step.
+var<private> RHO: array<u32, 24> = array(
+    1u, 3u, 6u, 10u, 15u, 21u, 28u, 36u, 45u, 55u, 2u, 14u,
+    27u, 41u, 56u, 8u, 25u, 43u, 62u, 18u, 39u, 61u, 20u, 44u,
+);
+
+// Lanes visited by the pi step, each moved to the place of the next one.
+var<private> PI: array<u32, 24> = array(
+    10u, 7u, 11u, 17u, 18u, 3u, 5u, 16u, 8u, 21u, 24u, 4u,
+    15u, 23u, 19u, 13u, 12u, 2u, 20u, 14u, 22u, 9u, 6u, 1u,
+);
+
+// Rotates a lane left by 0 to 63 bits.
+fn rotl(lane: vec2<u32>, bits: u32) -> vec2<u32> {
+    // Rotating by 32 bits swaps the halves, and shifts must stay below 32
+    var value = lane;
+    var shift = bits;
+    if (shift >= 32u) {
+        value = value.yx;
+        shift -= 32u;
+    }
+    if (shift == 0u) {
+        return value;
+    }
+    return vec2(
+        (value.x << shift) | (value.y >> (32u - shift)),
+        (value.y << shift) | (value.x >> (32u - shift)),
+    );
+}
+
+// Applies Keccak-f[1600] to a state held in registers.
+fn keccak_f1600(state: ptr<function, array<vec2<u32>, 25>>) {
+    for (var index = 0u; index < 24u; index++) {
+        // Theta: mix each column with its two neighbours
+        var columns: array<vec2<u32>, 5>;
+        for (var x = 0u; x < 5u; x++) {
+            columns[x] = (*state)[x] ^ (*state)[x + 5u] ^ (*state)[x + 10u] ^
+                (*state)[x + 15u] ^ (*state)[x + 20u];
+        }
+        for (var x = 0u; x < 5u; x++) {
+            let mix = columns[(x + 4u) % 5u] ^ rotl(columns[(x + 1u) % 5u], 1u);
+            for (var y = 0u; y < 25u; y += 5u) {
+                (*state)[y + x] ^= mix;
+            }
+        }
+
+        // Rho and pi: rotate the lanes and move them to their new place
+        var last = (*state)[1];
+        for (var visit = 0u; visit < 24u; visit++) {
+            let next = (*state)[PI[visit]];
+            (*state)[PI[visit]] = rotl(last, RHO[visit]);
+            last = next;
+        }
+
+        // Chi: combine each lane with the next two of its row
+        for (var y = 0u; y < 25u; y += 5u) {
+            var row: array<vec2<u32>, 5>;
+            for (var x = 0u; x < 5u; x++) {
+                row[x] = (*state)[y + x];
+            }
+            for (var x = 0u; x < 5u; x++) {
+                (*state)[y + x] = row[x] ^ (~row[(x + 1u) % 5u] & row[(x + 2u) % 5u]);
+            }
+        }
+
+        // Iota: break the symmetry of the rounds
+        (*state)[0] ^= ROUND_CONSTANTS[index];
+    }
+}
+
+@compute @workgroup_size(GROUP_SIZE)
+fn create2_search(
+    @builtin(workgroup_id) group: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+    @builtin(local_invocation_index) local: u32,
+) {
+    let id = (group.y * groups.x + group.x) * GROUP_SIZE + local;
+    let count = params[1];
+    if (count.y == 0u && id >= count.x) {
+        return;
+    }
+
+    // Add the invocation to the first nonce, carrying into the high half
+    let first = params[0];
+    let low = first.x + id;
+    let nonce = vec2(low, first.y + select(0u, 1u, low < first.x));
+
+    // Load the block and write the nonce bytes into their lanes
+    var state: array<vec2<u32>, 25>;
+    for (var lane = 0u; lane < 17u; lane++) {
+        state[lane] = preimage[lane];
+    }
+    for (var index = 0u; index < NONCE_LENGTH; index++) {
+        let shift = 8u * (NONCE_LENGTH - 1u - index);
+        let value = select(nonce.y >> (shift - 32u), nonce.x >> shift, shift < 32u) & 0xffu;
+        let position = NONCE_OFFSET + index;
+        let bit = 8u * (position % 8u);
+        if (bit < 32u) {
+            state[position / 8u].x |= value << bit;
+        } else {
+            state[position / 8u].y |= value << (bit - 32u);
+        }
+    }
+
+    keccak_f1600(&state);
+
+    // The address is the last 20 bytes of the hash
+    var matches = true;
+    for (var lane = 0u; lane < 3u; lane++) {
+        matches = matches && all((state[lane + 1u] & params[lane + 2u]) == params[lane + 5u]);
+    }
+    if (matches) {
+        atomicMin(&result, id);
+    }
+}
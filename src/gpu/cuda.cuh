@@ -0,0 +1,17 @@
+// Maps the OpenCL C dialect of the kernels onto CUDA C++, so that NVRTC
+// builds the same source as the OpenCL drivers.
+
+typedef unsigned long long ulong;
+typedef unsigned int uint;
+
+#define __kernel extern "C" __global__
+#define __global
+#define __constant __constant__
+#define inline __device__ __forceinline__
+
+#define get_global_id(dim) (blockIdx.x * blockDim.x + threadIdx.x)
+#define atomic_min(pointer, value) atomicMin(pointer, value)
+
+// Rotations are by constants after unrolling, which the compiler turns into
+// funnel shifts
+#define rotate(value, bits) (((value) << (bits)) | ((value) >> (64 - (bits))))
@@ -0,0 +1,487 @@
+use std::{
+    ffi::{CStr, CString, c_char, c_uint, c_void},
+    ptr,
+    sync::OnceLock,
+};
+
+use super::Library;
+use crate::error::{Error, Result};
+
+/// Paths the CUDA driver is looked up at, in order.
+const DRIVER_LIBRARIES: [&str; 2] = ["libcuda.so.1", "libcuda.so"];
+
+/// Paths the NVRTC runtime compiler is looked up at, in order.
+const NVRTC_LIBRARIES: [&str; 5] = [
+    "libnvrtc.so",
+    "libnvrtc.so.13",
+    "libnvrtc.so.12",
+    "libnvrtc.so.11.2",
+    "/usr/local/cuda/lib64/libnvrtc.so",
+];
+
+/// Maps the OpenCL C dialect of the kernels onto CUDA C++, so that both
+/// drivers build the same kernel source.
+const PROLOGUE: &str = include_str!("cuda.cuh");
+
+/// `CU_DEVICE_ATTRIBUTE_WARP_SIZE`.
+const ATTRIBUTE_WARP_SIZE: i32 = 10;
+/// `CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR`.
+const ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR: i32 = 75;
+/// `CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR`.
+const ATTRIBUTE_COMPUTE_CAPABILITY_MINOR: i32 = 76;
+
+/// `CU_FUNC_ATTRIBUTE_MAX_THREADS_PER_BLOCK`.
+const FUNCTION_MAX_THREADS_PER_BLOCK: i32 = 0;
+
+/// Opaque CUDA object handle.
+type Handle = *mut c_void;
+
+/// Address of a buffer in the memory of a device.
+type DevicePointer = u64;
+
+/// Entry points of the CUDA driver and of NVRTC, resolved when first used.
+struct Api {
+    init: unsafe extern "C" fn(c_uint) -> i32,
+    device_get_count: unsafe extern "C" fn(*mut i32) -> i32,
+    device_get: unsafe extern "C" fn(*mut i32, i32) -> i32,
+    device_get_name: unsafe extern "C" fn(*mut c_char, i32, i32) -> i32,
+    device_get_attribute: unsafe extern "C" fn(*mut i32, i32, i32) -> i32,
+    device_total_mem: unsafe extern "C" fn(*mut usize, i32) -> i32,
+    primary_ctx_retain: unsafe extern "C" fn(*mut Handle, i32) -> i32,
+    primary_ctx_release: unsafe extern "C" fn(i32) -> i32,
+    ctx_set_current: unsafe extern "C" fn(Handle) -> i32,
+    ctx_synchronize: unsafe extern "C" fn() -> i32,
+    module_load_data: unsafe extern "C" fn(*mut Handle, *const c_void) -> i32,
+    module_unload: unsafe extern "C" fn(Handle) -> i32,
+    module_get_function: unsafe extern "C" fn(*mut Handle, Handle, *const c_char) -> i32,
+    func_get_attribute: unsafe extern "C" fn(*mut i32, i32, Handle) -> i32,
+    mem_alloc: unsafe extern "C" fn(*mut DevicePointer, usize) -> i32,
+    mem_free: unsafe extern "C" fn(DevicePointer) -> i32,
+    memcpy_htod: unsafe extern "C" fn(DevicePointer, *const c_void, usize) -> i32,
+    memcpy_dtoh: unsafe extern "C" fn(*mut c_void, DevicePointer, usize) -> i32,
+    launch_kernel: unsafe extern "C" fn(
+        Handle,
+        c_uint,
+        c_uint,
+        c_uint,
+        c_uint,
+        c_uint,
+        c_uint,
+        c_uint,
+        Handle,
+        *mut *mut c_void,
+        *mut *mut c_void,
+    ) -> i32,
+    nvrtc_create_program: unsafe extern "C" fn(
+        *mut Handle,
+        *const c_char,
+        *const c_char,
+        i32,
+        *const *const c_char,
+        *const *const c_char,
+    ) -> i32,
+    nvrtc_compile_program: unsafe extern "C" fn(Handle, i32, *const *const c_char) -> i32,
+    nvrtc_get_program_log_size: unsafe extern "C" fn(Handle, *mut usize) -> i32,
+    nvrtc_get_program_log: unsafe extern "C" fn(Handle, *mut c_char) -> i32,
+    nvrtc_get_ptx_size: unsafe extern "C" fn(Handle, *mut usize) -> i32,
+    nvrtc_get_ptx: unsafe extern "C" fn(Handle, *mut c_char) -> i32,
+    nvrtc_destroy_program: unsafe extern "C" fn(*mut Handle) -> i32,
+}
+
+/// The CUDA driver and NVRTC, or why they could not be loaded.
+static API: OnceLock<Result<Api, String>> = OnceLock::new();
+
+/// Returns the CUDA driver, loading and initializing it on first use.
+///
+/// # Errors
+///
+/// Returns an error if the CUDA driver or NVRTC is not installed, lacks an
+/// entry point or fails to initialize.
+fn api() -> Result<&'static Api> {
+    API.get_or_init(load)
+        .as_ref()
+        .map_err(|reason| Error::Gpu(reason.clone()))
+}
+
+/// Loads the CUDA driver and NVRTC, resolves their entry points and
+/// initializes the driver.
+fn load() -> Result<Api, String> {
+    let driver = Library::open(&DRIVER_LIBRARIES).ok_or("no CUDA driver is installed")?;
+    let nvrtc = Library::open(&NVRTC_LIBRARIES).ok_or("NVRTC is not installed")?;
+    macro_rules! symbol {
+        ($library:ident, $name:literal) => {
+            // SAFETY: the entry points have the signatures of the CUDA 11
+            // driver API and NVRTC
+            unsafe { $library.function($name) }.ok_or(concat!("the CUDA libraries lack ", $name))?
+        };
+    }
+    let api = Api {
+        init: symbol!(driver, "cuInit"),
+        device_get_count: symbol!(driver, "cuDeviceGetCount"),
+        device_get: symbol!(driver, "cuDeviceGet"),
+        device_get_name: symbol!(driver, "cuDeviceGetName"),
+        device_get_attribute: symbol!(driver, "cuDeviceGetAttribute"),
+        device_total_mem: symbol!(driver, "cuDeviceTotalMem_v2"),
+        primary_ctx_retain: symbol!(driver, "cuDevicePrimaryCtxRetain"),
+        primary_ctx_release: symbol!(driver, "cuDevicePrimaryCtxRelease_v2"),
+        ctx_set_current: symbol!(driver, "cuCtxSetCurrent"),
+        ctx_synchronize: symbol!(driver, "cuCtxSynchronize"),
+        module_load_data: symbol!(driver, "cuModuleLoadData"),
+        module_unload: symbol!(driver, "cuModuleUnload"),
+        module_get_function: symbol!(driver, "cuModuleGetFunction"),
+        func_get_attribute: symbol!(driver, "cuFuncGetAttribute"),
+        mem_alloc: symbol!(driver, "cuMemAlloc_v2"),
+        mem_free: symbol!(driver, "cuMemFree_v2"),
+        memcpy_htod: symbol!(driver, "cuMemcpyHtoD_v2"),
+        memcpy_dtoh: symbol!(driver, "cuMemcpyDtoH_v2"),
+        launch_kernel: symbol!(driver, "cuLaunchKernel"),
+        nvrtc_create_program: symbol!(nvrtc, "nvrtcCreateProgram"),
+        nvrtc_compile_program: symbol!(nvrtc, "nvrtcCompileProgram"),
+        nvrtc_get_program_log_size: symbol!(nvrtc, "nvrtcGetProgramLogSize"),
+        nvrtc_get_program_log: symbol!(nvrtc, "nvrtcGetProgramLog"),
+        nvrtc_get_ptx_size: symbol!(nvrtc, "nvrtcGetPTXSize"),
+        nvrtc_get_ptx: symbol!(nvrtc, "nvrtcGetPTX"),
+        nvrtc_destroy_program: symbol!(nvrtc, "nvrtcDestroyProgram"),
+    };
+
+    // SAFETY: the driver is initialized once, before any other call
+    let status = unsafe { (api.init)(0) };
+    if status != 0 {
+        return Err(format!("cuInit failed with CUDA error {status}"));
+    }
+    Ok(api)
+}
+
+/// Turns a CUDA or NVRTC status code into an error naming the failed call.
+fn check(call: &str, status: i32) -> Result<()> {
+    if status == 0 {
+        return Ok(());
+    }
+    Err(Error::Gpu(format!(
+        "{call} failed with CUDA error {status}"
+    )))
+}
+
+/// A CUDA GPU, identified by its ordinal.
+#[derive(Debug, Clone)]
+pub(super) struct Device {
+    /// The device handle.
+    handle: i32,
+}
+
+/// Returns the CUDA GPUs.
+///
+/// # Errors
+///
+/// Returns an error if the CUDA driver or NVRTC is not installed, or the
+/// devices cannot be listed.
+pub(super) fn devices() -> Result<Vec<Device>> {
+    let api = api()?;
+    let mut count = 0;
+    // SAFETY: the count is written by the driver
+    check("cuDeviceGetCount", unsafe {
+        (api.device_get_count)(&mut count)
+    })?;
+    (0..count)
+        .map(|ordinal| {
+            let mut handle = 0;
+            // SAFETY: the ordinal is below the device count
+            check("cuDeviceGet", unsafe {
+                (api.device_get)(&mut handle, ordinal)
+            })?;
+            Ok(Device { handle })
+        })
+        .collect()
+}
+
+impl Device {
+    /// Returns the name of the device.
+    pub(super) fn name(&self) -> Result<String> {
+        let api = api()?;
+        let mut name = [0 as c_char; 256];
+        // SAFETY: the buffer is as large as the length passed to the driver
+        check("cuDeviceGetName", unsafe {
+            (api.device_get_name)(name.as_mut_ptr(), name.len() as i32, self.handle)
+        })?;
+        // SAFETY: the driver writes a NUL-terminated name within the buffer
+        let name = unsafe { CStr::from_ptr(name.as_ptr()) };
+        Ok(name.to_string_lossy().trim().to_string())
+    }
+
+    /// Returns the size of the memory of the device, in bytes.
+    pub(super) fn memory(&self) -> Result<u64> {
+        let api = api()?;
+        let mut memory = 0;
+        // SAFETY: the size is written by the driver
+        check("cuDeviceTotalMem", unsafe {
+            (api.device_total_mem)(&mut memory, self.handle)
+        })?;
+        Ok(memory as u64)
+    }
+
+    /// Reads an attribute of the device.
+    fn attribute(&self, attribute: i32) -> Result<i32> {
+        let api = api()?;
+        let mut value = 0;
+        // SAFETY: the value is written by the driver
+        check("cuDeviceGetAttribute", unsafe {
+            (api.device_get_attribute)(&mut value, attribute, self.handle)
+        })?;
+        Ok(value)
+    }
+
+    /// Compiles a kernel from OpenCL C source for this device, through the
+    /// CUDA prologue, and loads it.
+    ///
+    /// # Arguments
+    /// * `source` - The OpenCL C source of the program.
+    /// * `options` - The build options, e.g. `-D` definitions, separated by
+    ///   spaces.
+    /// * `name` - The name of the kernel function.
+    /// * `args` - The sizes of the buffers passed to the kernel, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program does not compile, with the compiler
+    /// log.
+    pub(super) fn kernel(
+        &self,
+        source: &str,
+        options: &str,
+        name: &str,
+        args: &[usize],
+    ) -> Result<Kernel> {
+        let api = api()?;
+
+        // Compile for the exact architecture of the device
+        let architecture = format!(
+            "--gpu-architecture=compute_{}{}",
+            self.attribute(ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR)?,
+            self.attribute(ATTRIBUTE_COMPUTE_CAPABILITY_MINOR)?
+        );
+        let ptx = compile(
+            api,
+            &format!("{PROLOGUE}\n{source}"),
+            &architecture,
+            options,
+        )?;
+
+        let mut kernel = Kernel {
+            device: self.handle,
+            context: ptr::null_mut(),
+            module: ptr::null_mut(),
+            function: ptr::null_mut(),
+            buffers: Vec::new(),
+        };
+        let name = CString::new(name).expect("kernel names have no NUL byte");
+
+        // SAFETY: every handle is checked before use, and the partially built
+        // kernel releases the ones created so far if a step fails
+        unsafe {
+            check(
+                "cuDevicePrimaryCtxRetain",
+                (api.primary_ctx_retain)(&mut kernel.context, self.handle),
+            )?;
+            check("cuCtxSetCurrent", (api.ctx_set_current)(kernel.context))?;
+            check(
+                "cuModuleLoadData",
+                (api.module_load_data)(&mut kernel.module, ptx.as_ptr().cast()),
+            )?;
+            check(
+                "cuModuleGetFunction",
+                (api.module_get_function)(&mut kernel.function, kernel.module, name.as_ptr()),
+            )?;
+            for &size in args {
+                let mut buffer = 0;
+                check("cuMemAlloc", (api.mem_alloc)(&mut buffer, size))?;
+                kernel.buffers.push(buffer);
+            }
+        }
+        Ok(kernel)
+    }
+}
+
+/// Compiles CUDA C++ source to PTX with NVRTC.
+///
+/// # Errors
+///
+/// Returns an error if the source does not compile, with the compiler log.
+fn compile(api: &Api, source: &str, architecture: &str, options: &str) -> Result<CString> {
+    let source = CString::new(source).expect("kernel sources have no NUL byte");
+    let options: Vec<CString> = [architecture]
+        .into_iter()
+        .chain(options.split_whitespace())
+        .map(|option| CString::new(option).expect("build options have no NUL byte"))
+        .collect();
+    let options: Vec<*const c_char> = options.iter().map(|option| option.as_ptr()).collect();
+
+    let mut program = ptr::null_mut();
+    // SAFETY: the strings outlive the program, and the buffers are as large
+    // as the sizes reported by NVRTC
+    unsafe {
+        check(
+            "nvrtcCreateProgram",
+            (api.nvrtc_create_program)(
+                &mut program,
+                source.as_ptr(),
+                c"piwi.cu".as_ptr(),
+                0,
+                ptr::null(),
+                ptr::null(),
+            ),
+        )?;
+        let compiled = (api.nvrtc_compile_program)(program, options.len() as i32, options.as_ptr());
+        let result = if compiled == 0 {
+            let mut size = 0;
+            check(
+                "nvrtcGetPTXSize",
+                (api.nvrtc_get_ptx_size)(program, &mut size),
+            )?;
+            let mut ptx = vec![0u8; size];
+            check(
+                "nvrtcGetPTX",
+                (api.nvrtc_get_ptx)(program, ptx.as_mut_ptr().cast()),
+            )?;
+            CString::from_vec_with_nul(ptx)
+                .map_err(|_| Error::Gpu("NVRTC returned malformed PTX".to_string()))
+        } else {
+            let mut size = 0;
+            (api.nvrtc_get_program_log_size)(program, &mut size);
+            let mut log = vec![0u8; size];
+            (api.nvrtc_get_program_log)(program, log.as_mut_ptr().cast());
+            Err(Error::Gpu(format!(
+                "the kernel failed to compile with NVRTC error {compiled}:\n{}",
+                String::from_utf8_lossy(&log).trim_end_matches('\0')
+            )))
+        };
+        (api.nvrtc_destroy_program)(&mut program);
+        result
+    }
+}
+
+/// A loaded kernel with its argument buffers, on the primary context of its
+/// device.
+#[derive(Debug)]
+pub(super) struct Kernel {
+    /// The device the kernel runs on.
+    device: i32,
+    /// The primary context of the device, retained by the kernel.
+    context: Handle,
+    /// The module the kernel was loaded from.
+    module: Handle,
+    /// The kernel function.
+    function: Handle,
+    /// The buffer bound to each argument of the kernel.
+    buffers: Vec<DevicePointer>,
+}
+
+// SAFETY: the kernel is only used by one thread at a time, which makes its
+// context current before each call
+unsafe impl Send for Kernel {}
+
+impl Kernel {
+    /// Returns the largest number of threads per block the kernel can be
+    /// launched with, which its register usage may keep below the limit of
+    /// the device, and the warp size blocks should be a multiple of.
+    pub(super) fn group_sizes(&self) -> Result<(usize, usize)> {
+        let api = api()?;
+        let mut max = 0;
+        // SAFETY: the value is written by the driver
+        check("cuFuncGetAttribute", unsafe {
+            (api.func_get_attribute)(&mut max, FUNCTION_MAX_THREADS_PER_BLOCK, self.function)
+        })?;
+        let warp = Device {
+            handle: self.device,
+        }
+        .attribute(ATTRIBUTE_WARP_SIZE)?;
+        Ok((max as usize, warp as usize))
+    }
+
+    /// Makes the context of the kernel current on the calling thread, which
+    /// changes between chunks.
+    fn enter(&self, api: &Api) -> Result<()> {
+        // SAFETY: the context is retained by the kernel
+        check("cuCtxSetCurrent", unsafe {
+            (api.ctx_set_current)(self.context)
+        })
+    }
+
+    /// Copies bytes to the buffer of an argument, blocking until done.
+    pub(super) fn write(&mut self, arg: usize, bytes: &[u8]) -> Result<()> {
+        let api = api()?;
+        self.enter(api)?;
+        // SAFETY: the buffer was allocated with the size of the argument,
+        // which the bytes fit in
+        check("cuMemcpyHtoD", unsafe {
+            (api.memcpy_htod)(self.buffers[arg], bytes.as_ptr().cast(), bytes.len())
+        })
+    }
+
+    /// Copies the buffer of an argument to bytes, blocking until done.
+    pub(super) fn read(&mut self, arg: usize, bytes: &mut [u8]) -> Result<()> {
+        let api = api()?;
+        self.enter(api)?;
+        // SAFETY: the buffer was allocated with the size of the argument,
+        // which the bytes fit in
+        check("cuMemcpyDtoH", unsafe {
+            (api.memcpy_dtoh)(bytes.as_mut_ptr().cast(), self.buffers[arg], bytes.len())
+        })
+    }
+
+    /// Runs the kernel over a range of threads and waits for it.
+    ///
+    /// # Arguments
+    /// * `items` - The number of threads, a multiple of the block size.
+    /// * `group_size` - The number of threads per block.
+    pub(super) fn run(&mut self, items: usize, group_size: usize) -> Result<()> {
+        let api = api()?;
+        self.enter(api)?;
+        let mut params: Vec<*mut c_void> = self
+            .buffers
+            .iter_mut()
+            .map(|buffer| (buffer as *mut DevicePointer).cast())
+            .collect();
+        // SAFETY: every argument points to a device pointer of the kernel
+        unsafe {
+            check(
+                "cuLaunchKernel",
+                (api.launch_kernel)(
+                    self.function,
+                    (items / group_size) as c_uint,
+                    1,
+                    1,
+                    group_size as c_uint,
+                    1,
+                    1,
+                    0,
+                    ptr::null_mut(),
+                    params.as_mut_ptr(),
+                    ptr::null_mut(),
+                ),
+            )?;
+            check("cuCtxSynchronize", (api.ctx_synchronize)())
+        }
+    }
+}
+
+impl Drop for Kernel {
+    fn drop(&mut self) {
+        let Ok(api) = api() else {
+            return;
+        };
+        if self.context.is_null() || self.enter(api).is_err() {
+            return;
+        }
+        // SAFETY: the buffers and module are owned by the kernel and released
+        // once, before the context they live in
+        unsafe {
+            for &buffer in &self.buffers {
+                (api.mem_free)(buffer);
+            }
+            if !self.module.is_null() {
+                (api.module_unload)(self.module);
+            }
+            (api.primary_ctx_release)(self.device);
+        }
+    }
+}
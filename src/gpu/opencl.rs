@@ -0,0 +1,556 @@
+use std::{
+    ffi::{CStr, CString, c_char, c_void},
+    ptr,
+    sync::OnceLock,
+};
+
+use super::Library;
+use crate::error::{Error, Result};
+
+/// Paths the OpenCL loader is looked up at, in order.
+#[cfg(target_os = "macos")]
+const LIBRARIES: [&str; 1] = ["/System/Library/Frameworks/OpenCL.framework/OpenCL"];
+/// Paths the OpenCL loader is looked up at, in order.
+#[cfg(not(target_os = "macos"))]
+const LIBRARIES: [&str; 2] = ["libOpenCL.so.1", "libOpenCL.so"];
+
+/// `CL_DEVICE_TYPE_GPU`.
+const DEVICE_TYPE_GPU: u64 = 1 << 2;
+/// `CL_DEVICE_GLOBAL_MEM_SIZE`.
+const DEVICE_GLOBAL_MEM_SIZE: u32 = 0x101F;
+/// `CL_DEVICE_NAME`.
+const DEVICE_NAME: u32 = 0x102B;
+/// `CL_DEVICE_VENDOR`.
+const DEVICE_VENDOR: u32 = 0x102C;
+/// `CL_KERNEL_WORK_GROUP_SIZE`.
+const KERNEL_WORK_GROUP_SIZE: u32 = 0x11B0;
+/// `CL_KERNEL_PREFERRED_WORK_GROUP_SIZE_MULTIPLE`.
+const KERNEL_PREFERRED_WORK_GROUP_SIZE_MULTIPLE: u32 = 0x11B3;
+/// `CL_PROGRAM_BUILD_LOG`.
+const PROGRAM_BUILD_LOG: u32 = 0x1183;
+/// `CL_MEM_READ_WRITE`.
+const MEM_READ_WRITE: u64 = 1 << 0;
+/// `CL_MEM_READ_ONLY`.
+const MEM_READ_ONLY: u64 = 1 << 2;
+
+/// Opaque OpenCL object handle.
+type Handle = *mut c_void;
+
+/// Entry points of the OpenCL loader, resolved when it is first used.
+struct Api {
+    get_platform_ids: unsafe extern "C" fn(u32, *mut Handle, *mut u32) -> i32,
+    get_device_ids: unsafe extern "C" fn(Handle, u64, u32, *mut Handle, *mut u32) -> i32,
+    get_device_info: unsafe extern "C" fn(Handle, u32, usize, *mut c_void, *mut usize) -> i32,
+    create_context: unsafe extern "C" fn(
+        *const isize,
+        u32,
+        *const Handle,
+        *const c_void,
+        *mut c_void,
+        *mut i32,
+    ) -> Handle,
+    create_command_queue: unsafe extern "C" fn(Handle, Handle, u64, *mut i32) -> Handle,
+    create_program_with_source:
+        unsafe extern "C" fn(Handle, u32, *const *const c_char, *const usize, *mut i32) -> Handle,
+    build_program: unsafe extern "C" fn(
+        Handle,
+        u32,
+        *const Handle,
+        *const c_char,
+        *const c_void,
+        *mut c_void,
+    ) -> i32,
+    get_program_build_info:
+        unsafe extern "C" fn(Handle, Handle, u32, usize, *mut c_void, *mut usize) -> i32,
+    create_kernel: unsafe extern "C" fn(Handle, *const c_char, *mut i32) -> Handle,
+    get_kernel_work_group_info:
+        unsafe extern "C" fn(Handle, Handle, u32, usize, *mut c_void, *mut usize) -> i32,
+    create_buffer: unsafe extern "C" fn(Handle, u64, usize, *mut c_void, *mut i32) -> Handle,
+    set_kernel_arg: unsafe extern "C" fn(Handle, u32, usize, *const c_void) -> i32,
+    enqueue_write_buffer: unsafe extern "C" fn(
+        Handle,
+        Handle,
+        u32,
+        usize,
+        usize,
+        *const c_void,
+        u32,
+        *const Handle,
+        *mut Handle,
+    ) -> i32,
+    enqueue_read_buffer: unsafe extern "C" fn(
+        Handle,
+        Handle,
+        u32,
+        usize,
+        usize,
+        *mut c_void,
+        u32,
+        *const Handle,
+        *mut Handle,
+    ) -> i32,
+    enqueue_nd_range_kernel: unsafe extern "C" fn(
+        Handle,
+        Handle,
+        u32,
+        *const usize,
+        *const usize,
+        *const usize,
+        u32,
+        *const Handle,
+        *mut Handle,
+    ) -> i32,
+    finish: unsafe extern "C" fn(Handle) -> i32,
+    release_mem_object: unsafe extern "C" fn(Handle) -> i32,
+    release_kernel: unsafe extern "C" fn(Handle) -> i32,
+    release_program: unsafe extern "C" fn(Handle) -> i32,
+    release_command_queue: unsafe extern "C" fn(Handle) -> i32,
+    release_context: unsafe extern "C" fn(Handle) -> i32,
+}
+
+/// The OpenCL loader, or why it could not be loaded.
+static API: OnceLock<Result<Api, String>> = OnceLock::new();
+
+/// Returns the OpenCL loader, loading it on first use.
+///
+/// # Errors
+///
+/// Returns an error if no OpenCL loader is installed or it lacks an entry
+/// point.
+fn api() -> Result<&'static Api> {
+    API.get_or_init(load)
+        .as_ref()
+        .map_err(|reason| Error::Gpu(reason.clone()))
+}
+
+/// Loads the OpenCL loader library and resolves its entry points.
+fn load() -> Result<Api, String> {
+    let library = Library::open(&LIBRARIES).ok_or("no OpenCL driver is installed")?;
+    macro_rules! symbol {
+        ($name:literal) => {
+            // SAFETY: the entry points have the signatures of the OpenCL 1.2
+            // specification
+            unsafe { library.function($name) }.ok_or(concat!("the OpenCL driver lacks ", $name))?
+        };
+    }
+    Ok(Api {
+        get_platform_ids: symbol!("clGetPlatformIDs"),
+        get_device_ids: symbol!("clGetDeviceIDs"),
+        get_device_info: symbol!("clGetDeviceInfo"),
+        create_context: symbol!("clCreateContext"),
+        create_command_queue: symbol!("clCreateCommandQueue"),
+        create_program_with_source: symbol!("clCreateProgramWithSource"),
+        build_program: symbol!("clBuildProgram"),
+        get_program_build_info: symbol!("clGetProgramBuildInfo"),
+        create_kernel: symbol!("clCreateKernel"),
+        get_kernel_work_group_info: symbol!("clGetKernelWorkGroupInfo"),
+        create_buffer: symbol!("clCreateBuffer"),
+        set_kernel_arg: symbol!("clSetKernelArg"),
+        enqueue_write_buffer: symbol!("clEnqueueWriteBuffer"),
+        enqueue_read_buffer: symbol!("clEnqueueReadBuffer"),
+        enqueue_nd_range_kernel: symbol!("clEnqueueNDRangeKernel"),
+        finish: symbol!("clFinish"),
+        release_mem_object: symbol!("clReleaseMemObject"),
+        release_kernel: symbol!("clReleaseKernel"),
+        release_program: symbol!("clReleaseProgram"),
+        release_command_queue: symbol!("clReleaseCommandQueue"),
+        release_context: symbol!("clReleaseContext"),
+    })
+}
+
+/// Turns an OpenCL status code into an error naming the failed call.
+fn check(call: &str, status: i32) -> Result<()> {
+    if status == 0 {
+        return Ok(());
+    }
+    Err(Error::Gpu(format!(
+        "{call} failed with OpenCL error {status}"
+    )))
+}
+
+/// An OpenCL GPU, identified by its handle.
+#[derive(Debug, Clone)]
+pub(super) struct Device {
+    /// The device handle, valid for the whole process.
+    handle: Handle,
+}
+
+// SAFETY: OpenCL device handles may be used from any thread
+unsafe impl Send for Device {}
+// SAFETY: OpenCL device handles may be used from any thread
+unsafe impl Sync for Device {}
+
+/// Returns the GPUs of every OpenCL platform.
+///
+/// # Errors
+///
+/// Returns an error if no OpenCL driver is installed or the platforms cannot
+/// be listed.
+pub(super) fn devices() -> Result<Vec<Device>> {
+    let api = api()?;
+
+    // SAFETY: the counts and arrays match the sizes passed to OpenCL
+    unsafe {
+        let mut count = 0;
+        check(
+            "clGetPlatformIDs",
+            (api.get_platform_ids)(0, ptr::null_mut(), &mut count),
+        )?;
+        let mut platforms = vec![ptr::null_mut(); count as usize];
+        check(
+            "clGetPlatformIDs",
+            (api.get_platform_ids)(count, platforms.as_mut_ptr(), ptr::null_mut()),
+        )?;
+
+        let mut devices = Vec::new();
+        for platform in platforms {
+            // Platforms without GPUs report an error instead of no device
+            let mut count = 0;
+            if (api.get_device_ids)(platform, DEVICE_TYPE_GPU, 0, ptr::null_mut(), &mut count) != 0
+            {
+                continue;
+            }
+            let mut handles = vec![ptr::null_mut(); count as usize];
+            check(
+                "clGetDeviceIDs",
+                (api.get_device_ids)(
+                    platform,
+                    DEVICE_TYPE_GPU,
+                    count,
+                    handles.as_mut_ptr(),
+                    ptr::null_mut(),
+                ),
+            )?;
+            devices.extend(handles.into_iter().map(|handle| Device { handle }));
+        }
+        Ok(devices)
+    }
+}
+
+impl Device {
+    /// Returns the name of the device.
+    pub(super) fn name(&self) -> Result<String> {
+        let bytes = self.info_bytes(DEVICE_NAME)?;
+        Ok(CStr::from_bytes_until_nul(&bytes)
+            .map_or_else(
+                |_| String::from_utf8_lossy(&bytes).into_owned(),
+                |name| name.to_string_lossy().into_owned(),
+            )
+            .trim()
+            .to_string())
+    }
+
+    /// Returns whether the device is an NVIDIA GPU.
+    pub(super) fn is_nvidia(&self) -> bool {
+        self.info_bytes(DEVICE_VENDOR)
+            .is_ok_and(|vendor| String::from_utf8_lossy(&vendor).contains("NVIDIA"))
+    }
+
+    /// Returns the size of the global memory of the device, in bytes.
+    pub(super) fn memory(&self) -> Result<u64> {
+        let bytes = self.info_bytes(DEVICE_GLOBAL_MEM_SIZE)?;
+        let bytes = bytes
+            .first_chunk()
+            .ok_or_else(|| Error::Gpu("clGetDeviceInfo returned no memory size".to_string()))?;
+        Ok(u64::from_ne_bytes(*bytes))
+    }
+
+    /// Reads a variable-size device property.
+    fn info_bytes(&self, param: u32) -> Result<Vec<u8>> {
+        let api = api()?;
+        let mut size = 0;
+        // SAFETY: the buffer is as large as the size reported by OpenCL
+        unsafe {
+            check(
+                "clGetDeviceInfo",
+                (api.get_device_info)(self.handle, param, 0, ptr::null_mut(), &mut size),
+            )?;
+            let mut bytes = vec![0u8; size];
+            check(
+                "clGetDeviceInfo",
+                (api.get_device_info)(
+                    self.handle,
+                    param,
+                    size,
+                    bytes.as_mut_ptr().cast(),
+                    ptr::null_mut(),
+                ),
+            )?;
+            Ok(bytes)
+        }
+    }
+
+    /// Builds a kernel from OpenCL C source for this device.
+    ///
+    /// # Arguments
+    /// * `source` - The OpenCL C source of the program.
+    /// * `options` - The build options, e.g. `-D` definitions.
+    /// * `name` - The name of the kernel function.
+    /// * `args` - The sizes of the buffers passed to the kernel, in order.
+    ///   Every buffer is read-only, except the last one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program does not build, with the build log.
+    pub(super) fn kernel(
+        &self,
+        source: &str,
+        options: &str,
+        name: &str,
+        args: &[usize],
+    ) -> Result<Kernel> {
+        let api = api()?;
+        let mut kernel = Kernel {
+            device: self.handle,
+            context: ptr::null_mut(),
+            queue: ptr::null_mut(),
+            program: ptr::null_mut(),
+            kernel: ptr::null_mut(),
+            buffers: Vec::new(),
+        };
+        let mut status = 0;
+
+        // SAFETY: every handle is checked before use, and the partially built
+        // kernel releases the ones created so far if a step fails
+        unsafe {
+            kernel.context = (api.create_context)(
+                ptr::null(),
+                1,
+                &self.handle,
+                ptr::null(),
+                ptr::null_mut(),
+                &mut status,
+            );
+            check("clCreateContext", status)?;
+            kernel.queue = (api.create_command_queue)(kernel.context, self.handle, 0, &mut status);
+            check("clCreateCommandQueue", status)?;
+
+            let (source, length) = (source.as_ptr().cast::<c_char>(), source.len());
+            kernel.program =
+                (api.create_program_with_source)(kernel.context, 1, &source, &length, &mut status);
+            check("clCreateProgramWithSource", status)?;
+            let options = CString::new(options).expect("build options have no NUL byte");
+            let built = (api.build_program)(
+                kernel.program,
+                1,
+                &self.handle,
+                options.as_ptr(),
+                ptr::null(),
+                ptr::null_mut(),
+            );
+            if built != 0 {
+                let log = self.build_log(kernel.program).unwrap_or_default();
+                return Err(Error::Gpu(format!(
+                    "the kernel failed to build with OpenCL error {built}:\n{log}"
+                )));
+            }
+
+            let name = CString::new(name).expect("kernel names have no NUL byte");
+            kernel.kernel = (api.create_kernel)(kernel.program, name.as_ptr(), &mut status);
+            check("clCreateKernel", status)?;
+
+            // Bind one buffer to each argument
+            for (index, &size) in args.iter().enumerate() {
+                let flags = if index + 1 == args.len() {
+                    MEM_READ_WRITE
+                } else {
+                    MEM_READ_ONLY
+                };
+                let buffer =
+                    (api.create_buffer)(kernel.context, flags, size, ptr::null_mut(), &mut status);
+                check("clCreateBuffer", status)?;
+                kernel.buffers.push(buffer);
+                check(
+                    "clSetKernelArg",
+                    (api.set_kernel_arg)(
+                        kernel.kernel,
+                        index as u32,
+                        size_of::<Handle>(),
+                        (&raw const kernel.buffers[index]).cast(),
+                    ),
+                )?;
+            }
+        }
+        Ok(kernel)
+    }
+
+    /// Returns the build log of a program on this device.
+    fn build_log(&self, program: Handle) -> Result<String> {
+        let api = api()?;
+        let mut size = 0;
+        // SAFETY: the buffer is as large as the size reported by OpenCL
+        unsafe {
+            check(
+                "clGetProgramBuildInfo",
+                (api.get_program_build_info)(
+                    program,
+                    self.handle,
+                    PROGRAM_BUILD_LOG,
+                    0,
+                    ptr::null_mut(),
+                    &mut size,
+                ),
+            )?;
+            let mut log = vec![0u8; size];
+            check(
+                "clGetProgramBuildInfo",
+                (api.get_program_build_info)(
+                    program,
+                    self.handle,
+                    PROGRAM_BUILD_LOG,
+                    size,
+                    log.as_mut_ptr().cast(),
+                    ptr::null_mut(),
+                ),
+            )?;
+            Ok(String::from_utf8_lossy(&log)
+                .trim_end_matches('\0')
+                .to_string())
+        }
+    }
+}
+
+/// A built kernel with its own context, queue and argument buffers.
+#[derive(Debug)]
+pub(super) struct Kernel {
+    /// The device the kernel was built for.
+    device: Handle,
+    /// The context owning the other objects.
+    context: Handle,
+    /// The in-order queue the kernel runs on.
+    queue: Handle,
+    /// The program the kernel was built from.
+    program: Handle,
+    /// The kernel function.
+    kernel: Handle,
+    /// The buffer bound to each argument of the kernel.
+    buffers: Vec<Handle>,
+}
+
+// SAFETY: the kernel is only used by one thread at a time, which OpenCL
+// allows for every object it holds
+unsafe impl Send for Kernel {}
+
+impl Kernel {
+    /// Returns the largest work-group size the kernel can be launched with,
+    /// which its register usage may keep below the limit of the device, and
+    /// the SIMD width work groups should be a multiple of.
+    pub(super) fn group_sizes(&self) -> Result<(usize, usize)> {
+        Ok((
+            self.work_group_info(KERNEL_WORK_GROUP_SIZE)?,
+            self.work_group_info(KERNEL_PREFERRED_WORK_GROUP_SIZE_MULTIPLE)?,
+        ))
+    }
+
+    /// Reads a work-group property of the kernel on its device.
+    fn work_group_info(&self, param: u32) -> Result<usize> {
+        let api = api()?;
+        let mut value = 0usize;
+        // SAFETY: the value is as large as the size passed to OpenCL
+        check("clGetKernelWorkGroupInfo", unsafe {
+            (api.get_kernel_work_group_info)(
+                self.kernel,
+                self.device,
+                param,
+                size_of::<usize>(),
+                (&raw mut value).cast(),
+                ptr::null_mut(),
+            )
+        })?;
+        Ok(value)
+    }
+
+    /// Copies bytes to the buffer of an argument, blocking until done.
+    pub(super) fn write(&mut self, arg: usize, bytes: &[u8]) -> Result<()> {
+        let api = api()?;
+        // SAFETY: the buffer was created with the size of the argument, which
+        // the bytes fit in
+        check("clEnqueueWriteBuffer", unsafe {
+            (api.enqueue_write_buffer)(
+                self.queue,
+                self.buffers[arg],
+                1,
+                0,
+                bytes.len(),
+                bytes.as_ptr().cast(),
+                0,
+                ptr::null(),
+                ptr::null_mut(),
+            )
+        })
+    }
+
+    /// Copies the buffer of an argument to bytes, blocking until done.
+    pub(super) fn read(&mut self, arg: usize, bytes: &mut [u8]) -> Result<()> {
+        let api = api()?;
+        // SAFETY: the buffer was created with the size of the argument, which
+        // the bytes fit in
+        check("clEnqueueReadBuffer", unsafe {
+            (api.enqueue_read_buffer)(
+                self.queue,
+                self.buffers[arg],
+                1,
+                0,
+                bytes.len(),
+                bytes.as_mut_ptr().cast(),
+                0,
+                ptr::null(),
+                ptr::null_mut(),
+            )
+        })
+    }
+
+    /// Runs the kernel over a range of work items and waits for it.
+    ///
+    /// # Arguments
+    /// * `items` - The number of work items, a multiple of the group size.
+    /// * `group_size` - The number of work items per work group.
+    pub(super) fn run(&mut self, items: usize, group_size: usize) -> Result<()> {
+        let api = api()?;
+        // SAFETY: the kernel arguments are all bound
+        unsafe {
+            check(
+                "clEnqueueNDRangeKernel",
+                (api.enqueue_nd_range_kernel)(
+                    self.queue,
+                    self.kernel,
+                    1,
+                    ptr::null(),
+                    &items,
+                    &group_size,
+                    0,
+                    ptr::null(),
+                    ptr::null_mut(),
+                ),
+            )?;
+            check("clFinish", (api.finish)(self.queue))
+        }
+    }
+}
+
+impl Drop for Kernel {
+    fn drop(&mut self) {
+        let Ok(api) = api() else {
+            return;
+        };
+        // SAFETY: the handles are owned by the kernel and released once, in
+        // the reverse order of their creation
+        unsafe {
+            for &buffer in &self.buffers {
+                (api.release_mem_object)(buffer);
+            }
+            if !self.kernel.is_null() {
+                (api.release_kernel)(self.kernel);
+            }
+            if !self.program.is_null() {
+                (api.release_program)(self.program);
+            }
+            if !self.queue.is_null() {
+                (api.release_command_queue)(self.queue);
+            }
+            if !self.context.is_null() {
+                (api.release_context)(self.context);
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use std::{fs, path::PathBuf, time::Duration, vec};
+
+use serde_json::{Map, Value, json};
+
+use crate::{
+    error::{Error, Result},
+    sessions::piwi_home,
+};
+
+/// Batch sizes tried while calibrating, in nonces per dispatch.
+const CALIBRATION_BATCHES: [u64; 3] = [1 << 20, 1 << 22, 1 << 24];
+
+/// Largest number of work items per work group tried while calibrating.
+const MAX_GROUP_SIZE: usize = 1024;
+
+/// Name of the file holding the tuned launch sizes of each GPU model.
+const PROFILES_FILE: &str = "gpu-profiles.json";
+
+/// Launch parameters of a GPU kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct LaunchSizes {
+    /// Number of work items per work group.
+    pub(super) group: usize,
+    /// Number of nonces searched by each dispatch, which bounds how late a
+    /// cancellation or a match of another worker is noticed.
+    pub(super) batch: u64,
+}
+
+/// Picks the launch sizes maximizing the hashrate of a kernel on a GPU model.
+///
+/// Like the CPU tuner, the first full dispatches of a search each run with
+/// one of the candidate sizes, so calibrating costs no work. The fastest
+/// sizes are then kept, to be saved in the profile of the GPU model so that
+/// later searches on the same model start with them.
+#[derive(Debug)]
+pub(super) struct Tuner {
+    /// Whether the GPU ran a full dispatch yet, which is not timed as it
+    /// pays for the warm-up of the driver.
+    warm: bool,
+    /// Sizes still to be calibrated.
+    candidates: vec::IntoIter<LaunchSizes>,
+    /// Fastest sizes so far, with their hashrate.
+    best: Option<(LaunchSizes, f64)>,
+    /// Sizes picked once calibrated or loaded from the profiles.
+    tuned: Option<LaunchSizes>,
+}
+
+impl Tuner {
+    /// Creates a tuner for a kernel.
+    ///
+    /// # Arguments
+    /// * `profile` - The saved sizes of the GPU model, if it was tuned
+    ///   before, which skips calibrating.
+    /// * `max_group` - The largest work-group size the kernel supports.
+    /// * `simd_width` - The number of work items the GPU runs in lockstep.
+    pub(super) fn new(profile: Option<LaunchSizes>, max_group: usize, simd_width: usize) -> Self {
+        let candidates: Vec<_> = CALIBRATION_BATCHES
+            .into_iter()
+            .flat_map(|batch| {
+                group_sizes(max_group, simd_width)
+                    .into_iter()
+                    .map(move |group| LaunchSizes { group, batch })
+            })
+            .collect();
+        Self {
+            tuned: profile,
+            warm: false,
+            candidates: candidates.into_iter(),
+            best: None,
+        }
+    }
+
+    /// Returns the sizes to launch the next dispatch with.
+    pub(super) fn sizes(&self) -> LaunchSizes {
+        self.tuned.unwrap_or_else(|| {
+            *self
+                .candidates
+                .as_slice()
+                .first()
+                .expect("candidates remain until the sizes are tuned")
+        })
+    }
+
+    /// Records how long a dispatch launched with the given sizes took,
+    /// returning the fastest sizes once every candidate was tried.
+    ///
+    /// Dispatches cut short at the end of a chunk are not timed, as they pay
+    /// the same launch overhead for fewer nonces.
+    ///
+    /// # Arguments
+    /// * `sizes` - The sizes the dispatch was launched with.
+    /// * `nonces` - The number of nonces searched by the dispatch.
+    /// * `elapsed` - The time the dispatch took, transfers included.
+    pub(super) fn record(
+        &mut self,
+        sizes: LaunchSizes,
+        nonces: u64,
+        elapsed: Duration,
+    ) -> Option<LaunchSizes> {
+        if self.tuned.is_some() || nonces < sizes.batch {
+            return None;
+        }
+        if !self.warm {
+            self.warm = true;
+            return None;
+        }
+        self.candidates.next()?;
+
+        let hashrate = nonces as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        if self.best.is_none_or(|(_, best)| hashrate > best) {
+            self.best = Some((sizes, hashrate));
+        }
+
+        // Keep the fastest sizes once every one was tried
+        if self.candidates.len() == 0 {
+            self.tuned = self.best.map(|(sizes, _)| sizes);
+            return self.tuned;
+        }
+        None
+    }
+}
+
+/// Returns the work-group sizes worth trying for a kernel: the powers of two
+/// multiples of the SIMD width within the limit of the kernel and
+/// [`MAX_GROUP_SIZE`], or the limit itself if it is below the SIMD width.
+///
+/// Apple GPUs, for instance, often cap the Keccak kernel below their device
+/// limit because of its register usage, and run 32-wide SIMD groups.
+fn group_sizes(max: usize, simd_width: usize) -> Vec<usize> {
+    let max = max.clamp(1, MAX_GROUP_SIZE);
+    let simd_width = simd_width.max(1);
+    if max < simd_width {
+        return vec![max];
+    }
+    std::iter::successors(Some(simd_width), |size| Some(size * 2))
+        .take_while(|&size| size <= max)
+        .collect()
+}
+
+/// Returns the path of the tuning profiles, under [`piwi_home`].
+///
+/// # Errors
+///
+/// Returns an error if neither `PIWI_HOME` nor `HOME` is set.
+fn profiles_path() -> Result<PathBuf> {
+    Ok(piwi_home()?.join(PROFILES_FILE))
+}
+
+/// Reads the tuning profiles, keyed by GPU model, skipping a missing or
+/// malformed file.
+fn read_profiles() -> Map<String, Value> {
+    profiles_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Returns the saved launch sizes of a GPU model, if it was tuned before.
+pub(super) fn load_profile(model: &str) -> Option<LaunchSizes> {
+    let profile = read_profiles().remove(model)?;
+    Some(LaunchSizes {
+        group: usize::try_from(profile["group"].as_u64()?).ok()?,
+        batch: profile["batch"].as_u64()?,
+    })
+}
+
+/// Saves the tuned launch sizes of a GPU model with the other profiles.
+///
+/// # Errors
+///
+/// Returns an error if the profiles cannot be written.
+pub(super) fn save_profile(model: &str, sizes: LaunchSizes) -> Result<()> {
+    let path = profiles_path()?;
+    let mut profiles = read_profiles();
+    profiles.insert(
+        model.to_string(),
+        json!({ "group": sizes.group, "batch": sizes.batch }),
+    );
+
+    let write_error = |source| Error::Write {
+        path: path.display().to_string(),
+        source,
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(write_error)?;
+    }
+    let contents = serde_json::to_string_pretty(&profiles).expect("profiles serialize");
+    fs::write(&path, contents + "\n").map_err(write_error)
+}
+
+#[test]
+fn test_tuner() {
+    let mut tuner = Tuner::new(None, 100, 32);
+    assert_eq!(tuner.candidates.len(), 6);
+
+    // The warm-up and the cut short dispatches are not timed
+    let first = tuner.sizes();
+    assert_eq!(
+        first,
+        LaunchSizes {
+            group: 32,
+            batch: 1 << 20
+        }
+    );
+    assert_eq!(
+        tuner.record(first, first.batch, Duration::from_secs(100)),
+        None
+    );
+    assert_eq!(
+        tuner.record(first, first.batch / 2, Duration::from_secs(100)),
+        None
+    );
+    assert_eq!(tuner.sizes(), first);
+
+    // The third candidate, with 2^22 nonces per dispatch, is the fastest
+    let mut tuned = None;
+    for rate in [1.0, 2.0, 4.0, 3.0, 1.0, 2.0] {
+        let sizes = tuner.sizes();
+        tuned = tuner.record(
+            sizes,
+            sizes.batch,
+            Duration::from_secs_f64(sizes.batch as f64 / rate),
+        );
+    }
+    let fastest = LaunchSizes {
+        group: 32,
+        batch: 1 << 22,
+    };
+    assert_eq!(tuned, Some(fastest));
+    assert_eq!(tuner.sizes(), fastest);
+}
@@ -0,0 +1,408 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, mpsc},
+};
+
+use wgpu::{
+    Adapter, Backend, Backends, BindGroup, BindGroupDescriptor, BindGroupEntry,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType,
+    BufferDescriptor, BufferUsages, CommandEncoderDescriptor, ComputePassDescriptor,
+    ComputePipeline, ComputePipelineDescriptor, DeviceDescriptor, DeviceType, Instance,
+    InstanceDescriptor, MapMode, PipelineCompilationOptions, PipelineLayout,
+    PipelineLayoutDescriptor, PollType, Queue, ShaderModule, ShaderModuleDescriptor, ShaderSource,
+    ShaderStages,
+};
+
+use crate::error::{Error, Result};
+
+/// Backends whose GPUs are used, in order of preference, as a GPU is usually
+/// reported by several of them.
+const BACKENDS: [Backend; 4] = [Backend::Metal, Backend::Vulkan, Backend::Dx12, Backend::Gl];
+
+/// A GPU reported by wgpu, on the first backend that reports GPUs.
+#[derive(Debug, Clone)]
+pub(super) struct Device {
+    /// The adapter of the GPU, which devices are requested from.
+    adapter: Adapter,
+}
+
+/// Returns the GPUs reported by the preferred wgpu backend.
+///
+/// # Errors
+///
+/// Returns an error if no backend reports a GPU.
+pub(super) fn devices() -> Result<Vec<Device>> {
+    let devices = adapters(false);
+    if devices.is_empty() {
+        return Err(Error::Gpu("wgpu found no GPU".to_string()));
+    }
+    Ok(devices)
+}
+
+/// Returns the adapters of the first backend of [`BACKENDS`] reporting any,
+/// which the `WGPU_BACKEND` environment variable may restrict.
+///
+/// # Arguments
+/// * `software` - Whether to also keep software rasterizers, e.g. llvmpipe.
+pub(super) fn adapters(software: bool) -> Vec<Device> {
+    let instance = Instance::new(InstanceDescriptor::new_without_display_handle_from_env());
+    let adapters = pollster::block_on(instance.enumerate_adapters(Backends::all()));
+    let adapters: Vec<_> = adapters
+        .into_iter()
+        .filter(|adapter| software || adapter.get_info().device_type != DeviceType::Cpu)
+        .collect();
+    BACKENDS
+        .into_iter()
+        .map(|backend| {
+            adapters
+                .iter()
+                .filter(|adapter| adapter.get_info().backend == backend)
+                .map(|adapter| Device {
+                    adapter: adapter.clone(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .find(|devices| !devices.is_empty())
+        .unwrap_or_default()
+}
+
+impl Device {
+    /// Returns the name of the device.
+    pub(super) fn name(&self) -> Result<String> {
+        Ok(self.adapter.get_info().name)
+    }
+
+    /// Returns the size of the memory of the device, which wgpu does not
+    /// report.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error.
+    pub(super) fn memory(&self) -> Result<u64> {
+        Err(Error::Gpu(
+            "wgpu does not report the memory size".to_string(),
+        ))
+    }
+
+    /// Returns the name of the graphics API the device is used through.
+    pub(super) fn backend(&self) -> &'static str {
+        match self.adapter.get_info().backend {
+            Backend::Metal => "Metal",
+            Backend::Vulkan => "Vulkan",
+            Backend::Dx12 => "DirectX 12",
+            Backend::Gl => "OpenGL",
+            _ => "WebGPU",
+        }
+    }
+
+    /// Builds a kernel from WGSL source for this device.
+    ///
+    /// # Arguments
+    /// * `source` - The WGSL source of the program.
+    /// * `constants` - The values of its pipeline-overridable constants.
+    /// * `name` - The name of the entry point.
+    /// * `args` - The sizes of the storage buffers bound to the kernel, in
+    ///   order. Every buffer is read-only, except the last one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no device can be requested from the adapter, or
+    /// if the program does not build, with the compiler message.
+    pub(super) fn kernel(
+        &self,
+        source: &str,
+        constants: &[(&str, usize)],
+        name: &str,
+        args: &[usize],
+    ) -> Result<Kernel> {
+        // Take the limits of the adapter, as the defaults cap workgroups at
+        // 256 invocations
+        let limits = self.adapter.limits();
+        let (device, queue) = pollster::block_on(self.adapter.request_device(&DeviceDescriptor {
+            required_limits: limits.clone(),
+            ..Default::default()
+        }))
+        .map_err(|err| Error::Gpu(format!("wgpu could not open the device: {err}")))?;
+
+        // Report validation and device errors through the next call, as wgpu
+        // panics on them by default
+        let errors = Errors::default();
+        device.on_uncaptured_error(Arc::new({
+            let errors = errors.clone();
+            move |err| errors.set(err.to_string())
+        }));
+        device.set_device_lost_callback({
+            let errors = errors.clone();
+            move |_, message| errors.set(format!("the device was lost: {message}"))
+        });
+
+        let module = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some(name),
+            source: ShaderSource::Wgsl(source.into()),
+        });
+        let entries: Vec<_> = (0..args.len())
+            .map(|index| BindGroupLayoutEntry {
+                binding: index as u32,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage {
+                        read_only: index + 1 != args.len(),
+                    },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            })
+            .collect();
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some(name),
+            entries: &entries,
+        });
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some(name),
+            bind_group_layouts: &[Some(&bind_group_layout)],
+            immediate_size: 0,
+        });
+
+        // Bind one buffer to each argument, and read them back through a
+        // mappable one
+        let buffers: Vec<_> = args
+            .iter()
+            .map(|&size| {
+                device.create_buffer(&BufferDescriptor {
+                    label: Some(name),
+                    size: size.next_multiple_of(4) as u64,
+                    usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                })
+            })
+            .collect();
+        let readback = device.create_buffer(&BufferDescriptor {
+            label: Some(name),
+            size: args.iter().max().copied().unwrap_or(4).next_multiple_of(4) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let entries: Vec<_> = buffers
+            .iter()
+            .enumerate()
+            .map(|(index, buffer)| BindGroupEntry {
+                binding: index as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some(name),
+            layout: &bind_group_layout,
+            entries: &entries,
+        });
+        errors.check("the kernel failed to build with wgpu")?;
+
+        let mut kernel = Kernel {
+            info: self.adapter.get_info(),
+            limits,
+            device,
+            queue,
+            module,
+            layout,
+            name: name.to_string(),
+            constants: constants
+                .iter()
+                .map(|&(constant, value)| (constant.to_string(), value as f64))
+                .collect(),
+            pipelines: HashMap::new(),
+            buffers,
+            readback,
+            bind_group,
+            errors,
+        };
+
+        // Build the program now, so that compiler errors are reported before
+        // mining starts
+        let (max_group_size, _) = kernel.group_sizes()?;
+        kernel.pipeline(max_group_size.min(64))?;
+        Ok(kernel)
+    }
+}
+
+/// Errors wgpu reported since they were last checked.
+#[derive(Debug, Clone, Default)]
+struct Errors(Arc<Mutex<Option<String>>>);
+
+impl Errors {
+    /// Keeps the first error reported.
+    fn set(&self, message: String) {
+        self.0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get_or_insert(message);
+    }
+
+    /// Returns the error reported since the last check, if any.
+    fn check(&self, context: &str) -> Result<()> {
+        match self.0.lock().unwrap_or_else(|err| err.into_inner()).take() {
+            Some(message) => Err(Error::Gpu(format!("{context}:\n{message}"))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A built kernel with its own device, queue and argument buffers.
+pub(super) struct Kernel {
+    /// The adapter the device was requested from.
+    info: wgpu::AdapterInfo,
+    /// The limits the device was requested with.
+    limits: wgpu::Limits,
+    /// The logical device owning the other objects.
+    device: wgpu::Device,
+    /// The queue the kernel runs on.
+    queue: Queue,
+    /// The shader module the pipelines are built from.
+    module: ShaderModule,
+    /// The layout of the argument buffers shared by the pipelines.
+    layout: PipelineLayout,
+    /// The name of the entry point.
+    name: String,
+    /// The values of the pipeline-overridable constants.
+    constants: Vec<(String, f64)>,
+    /// The pipeline built for each workgroup size, which WGSL fixes when the
+    /// pipeline is built.
+    pipelines: HashMap<usize, ComputePipeline>,
+    /// The buffer bound to each argument of the kernel.
+    buffers: Vec<Buffer>,
+    /// The buffer arguments are copied to before being read.
+    readback: Buffer,
+    /// The bindings of the buffers to the arguments.
+    bind_group: BindGroup,
+    /// The errors reported by the device.
+    errors: Errors,
+}
+
+impl fmt::Debug for Kernel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Kernel")
+            .field("device", &self.info.name)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Kernel {
+    /// Returns the largest workgroup size of the device, and the largest
+    /// subgroup size, which workgroups should be a multiple of.
+    ///
+    /// On Metal, wgpu compiles every pipeline with the device limit as its
+    /// `maxTotalThreadsPerThreadgroup`, so threadgroups of that size always
+    /// run, however many registers the kernel takes. The subgroup size it
+    /// reports there is 64, a multiple of the `threadExecutionWidth` of 32 of
+    /// Apple GPUs.
+    pub(super) fn group_sizes(&self) -> Result<(usize, usize)> {
+        let max = self
+            .limits
+            .max_compute_invocations_per_workgroup
+            .min(self.limits.max_compute_workgroup_size_x);
+        Ok((max as usize, self.info.subgroup_max_size.max(1) as usize))
+    }
+
+    /// Returns the pipeline running workgroups of the given size, building
+    /// it the first time.
+    fn pipeline(&mut self, group_size: usize) -> Result<&ComputePipeline> {
+        if !self.pipelines.contains_key(&group_size) {
+            let constants: Vec<(&str, f64)> = self
+                .constants
+                .iter()
+                .map(|(name, value)| (name.as_str(), *value))
+                .chain([("GROUP_SIZE", group_size as f64)])
+                .collect();
+            let pipeline = self
+                .device
+                .create_compute_pipeline(&ComputePipelineDescriptor {
+                    label: Some(&self.name),
+                    layout: Some(&self.layout),
+                    module: &self.module,
+                    entry_point: Some(&self.name),
+                    compilation_options: PipelineCompilationOptions {
+                        constants: &constants,
+                        ..Default::default()
+                    },
+                    cache: None,
+                });
+            self.errors.check("the kernel failed to build with wgpu")?;
+            self.pipelines.insert(group_size, pipeline);
+        }
+        Ok(&self.pipelines[&group_size])
+    }
+
+    /// Copies bytes to the buffer of an argument, before the next run.
+    pub(super) fn write(&mut self, arg: usize, bytes: &[u8]) -> Result<()> {
+        self.queue.write_buffer(&self.buffers[arg], 0, bytes);
+        self.errors.check("wgpu failed to write a buffer")
+    }
+
+    /// Copies the buffer of an argument to bytes, blocking until done.
+    pub(super) fn read(&mut self, arg: usize, bytes: &mut [u8]) -> Result<()> {
+        let size = bytes.len().next_multiple_of(4) as u64;
+        let mut encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&self.buffers[arg], 0, &self.readback, 0, size);
+        self.queue.submit([encoder.finish()]);
+
+        // Map the copy once the queue is done with it
+        let (sender, receiver) = mpsc::channel();
+        self.readback
+            .map_async(MapMode::Read, ..size, move |mapped| {
+                let _ = sender.send(mapped);
+            });
+        self.wait()?;
+        receiver
+            .recv()
+            .map_err(|_| Error::Gpu("wgpu did not map the buffer".to_string()))?
+            .map_err(|err| Error::Gpu(format!("wgpu failed to map the buffer: {err}")))?;
+        {
+            let mapped = self
+                .readback
+                .get_mapped_range(..size)
+                .map_err(|err| Error::Gpu(format!("wgpu failed to map the buffer: {err}")))?;
+            bytes.copy_from_slice(&mapped[..bytes.len()]);
+        }
+        self.readback.unmap();
+        Ok(())
+    }
+
+    /// Runs the kernel over a number of invocations and waits for it.
+    ///
+    /// # Arguments
+    /// * `items` - The number of invocations, a multiple of the group size.
+    /// * `group_size` - The number of invocations per workgroup.
+    pub(super) fn run(&mut self, items: usize, group_size: usize) -> Result<()> {
+        // Spread the workgroups over a second dimension past the limit of
+        // the first one
+        let groups = (items / group_size) as u32;
+        let width = groups.min(self.limits.max_compute_workgroups_per_dimension);
+        let height = groups.div_ceil(width.max(1));
+
+        self.pipeline(group_size)?;
+        let pipeline = &self.pipelines[&group_size];
+        let mut encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor::default());
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.dispatch_workgroups(width, height, 1);
+        }
+        self.queue.submit([encoder.finish()]);
+        self.wait()
+    }
+
+    /// Waits for the work submitted to the queue, reporting its errors.
+    fn wait(&self) -> Result<()> {
+        self.device
+            .poll(PollType::wait_indefinitely())
+            .map_err(|err| Error::Gpu(format!("wgpu failed to wait for the GPU: {err}")))?;
+        self.errors.check("the kernel failed to run with wgpu")
+    }
+}
@@ -0,0 +1,667 @@
+use std::{fmt, ops::Range, sync::OnceLock};
+
+use alloy_primitives::{Address, B256, keccak256};
+
+use crate::{
+    cli::Hasher,
+    error::{Error, Result},
+};
+
+/// Expands to the 24 rounds of Keccak-f[1600] over a state of 25 lanes, using
+/// the lane operations of a SIMD backend.
+///
+/// # Arguments
+/// * `state` - The state, as an array of 25 lane vectors.
+/// * `xor` - Returns `a ^ b`.
+/// * `andnot` - Returns `!a & b`.
+/// * `rotl` - Rotates each lane left by a number of bits.
+/// * `splat` - Broadcasts a constant to every lane.
+#[cfg(feature = "simd")]
+macro_rules! keccak_f1600 {
+    ($state:ident, $xor:ident, $andnot:ident, $rotl:ident, $splat:ident) => {
+        for round_constant in $crate::hash::ROUND_CONSTANTS {
+            // Theta: mix each column with its two neighbours
+            let mut columns = [$state[0]; 5];
+            for (x, column) in columns.iter_mut().enumerate() {
+                *column = $xor(
+                    $xor(
+                        $xor($state[x], $state[x + 5]),
+                        $xor($state[x + 10], $state[x + 15]),
+                    ),
+                    $state[x + 20],
+                );
+            }
+            for x in 0..5 {
+                let mix = $xor(columns[(x + 4) % 5], $rotl(columns[(x + 1) % 5], 1));
+                for y in 0..5 {
+                    $state[5 * y + x] = $xor($state[5 * y + x], mix);
+                }
+            }
+
+            // Rho and pi: rotate the lanes and move them to their new place
+            let mut last = $state[1];
+            for (lane, rotation) in $crate::hash::PI.into_iter().zip($crate::hash::RHO) {
+                let next = $state[lane];
+                $state[lane] = $rotl(last, rotation);
+                last = next;
+            }
+
+            // Chi: combine each lane with the next two of its row
+            for y in 0..5 {
+                let row = [
+                    $state[5 * y],
+                    $state[5 * y + 1],
+                    $state[5 * y + 2],
+                    $state[5 * y + 3],
+                    $state[5 * y + 4],
+                ];
+                for x in 0..5 {
+                    $state[5 * y + x] = $xor(row[x], $andnot(row[(x + 1) % 5], row[(x + 2) % 5]));
+                }
+            }
+
+            // Iota: break the symmetry of the rounds
+            $state[0] = $xor($state[0], $splat(round_constant));
+        }
+    };
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod avx2;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod avx512;
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+mod neon;
+
+/// Number of messages hashed together by batched Keccak backends, the widest
+/// batch of any backend.
+pub(super) const LANES: usize = 8;
+
+/// Number of bytes absorbed by each Keccak256 permutation.
+const RATE: usize = 136;
+
+/// Largest number of lanes covering the varying bytes of a message.
+const MAX_VARYING_LANES: usize = 6;
+
+/// Round constants of Keccak-f[1600].
+#[cfg(feature = "simd")]
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotations of the lanes in the order they are visited by the pi step.
+#[cfg(feature = "simd")]
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+/// Lanes visited by the pi step, each moved to the place of the next one.
+#[cfg(feature = "simd")]
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// Keccak kernel selected for the whole process.
+static BACKEND: OnceLock<Backend> = OnceLock::new();
+
+/// Offset of the salt in the CREATE2 preimage.
+const SALT_OFFSET: usize = 1 + 20;
+
+/// Offset of the salt in the preimage of a zkSync Era CREATE2 address, after
+/// the hash of `zksyncCreate2` and the padded sender.
+const ZKSYNC_SALT_OFFSET: usize = 32 + 32;
+
+/// CREATE2 preimage `0xff ++ factory ++ salt ++ init_code_hash` of salts
+/// sharing all but a range of their bytes.
+///
+/// The fixed bytes are absorbed once, so deriving addresses only loads the
+/// varying salt bytes and runs the permutation.
+#[derive(Debug, Clone)]
+pub(super) struct Create2Preimage {
+    /// Sponge with the fixed bytes of the preimage absorbed.
+    midstate: Midstate,
+    /// Range of the salt bytes that differ between salts.
+    varying: Range<usize>,
+}
+
+impl Create2Preimage {
+    /// Absorbs the fixed bytes of the preimage.
+    ///
+    /// # Arguments
+    /// * `factory` - The address of the CREATE2 factory contract.
+    /// * `salt` - A salt, of which only the bytes outside of `varying` are
+    ///   absorbed.
+    /// * `init_code_hash` - The keccak256 hash of the contract initialization
+    ///   code.
+    /// * `varying` - The range of the salt bytes that differ between salts.
+    pub(super) fn new(
+        factory: Address,
+        salt: &[u8; 32],
+        init_code_hash: B256,
+        varying: Range<usize>,
+    ) -> Self {
+        let preimage = [&[0xff], factory.as_slice(), salt, init_code_hash.as_slice()].concat();
+        let shifted = varying.start + SALT_OFFSET..varying.end + SALT_OFFSET;
+        Self {
+            midstate: Midstate::new(&preimage, shifted),
+            varying,
+        }
+    }
+
+    /// Absorbs the fixed bytes of the preimage of zkSync Era CREATE2
+    /// addresses, `keccak256("zksyncCreate2") ++ sender ++ salt ++
+    /// bytecodeHash ++ constructorInputHash` with the sender padded to 32
+    /// bytes.
+    ///
+    /// # Arguments
+    /// * `factory` - The address of the contract calling CREATE2.
+    /// * `salt` - A salt, of which only the bytes outside of `varying` are
+    ///   absorbed.
+    /// * `bytecode_hash` - The zkSync Era hash of the contract bytecode.
+    /// * `constructor_input_hash` - The keccak256 hash of the constructor
+    ///   arguments.
+    /// * `varying` - The range of the salt bytes that differ between salts.
+    pub(super) fn zksync(
+        factory: Address,
+        salt: &[u8; 32],
+        bytecode_hash: B256,
+        constructor_input_hash: B256,
+        varying: Range<usize>,
+    ) -> Self {
+        let preimage = zksync_preimage(factory, salt, bytecode_hash, constructor_input_hash);
+        let shifted = varying.start + ZKSYNC_SALT_OFFSET..varying.end + ZKSYNC_SALT_OFFSET;
+        Self {
+            midstate: Midstate::new(&preimage, shifted),
+            varying,
+        }
+    }
+
+    /// Returns the addresses the factory deploys to with a batch of salts.
+    #[inline]
+    pub(super) fn addresses(&self, salts: &[[u8; 32]; LANES]) -> [Address; LANES] {
+        let varying = salts.each_ref().map(|salt| &salt[self.varying.clone()]);
+        self.midstate.hash_batch(varying).map(Address::from_word)
+    }
+}
+
+/// Computes the address zkSync Era deploys a contract to with CREATE2.
+///
+/// # Arguments
+/// * `factory` - The address of the contract calling CREATE2.
+/// * `salt` - The CREATE2 salt.
+/// * `bytecode_hash` - The zkSync Era hash of the contract bytecode.
+/// * `constructor_input_hash` - The keccak256 hash of the constructor
+///   arguments.
+pub(super) fn zksync_create2(
+    factory: Address,
+    salt: B256,
+    bytecode_hash: B256,
+    constructor_input_hash: B256,
+) -> Address {
+    let preimage = zksync_preimage(factory, &salt.0, bytecode_hash, constructor_input_hash);
+    Address::from_word(keccak256(preimage))
+}
+
+/// Builds the preimage of a zkSync Era CREATE2 address.
+fn zksync_preimage(
+    factory: Address,
+    salt: &[u8; 32],
+    bytecode_hash: B256,
+    constructor_input_hash: B256,
+) -> Vec<u8> {
+    [
+        keccak256("zksyncCreate2").as_slice(),
+        factory.into_word().as_slice(),
+        salt,
+        bytecode_hash.as_slice(),
+        constructor_input_hash.as_slice(),
+    ]
+    .concat()
+}
+
+/// Builds the RLP encoding of the list `[deployer, nonce]`, with the
+/// deployer left empty.
+///
+/// The list is always shorter than 56 bytes, so the encoding starts with a
+/// 1-byte list header and the 20-byte string header, followed by the address
+/// at bytes 2 to 22 and the nonce.
+fn create1_template(nonce: u64) -> Vec<u8> {
+    // Integers are encoded as their big-endian bytes without leading zeros,
+    // single bytes below 0x80 standing for themselves
+    let bytes = nonce.to_be_bytes();
+    let trimmed = &bytes[nonce.leading_zeros() as usize / 8..];
+    let nonce = match trimmed {
+        [byte] if *byte < 0x80 => trimmed.to_vec(),
+        _ => [&[0x80 + trimmed.len() as u8], trimmed].concat(),
+    };
+    [
+        [0xc0 + 21 + nonce.len() as u8, 0x94].as_slice(),
+        &[0; 20],
+        &nonce,
+    ]
+    .concat()
+}
+
+/// CREATE preimage `rlp([deployer, nonce])` of the contract an account
+/// deploys at a given nonce.
+///
+/// The encoding is written once, so deriving addresses only loads the
+/// deployer addresses and runs the permutation.
+#[derive(Debug, Clone)]
+pub(super) struct Create1Preimage {
+    /// Sponge with the RLP headers and the nonce absorbed.
+    midstate: Midstate,
+}
+
+impl Default for Create1Preimage {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl Create1Preimage {
+    /// Absorbs the RLP headers and the nonce shared by every deployer.
+    pub(super) fn new(nonce: u64) -> Self {
+        Self {
+            midstate: Midstate::new(&create1_template(nonce), 2..22),
+        }
+    }
+
+    /// Returns the addresses of the contracts deployed by a batch of
+    /// accounts at the nonce.
+    #[inline]
+    pub(super) fn addresses(&self, deployers: &[Address; LANES]) -> [Address; LANES] {
+        let varying = deployers.each_ref().map(|deployer| deployer.as_slice());
+        self.midstate.hash_batch(varying).map(Address::from_word)
+    }
+}
+
+/// Keccak256 sponge with the fixed bytes of a message absorbed.
+///
+/// The blocks before the one holding the varying bytes are absorbed once,
+/// and the fixed bytes and the padding of that block are laid out in the
+/// state once, so only the 64-bit lanes covering the varying bytes are
+/// loaded for each message. Messages shorter than the rate are hashed with a
+/// single permutation, longer ones also absorb the blocks following the
+/// varying bytes.
+#[derive(Debug, Clone)]
+pub(super) struct Midstate {
+    /// State with the fixed bytes and the padding absorbed.
+    state: [u64; 25],
+    /// Padded block bytes of the lanes covering the varying bytes.
+    window: [u8; 8 * MAX_VARYING_LANES],
+    /// Lanes covering the varying bytes.
+    lanes: Range<usize>,
+    /// Offset of the varying bytes in the window.
+    offset: usize,
+    /// Padded blocks following the one holding the varying bytes.
+    tail: Vec<[u64; RATE / 8]>,
+}
+
+impl Midstate {
+    /// Absorbs the bytes of a message outside of its varying range.
+    ///
+    /// # Arguments
+    /// * `message` - A message, of which only the fixed bytes are absorbed.
+    /// * `varying` - The range of the bytes that differ between messages,
+    ///   which must not cross a block boundary.
+    pub(super) fn new(message: &[u8], varying: Range<usize>) -> Self {
+        assert!(
+            Self::fits(&varying),
+            "the varying bytes must be in one block"
+        );
+
+        // Pad the message with the Keccak 0x01 ... 0x80 padding
+        let mut padded = message.to_vec();
+        padded.resize(message.len() / RATE * RATE + RATE, 0);
+        padded[message.len()] ^= 0x01;
+        *padded.last_mut().expect("the padding is never empty") ^= 0x80;
+        let mut blocks = padded.chunks_exact(RATE).map(|block| {
+            let mut lanes = [0u64; RATE / 8];
+            for (lane, bytes) in lanes.iter_mut().zip(block.chunks_exact(8)) {
+                *lane = u64::from_le_bytes(bytes.try_into().expect("lanes are 8 bytes"));
+            }
+            lanes
+        });
+
+        // Absorb the blocks before the one holding the varying bytes
+        let index = varying.start / RATE;
+        let mut state = [0u64; 25];
+        for block in blocks.by_ref().take(index) {
+            absorb(&mut state, &block);
+            keccak::f1600(&mut state);
+        }
+        let block = blocks.next().expect("the varying bytes are in the message");
+        absorb(&mut state, &block);
+        let tail = blocks.collect();
+
+        // Keep the block bytes of the varying lanes to load messages into,
+        // leaving these lanes of the state as they were before the block
+        let block = &padded[index * RATE..(index + 1) * RATE];
+        let varying = varying.start - index * RATE..varying.end - index * RATE;
+        let lanes = varying.start / 8..varying.end.div_ceil(8);
+        assert!(lanes.len() <= MAX_VARYING_LANES, "too many varying bytes");
+        let mut window = [0u8; 8 * MAX_VARYING_LANES];
+        window[..8 * lanes.len()].copy_from_slice(&block[8 * lanes.start..8 * lanes.end]);
+        for (lane, bytes) in state[lanes.clone()].iter_mut().zip(window.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().expect("lanes are 8 bytes"));
+        }
+        Self {
+            state,
+            window,
+            offset: varying.start - 8 * lanes.start,
+            lanes,
+            tail,
+        }
+    }
+
+    /// Returns whether a range of varying bytes lies in a single block, as
+    /// needed to build a midstate.
+    pub(super) fn fits(varying: &Range<usize>) -> bool {
+        varying.start / RATE == varying.end.saturating_sub(1) / RATE
+    }
+
+    /// Returns the Keccak256 hashes of a batch of messages sharing the fixed
+    /// bytes.
+    ///
+    /// # Arguments
+    /// * `varying` - The varying bytes of each message.
+    #[inline]
+    pub(super) fn hash_batch(&self, varying: [&[u8]; LANES]) -> [B256; LANES] {
+        let mut states = varying.map(|varying| self.load(varying));
+        f1600_batch(&mut states);
+        for block in &self.tail {
+            states.iter_mut().for_each(|state| absorb(state, block));
+            f1600_batch(&mut states);
+        }
+        states.map(|state| {
+            let mut hash = B256::ZERO;
+            for (bytes, lane) in hash.chunks_exact_mut(8).zip(state) {
+                bytes.copy_from_slice(&lane.to_le_bytes());
+            }
+            hash
+        })
+    }
+
+    /// Returns the state with the varying bytes of a message absorbed.
+    #[inline]
+    fn load(&self, varying: &[u8]) -> [u64; 25] {
+        let mut window = self.window;
+        window[self.offset..self.offset + varying.len()].copy_from_slice(varying);
+
+        let mut state = self.state;
+        for (lane, bytes) in state[self.lanes.clone()]
+            .iter_mut()
+            .zip(window.chunks_exact(8))
+        {
+            *lane ^= u64::from_le_bytes(bytes.try_into().expect("lanes are 8 bytes"));
+        }
+        state
+    }
+}
+
+/// XORs a block of a message into the rate lanes of a state.
+#[inline]
+fn absorb(state: &mut [u64; 25], block: &[u64; RATE / 8]) {
+    for (lane, word) in state.iter_mut().zip(block) {
+        *lane ^= word;
+    }
+}
+
+/// Keccak-f[1600] kernels a batch of states can be hashed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Backend {
+    /// Eight states at once with AVX-512.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    Avx512,
+    /// Four states at once with AVX2.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    Avx2,
+    /// Two states at once with NEON and the SHA3 instructions.
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    NeonSha3,
+    /// Two states at once with NEON.
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    Neon,
+    /// One state at a time with the CRYPTOGAMS assembly.
+    #[cfg(feature = "asm")]
+    Asm,
+    /// One state at a time in portable Rust.
+    Portable,
+}
+
+impl Backend {
+    /// Returns the fastest kernel implementing a hasher on this machine, if
+    /// any. [`Hasher::Auto`] prefers batched SIMD, then assembly, then
+    /// portable Rust.
+    pub(super) fn detect(hasher: Hasher) -> Option<Self> {
+        #[cfg(feature = "asm")]
+        let asm = Some(Self::Asm);
+        #[cfg(not(feature = "asm"))]
+        let asm = None;
+        match hasher {
+            Hasher::Auto => Some(Self::detect_simd().or(asm).unwrap_or(Self::Portable)),
+            Hasher::Simd => Self::detect_simd(),
+            Hasher::Asm => asm,
+            Hasher::Portable => Some(Self::Portable),
+        }
+    }
+
+    /// Returns the widest SIMD kernel the CPU supports, if built in.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    fn detect_simd() -> Option<Self> {
+        if std::arch::is_x86_feature_detected!("avx512f") {
+            Some(Self::Avx512)
+        } else if std::arch::is_x86_feature_detected!("avx2") {
+            Some(Self::Avx2)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the widest SIMD kernel the CPU supports, if built in.
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    fn detect_simd() -> Option<Self> {
+        if std::arch::is_aarch64_feature_detected!("sha3") {
+            Some(Self::NeonSha3)
+        } else if std::arch::is_aarch64_feature_detected!("neon") {
+            Some(Self::Neon)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the widest SIMD kernel the CPU supports, if built in.
+    #[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+    fn detect_simd() -> Option<Self> {
+        None
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+            Self::Avx512 => "AVX-512",
+            #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+            Self::Avx2 => "AVX2",
+            #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+            Self::NeonSha3 => "NEON+SHA3",
+            #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+            Self::Neon => "NEON",
+            #[cfg(feature = "asm")]
+            Self::Asm => "asm",
+            Self::Portable => "portable",
+        })
+    }
+}
+
+/// Selects the Keccak kernel every batch is hashed with for the rest of the
+/// process, returning the selected kernel.
+///
+/// Only the first selection takes effect; batches hashed before any selection
+/// use the fastest kernel available.
+///
+/// # Errors
+///
+/// Returns an error if the hasher is not supported by the CPU or not built
+/// in.
+pub(super) fn select(hasher: Hasher) -> Result<Backend> {
+    let backend = Backend::detect(hasher).ok_or(match hasher {
+        Hasher::Asm => Error::HasherUnavailable {
+            name: "asm",
+            reason: "piwi was built without the `asm` feature",
+        },
+        _ => Error::HasherUnavailable {
+            name: "simd",
+            reason: "the CPU supports neither AVX-512, AVX2 nor NEON, or the `simd` feature is disabled",
+        },
+    })?;
+    Ok(*BACKEND.get_or_init(|| backend))
+}
+
+/// Applies Keccak-f[1600] to a batch of states with the selected kernel,
+/// detected once per process.
+#[inline]
+fn f1600_batch(states: &mut [[u64; 25]; LANES]) {
+    let backend = BACKEND.get_or_init(|| {
+        Backend::detect(Hasher::Auto).expect("the automatic hasher is always available")
+    });
+    f1600_batch_with(*backend, states);
+}
+
+/// Applies Keccak-f[1600] to a batch of states with the given kernel, falling
+/// back to portable Rust if the CPU does not support it.
+#[inline]
+pub(super) fn f1600_batch_with(backend: Backend, states: &mut [[u64; 25]; LANES]) {
+    match backend {
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        Backend::Avx512 if std::arch::is_x86_feature_detected!("avx512f") => {
+            // SAFETY: the CPU supports AVX-512
+            unsafe { avx512::f1600x8(states) };
+        }
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        Backend::Avx2 if std::arch::is_x86_feature_detected!("avx2") => {
+            for half in states.as_chunks_mut().0 {
+                // SAFETY: the CPU supports AVX2
+                unsafe { avx2::f1600x4(half) };
+            }
+        }
+        #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+        Backend::NeonSha3 if std::arch::is_aarch64_feature_detected!("sha3") => {
+            for pair in states.as_chunks_mut().0 {
+                // SAFETY: the CPU supports NEON and the SHA3 instructions
+                unsafe { neon::f1600x2_sha3(pair) };
+            }
+        }
+        #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+        Backend::Neon if std::arch::is_aarch64_feature_detected!("neon") => {
+            for pair in states.as_chunks_mut().0 {
+                // SAFETY: the CPU supports NEON
+                unsafe { neon::f1600x2(pair) };
+            }
+        }
+        #[cfg(feature = "asm")]
+        Backend::Asm => {
+            // Absorbing a block of zeros leaves the state as is before
+            // permuting it
+            for state in states {
+                sha3_asm::sha3_absorb(state, &[0; RATE], RATE);
+            }
+        }
+        _ => states.iter_mut().for_each(keccak::f1600),
+    }
+}
+
+#[test]
+fn test_midstate() {
+    use alloy_primitives::{address, keccak256};
+
+    // Hash salts differing in their last 6 bytes
+    let salts: [[u8; 32]; LANES] = std::array::from_fn(|index| {
+        let mut salt = [0x5a; 32];
+        salt[26..].copy_from_slice(&(index as u64 * 0x0101_0101).to_be_bytes()[2..]);
+        salt
+    });
+    let midstate = Midstate::new(&salts[0], 26..32);
+    let hashes = midstate.hash_batch(salts.each_ref().map(|salt| &salt[26..]));
+    assert_eq!(hashes, salts.map(keccak256));
+
+    // Hash the salts between a long prefix and suffix
+    let messages = salts.map(|salt| [[0xa5; 300].as_slice(), &salt, &[0x3c; 200]].concat());
+    let midstate = Midstate::new(&messages[0], 326..332);
+    let hashes = midstate.hash_batch(salts.each_ref().map(|salt| &salt[26..]));
+    assert_eq!(hashes, messages.map(keccak256));
+
+    let factory = address!("0x4e59b44847b379578914e5a3c99a3a8a1d6b1c6b");
+    let init_code_hash = keccak256([0x60, 0x80]);
+    let preimage = Create2Preimage::new(factory, &salts[0], init_code_hash, 26..32);
+    let addresses = preimage.addresses(&salts);
+    assert_eq!(
+        addresses,
+        salts.map(|salt| factory.create2(salt, init_code_hash))
+    );
+    let constructor_input_hash = keccak256([]);
+    let preimage = Create2Preimage::zksync(
+        factory,
+        &salts[0],
+        init_code_hash,
+        constructor_input_hash,
+        26..32,
+    );
+    assert_eq!(
+        preimage.addresses(&salts),
+        salts.map(|salt| zksync_create2(
+            factory,
+            salt.into(),
+            init_code_hash,
+            constructor_input_hash
+        ))
+    );
+    for nonce in [0, 1, 0x7f, 0x80, 0x1234, u64::MAX] {
+        assert_eq!(
+            Create1Preimage::new(nonce).addresses(&addresses),
+            addresses.map(|address| address.create(nonce))
+        );
+    }
+}
+
+#[test]
+fn test_f1600_batch_with() {
+    // Cross-check every available implementation against the portable one
+    let states: [[u64; 25]; LANES] =
+        std::array::from_fn(|index| std::array::from_fn(|lane| (index * 25 + lane) as u64));
+    let mut expected = states;
+    expected.iter_mut().for_each(keccak::f1600);
+    for backend in [Hasher::Simd, Hasher::Asm, Hasher::Portable]
+        .into_iter()
+        .filter_map(Backend::detect)
+    {
+        let mut states = states;
+        f1600_batch_with(backend, &mut states);
+        assert_eq!(states, expected, "{backend}");
+    }
+}
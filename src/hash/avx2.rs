@@ -0,0 +1,68 @@
+use std::{arch::x86_64::*, mem};
+
+/// Applies Keccak-f[1600] to four states at once, one per 64-bit lane of the
+/// AVX2 registers.
+#[target_feature(enable = "avx2")]
+pub(super) fn f1600x4(states: &mut [[u64; 25]; 4]) {
+    // Interleave the states so that each register holds a lane of every state
+    let mut state: [__m256i; 25] = std::array::from_fn(|lane| {
+        _mm256_set_epi64x(
+            states[3][lane] as i64,
+            states[2][lane] as i64,
+            states[1][lane] as i64,
+            states[0][lane] as i64,
+        )
+    });
+
+    keccak_f1600!(state, xor, andnot, rotl, splat);
+
+    for (lane, vector) in state.into_iter().enumerate() {
+        // SAFETY: a 256-bit vector has the layout of four 64-bit integers
+        let lanes: [u64; 4] = unsafe { mem::transmute(vector) };
+        for (state, value) in states.iter_mut().zip(lanes) {
+            state[lane] = value;
+        }
+    }
+}
+
+#[target_feature(enable = "avx2")]
+#[inline]
+fn xor(a: __m256i, b: __m256i) -> __m256i {
+    _mm256_xor_si256(a, b)
+}
+
+#[target_feature(enable = "avx2")]
+#[inline]
+fn andnot(a: __m256i, b: __m256i) -> __m256i {
+    _mm256_andnot_si256(a, b)
+}
+
+#[target_feature(enable = "avx2")]
+#[inline]
+fn rotl(a: __m256i, bits: u32) -> __m256i {
+    let left = _mm256_sll_epi64(a, _mm_cvtsi32_si128(bits as i32));
+    let right = _mm256_srl_epi64(a, _mm_cvtsi32_si128(64 - bits as i32));
+    _mm256_or_si256(left, right)
+}
+
+#[target_feature(enable = "avx2")]
+#[inline]
+fn splat(value: u64) -> __m256i {
+    _mm256_set1_epi64x(value as i64)
+}
+
+#[test]
+fn test_f1600x4() {
+    if !is_x86_feature_detected!("avx2") {
+        return;
+    }
+
+    // Cross-check the lanes against the scalar permutation
+    let mut states: [[u64; 25]; 4] =
+        std::array::from_fn(|index| std::array::from_fn(|lane| (index * 25 + lane) as u64));
+    let mut expected = states;
+    expected.iter_mut().for_each(keccak::f1600);
+    // SAFETY: the CPU supports AVX2
+    unsafe { f1600x4(&mut states) };
+    assert_eq!(states, expected);
+}
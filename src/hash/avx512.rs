@@ -0,0 +1,70 @@
+use std::{arch::x86_64::*, mem};
+
+/// Applies Keccak-f[1600] to eight states at once, one per 64-bit lane of the
+/// AVX-512 registers.
+#[target_feature(enable = "avx512f")]
+pub(super) fn f1600x8(states: &mut [[u64; 25]; 8]) {
+    // Interleave the states so that each register holds a lane of every state
+    let mut state: [__m512i; 25] = std::array::from_fn(|lane| {
+        _mm512_set_epi64(
+            states[7][lane] as i64,
+            states[6][lane] as i64,
+            states[5][lane] as i64,
+            states[4][lane] as i64,
+            states[3][lane] as i64,
+            states[2][lane] as i64,
+            states[1][lane] as i64,
+            states[0][lane] as i64,
+        )
+    });
+
+    keccak_f1600!(state, xor, andnot, rotl, splat);
+
+    for (lane, vector) in state.into_iter().enumerate() {
+        // SAFETY: a 512-bit vector has the layout of eight 64-bit integers
+        let lanes: [u64; 8] = unsafe { mem::transmute(vector) };
+        for (state, value) in states.iter_mut().zip(lanes) {
+            state[lane] = value;
+        }
+    }
+}
+
+#[target_feature(enable = "avx512f")]
+#[inline]
+fn xor(a: __m512i, b: __m512i) -> __m512i {
+    _mm512_xor_si512(a, b)
+}
+
+#[target_feature(enable = "avx512f")]
+#[inline]
+fn andnot(a: __m512i, b: __m512i) -> __m512i {
+    _mm512_andnot_si512(a, b)
+}
+
+#[target_feature(enable = "avx512f")]
+#[inline]
+fn rotl(a: __m512i, bits: u32) -> __m512i {
+    _mm512_rolv_epi64(a, _mm512_set1_epi64(i64::from(bits)))
+}
+
+#[target_feature(enable = "avx512f")]
+#[inline]
+fn splat(value: u64) -> __m512i {
+    _mm512_set1_epi64(value as i64)
+}
+
+#[test]
+fn test_f1600x8() {
+    if !is_x86_feature_detected!("avx512f") {
+        return;
+    }
+
+    // Cross-check the lanes against the scalar permutation
+    let mut states: [[u64; 25]; 8] =
+        std::array::from_fn(|index| std::array::from_fn(|lane| (index * 25 + lane) as u64));
+    let mut expected = states;
+    expected.iter_mut().for_each(keccak::f1600);
+    // SAFETY: the CPU supports AVX-512
+    unsafe { f1600x8(&mut states) };
+    assert_eq!(states, expected);
+}
@@ -0,0 +1,80 @@
+use std::{arch::aarch64::*, mem};
+
+/// Applies Keccak-f[1600] to two states at once, one per 64-bit lane of the
+/// NEON registers.
+#[target_feature(enable = "neon")]
+#[inline]
+pub(super) fn f1600x2(states: &mut [[u64; 25]; 2]) {
+    // Interleave the states so that each register holds a lane of both states
+    let mut state: [uint64x2_t; 25] = std::array::from_fn(|lane| {
+        vcombine_u64(vcreate_u64(states[0][lane]), vcreate_u64(states[1][lane]))
+    });
+
+    keccak_f1600!(state, xor, andnot, rotl, splat);
+
+    for (lane, vector) in state.into_iter().enumerate() {
+        // SAFETY: a 128-bit vector has the layout of two 64-bit integers
+        let lanes: [u64; 2] = unsafe { mem::transmute(vector) };
+        for (state, value) in states.iter_mut().zip(lanes) {
+            state[lane] = value;
+        }
+    }
+}
+
+/// Applies Keccak-f[1600] to two states at once with the SHA3 instructions
+/// enabled, which the compiler fuses the lane operations into.
+#[target_feature(enable = "neon,sha3")]
+pub(super) fn f1600x2_sha3(states: &mut [[u64; 25]; 2]) {
+    f1600x2(states);
+}
+
+#[target_feature(enable = "neon")]
+#[inline]
+fn xor(a: uint64x2_t, b: uint64x2_t) -> uint64x2_t {
+    veorq_u64(a, b)
+}
+
+#[target_feature(enable = "neon")]
+#[inline]
+fn andnot(a: uint64x2_t, b: uint64x2_t) -> uint64x2_t {
+    vbicq_u64(b, a)
+}
+
+#[target_feature(enable = "neon")]
+#[inline]
+fn rotl(a: uint64x2_t, bits: u32) -> uint64x2_t {
+    // Negative shifts move the bits right
+    let left = vshlq_u64(a, vdupq_n_s64(i64::from(bits)));
+    let right = vshlq_u64(a, vdupq_n_s64(i64::from(bits) - 64));
+    vorrq_u64(left, right)
+}
+
+#[target_feature(enable = "neon")]
+#[inline]
+fn splat(value: u64) -> uint64x2_t {
+    vdupq_n_u64(value)
+}
+
+#[test]
+fn test_f1600x2() {
+    if !std::arch::is_aarch64_feature_detected!("neon") {
+        return;
+    }
+
+    // Cross-check the lanes against the scalar permutation
+    let mut states: [[u64; 25]; 2] =
+        std::array::from_fn(|index| std::array::from_fn(|lane| (index * 25 + lane) as u64));
+    let mut expected = states;
+    expected.iter_mut().for_each(keccak::f1600);
+    // SAFETY: the CPU supports NEON
+    unsafe { f1600x2(&mut states) };
+    assert_eq!(states, expected);
+
+    if std::arch::is_aarch64_feature_detected!("sha3") {
+        let mut states = expected;
+        expected.iter_mut().for_each(keccak::f1600);
+        // SAFETY: the CPU supports NEON and the SHA3 instructions
+        unsafe { f1600x2_sha3(&mut states) };
+        assert_eq!(states, expected);
+    }
+}
@@ -0,0 +1,366 @@
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy_primitives::{Address, B256, hex, keccak256};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    artifact,
+    cli::{CreationCode, InitCode},
+    error::{Error, Result},
+};
+
+/// Largest init code deployable since EIP-3860, twice the EIP-170 limit on
+/// runtime code.
+const MAX_INIT_CODE_SIZE: usize = 49_152;
+
+impl InitCode {
+    /// Resolves the init code hash from whichever source was given, along
+    /// with the creation code and constructor arguments it hashes, unless the
+    /// hash itself was given. The implementation and initializer of an
+    /// ERC-1967 proxy are encoded as its constructor arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no source was given or the init code file or
+    /// artifact cannot be read.
+    pub(super) fn resolve(self) -> Result<(B256, Option<Vec<u8>>)> {
+        if let Some(init_code_hash) = self.source.init_code_hash {
+            return Ok((init_code_hash, None));
+        }
+        let (mut init_code, constructor_args) = self.read()?;
+        init_code.extend(constructor_args);
+        check_init_code(&init_code)?;
+        Ok((keccak256(&init_code), Some(init_code)))
+    }
+
+    /// Resolves the zkSync Era bytecode hash from whichever source was given,
+    /// along with the hash of the constructor arguments, which zkSync Era
+    /// hashes separately from the bytecode.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no source was given, the init code file or
+    /// artifact cannot be read, or the bytecode cannot be deployed on zkSync
+    /// Era.
+    pub(super) fn resolve_zksync(self) -> Result<(B256, B256)> {
+        if let Some(bytecode_hash) = self.source.init_code_hash {
+            return Ok((bytecode_hash, keccak256([])));
+        }
+        let (bytecode, constructor_args) = self.read()?;
+        Ok((
+            hash_zksync_bytecode(&bytecode)?,
+            keccak256(constructor_args),
+        ))
+    }
+
+    /// Loads the creation code and the encoded constructor arguments.
+    fn read(self) -> Result<(Vec<u8>, Vec<u8>)> {
+        // Load the creation code from the command line, a file or an artifact
+        let source = self.source;
+        let init_code = if let Some(init_code) = source.init_code {
+            init_code.into()
+        } else if let Some(path) = source.init_code_file {
+            read_init_code(&path)?
+        } else if let Some(path) = source.artifact {
+            artifact::read_artifact(&path)?
+        } else if let Some(implementation) = source.clone_of {
+            clone_init_code(implementation)
+        } else {
+            return Err(Error::MissingInitCode);
+        };
+
+        // Beacon proxies take their beacon where ERC-1967 proxies take their
+        // implementation
+        let constructor_args = match self.proxy_implementation.or(self.proxy_beacon) {
+            Some(target) => encode_proxy_args(target, &self.proxy_data.unwrap_or_default()),
+            None => encode_constructor_args(&self.constructor_args)?,
+        };
+        Ok((init_code, constructor_args))
+    }
+}
+
+impl CreationCode {
+    /// Reads the creation code of the artifact with the constructor arguments
+    /// appended, if an artifact was given.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the artifact cannot be read or the constructor
+    /// arguments are malformed.
+    pub(super) fn read(&self) -> Result<Option<Vec<u8>>> {
+        let Some(path) = &self.artifact else {
+            return Ok(None);
+        };
+        let mut creation_code = artifact::read_artifact(path)?;
+        creation_code.extend(encode_constructor_args(&self.constructor_args)?);
+        check_init_code(&creation_code)?;
+        Ok(Some(creation_code))
+    }
+}
+
+/// Checks that init code can be deployed before any time is spent mining a
+/// salt for it, warning when it is close to the EIP-3860 limit or looks like
+/// runtime code given by mistake.
+///
+/// # Errors
+///
+/// Returns an error if the init code is over the EIP-3860 limit.
+pub(super) fn check_init_code(init_code: &[u8]) -> Result<()> {
+    if init_code.len() > MAX_INIT_CODE_SIZE {
+        return Err(Error::InitCodeTooLarge {
+            size: init_code.len(),
+            limit: MAX_INIT_CODE_SIZE,
+        });
+    }
+    if init_code.len() > MAX_INIT_CODE_SIZE / 10 * 9 {
+        eprintln!(
+            "warning: init code is {} bytes, close to the EIP-3860 limit of {MAX_INIT_CODE_SIZE} bytes",
+            init_code.len()
+        );
+    }
+    if !init_code.is_empty() && !returns_code(init_code) {
+        eprintln!(
+            "warning: init code never copies code to return it, it may be runtime code instead of \
+             creation code"
+        );
+    }
+    Ok(())
+}
+
+/// Returns whether bytecode copies code into memory and later returns, as
+/// the constructor prologue of creation code does to return the runtime
+/// code.
+fn returns_code(bytecode: &[u8]) -> bool {
+    // Walk the opcodes, skipping the data of PUSH1 to PUSH32
+    let mut copied = false;
+    let mut index = 0;
+    while index < bytecode.len() {
+        match bytecode[index] {
+            // CODECOPY or EXTCODECOPY
+            0x39 | 0x3c => copied = true,
+            // RETURN
+            0xf3 if copied => return true,
+            opcode @ 0x60..=0x7f => index += usize::from(opcode - 0x5f),
+            _ => {}
+        }
+        index += 1;
+    }
+    false
+}
+
+/// Reads contract creation bytecode from a file, or from stdin when the path
+/// is `-`.
+///
+/// The contents may either be hex encoded (with an optional `0x` prefix and
+/// surrounding whitespace) or raw binary bytecode.
+///
+/// # Errors
+///
+/// Returns an error if the input cannot be read or is malformed hex.
+pub(super) fn read_init_code(path: &str) -> Result<Vec<u8>> {
+    // Read the whole input as bytes, since it may not be valid UTF-8
+    let contents = if path == "-" {
+        let mut contents = Vec::new();
+        io::stdin().read_to_end(&mut contents).map(|_| contents)
+    } else {
+        fs::read(path)
+    }
+    .map_err(|source| Error::Read {
+        path: path.to_string(),
+        source,
+    })?;
+
+    decode_init_code(contents)
+}
+
+/// Decodes bytecode that is either hex encoded or already raw binary.
+fn decode_init_code(contents: Vec<u8>) -> Result<Vec<u8>> {
+    // Strip the surrounding whitespace and the optional hex prefix
+    let trimmed = contents.trim_ascii();
+    let digits = trimmed.strip_prefix(b"0x").unwrap_or(trimmed);
+
+    // Treat the input as hex only if every character is a hex digit
+    if !digits.is_empty() && digits.iter().all(u8::is_ascii_hexdigit) {
+        hex::decode(digits).map_err(|source| Error::InvalidHex {
+            name: "init code",
+            value: String::from_utf8_lossy(trimmed).into_owned(),
+            source,
+        })
+    } else {
+        Ok(contents)
+    }
+}
+
+/// ABI encodes constructor arguments given on the command line.
+///
+/// A single argument is taken as already encoded hex. Otherwise the first
+/// argument is the signature of the constructor parameters, e.g.
+/// `(address,uint24)`, and the remaining ones are their values.
+///
+/// # Errors
+///
+/// Returns an error if the hex, the signature or any value is malformed, or
+/// if the number of values does not match the signature.
+pub(super) fn encode_constructor_args(args: &[String]) -> Result<Vec<u8>> {
+    let (signature, values) = match args {
+        [] => return Ok(Vec::new()),
+        [encoded] => {
+            return hex::decode(encoded).map_err(|source| Error::InvalidHex {
+                name: "constructor arguments",
+                value: encoded.clone(),
+                source,
+            });
+        }
+        [signature, values @ ..] => (signature, values),
+    };
+
+    // Accept the parameter list with or without the surrounding parentheses
+    let signature = if signature.starts_with('(') {
+        signature.clone()
+    } else {
+        format!("({signature})")
+    };
+    let Ok(DynSolType::Tuple(types)) = DynSolType::parse(&signature) else {
+        return Err(Error::InvalidConstructorArgs(format!(
+            "invalid signature {signature:?}"
+        )));
+    };
+    if types.len() != values.len() {
+        return Err(Error::InvalidConstructorArgs(format!(
+            "signature {signature:?} expects {} values, but {} were given",
+            types.len(),
+            values.len()
+        )));
+    }
+
+    // Coerce each value to its parameter type before encoding them together
+    let values = types
+        .iter()
+        .zip(values)
+        .map(|(ty, value)| {
+            ty.coerce_str(value).map_err(|err| {
+                Error::InvalidConstructorArgs(format!("invalid {ty} value {value:?}: {err}"))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(DynSolValue::Tuple(values).abi_encode_params())
+}
+
+/// ABI encodes the `(address implementation, bytes data)` constructor
+/// arguments of an ERC-1967 proxy, or the `(address beacon, bytes data)` ones
+/// of a beacon proxy.
+pub(super) fn encode_proxy_args(target: Address, data: &[u8]) -> Vec<u8> {
+    DynSolValue::Tuple(vec![
+        DynSolValue::Address(target),
+        DynSolValue::Bytes(data.to_vec()),
+    ])
+    .abi_encode_params()
+}
+
+/// Builds the creation code of an ERC-1167 minimal proxy delegating to an
+/// implementation, as deployed by OpenZeppelin's `Clones` library.
+pub(super) fn clone_init_code(implementation: Address) -> Vec<u8> {
+    [
+        hex!("3d602d80600a3d3981f3363d3d373d3d3d363d73").as_slice(),
+        implementation.as_slice(),
+        &hex!("5af43d82803e903d91602b57fd5bf3"),
+    ]
+    .concat()
+}
+
+/// Computes the hash zkSync Era identifies bytecode with: a version byte and
+/// a zero byte, the length in 32-byte words, and the last 28 bytes of the
+/// SHA-256 of the bytecode.
+///
+/// # Errors
+///
+/// Returns an error if the bytecode is not made of an odd number of 32-byte
+/// words below 2^16.
+pub(super) fn hash_zksync_bytecode(bytecode: &[u8]) -> Result<B256> {
+    // zkSync Era only executes bytecode made of an odd number of words
+    let words = bytecode.len() / 32;
+    if !bytecode.len().is_multiple_of(32) {
+        return Err(Error::InvalidZksyncBytecode(format!(
+            "{} bytes is not a whole number of 32-byte words",
+            bytecode.len()
+        )));
+    }
+    let Ok(length) = u16::try_from(words) else {
+        return Err(Error::InvalidZksyncBytecode(format!(
+            "{words} words is over the limit of 65535"
+        )));
+    };
+    if words.is_multiple_of(2) {
+        return Err(Error::InvalidZksyncBytecode(format!(
+            "{words} words is not an odd number"
+        )));
+    }
+
+    let mut hash = B256::from_slice(&Sha256::digest(bytecode));
+    hash[..2].copy_from_slice(&[1, 0]);
+    hash[2..4].copy_from_slice(&length.to_be_bytes());
+    Ok(hash)
+}
+
+/// Computes the init code hash of creation bytecode with ABI encoded
+/// constructor arguments appended to it.
+pub(super) fn hash_init_code(mut init_code: Vec<u8>, constructor_args: &[u8]) -> B256 {
+    init_code.extend_from_slice(constructor_args);
+    keccak256(init_code)
+}
+
+#[test]
+fn test_decode_init_code() {
+    let binary = vec![0x60, 0x80, 0x60, 0x40];
+    assert_eq!(decode_init_code(b"0x60806040\n".to_vec()).unwrap(), binary);
+    assert_eq!(decode_init_code(b"60806040".to_vec()).unwrap(), binary);
+    assert_eq!(decode_init_code(binary.clone()).unwrap(), binary);
+    assert!(decode_init_code(b"0x608".to_vec()).is_err());
+}
+
+#[test]
+fn test_encode_constructor_args() {
+    let args = [
+        "(address,uint24)",
+        "0x000000000004444c5dc75cB358380D2e3dE08A90",
+        "3000",
+    ];
+    let encoded = encode_constructor_args(&args.map(String::from)).unwrap();
+    assert_eq!(
+        hex::encode(encoded),
+        "000000000000000000000000000000000004444c5dc75cb358380d2e3de08a90\
+         0000000000000000000000000000000000000000000000000000000000000bb8"
+    );
+
+    assert!(encode_constructor_args(&["(address)".to_string()]).is_err());
+    assert!(encode_constructor_args(&["address".to_string(), "0x12".to_string()]).is_err());
+}
+
+#[test]
+fn test_hash_zksync_bytecode() {
+    assert_eq!(
+        hash_zksync_bytecode(&[0; 96]).unwrap().to_string(),
+        "0x0100000398d1638007400cd2c3bef1cc745b864b76011a0e1bc52180ac6452d4"
+    );
+
+    // Bytecode must be an odd number of whole words
+    assert!(hash_zksync_bytecode(&[0; 64]).is_err());
+    assert!(hash_zksync_bytecode(&[0; 33]).is_err());
+}
+
+#[test]
+fn test_check_init_code() {
+    // Clones return the runtime code they copy, unlike the runtime code
+    let clone = clone_init_code(Address::ZERO);
+    assert!(returns_code(&clone));
+    assert!(!returns_code(&clone[10..]));
+
+    // Push data is not mistaken for opcodes
+    assert!(!returns_code(&hex!("6139f3")));
+
+    assert!(check_init_code(&[0; MAX_INIT_CODE_SIZE + 1]).is_err());
+}
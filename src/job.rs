@@ -0,0 +1,190 @@
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use alloy_primitives::{Address, B256, Bytes};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    mine::{Create2Miner, Create3Miner, Miner, MiningResult, ProxySalt},
+    pattern::Pattern,
+};
+
+/// Inputs of a mining run: how salts derive addresses, and the pattern the
+/// address has to match.
+///
+/// Serialized as the flat JSON object read by `piwi job`, e.g.
+/// `{"method": "create3", "deployer": "0x…", "factory": "0x…", "flags":
+/// "0x0080"}`, so that files, APIs and logs share one schema with
+/// [`MiningResult`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MiningJob {
+    /// How salts derive addresses.
+    #[serde(flatten)]
+    pub derivation: Derivation,
+    /// Bytes pinned right after the deployer address in the salt.
+    #[serde(default)]
+    pub salt_prefix: Bytes,
+    /// Flags and prefix the address has to match.
+    #[serde(flatten)]
+    pub pattern: Pattern,
+}
+
+/// Deployment method of a job, with the inputs deriving addresses from
+/// salts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "lowercase")]
+pub enum Derivation {
+    /// The factory deploys the init code with CREATE2.
+    Create2 {
+        /// Address calling the factory, which starts the salt.
+        deployer: Address,
+        /// Address of the CREATE2 factory contract.
+        factory: Address,
+        /// Keccak256 hash of the init code of the contract.
+        init_code_hash: B256,
+    },
+    /// The factory deploys a proxy with CREATE2, which deploys the contract
+    /// with CREATE.
+    Create3 {
+        /// Address calling the factory.
+        deployer: Address,
+        /// Address of the CREATE3 factory contract.
+        factory: Address,
+        /// How the factory derives the proxy salt, hashing the salt after
+        /// the deployer by default.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proxy_salt: Option<ProxySalt>,
+        /// Hash of the init code of the proxy, the usual 16-byte proxy by
+        /// default.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proxy_init_code_hash: Option<B256>,
+        /// Nonce the proxy deploys the contract at, 1 by default.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proxy_nonce: Option<u64>,
+    },
+}
+
+/// Miner built from a job, for either deployment method.
+pub(super) enum JobMiner {
+    /// Miner of CREATE2 jobs.
+    Create2(Create2Miner),
+    /// Miner of CREATE3 jobs.
+    Create3(Create3Miner),
+}
+
+impl MiningJob {
+    /// Builds the miner deriving addresses like the job.
+    pub(super) fn miner(&self) -> JobMiner {
+        match self.derivation {
+            Derivation::Create2 {
+                deployer,
+                factory,
+                init_code_hash,
+            } => JobMiner::Create2(
+                Create2Miner::new(deployer, factory, init_code_hash)
+                    .with_salt_prefix(self.salt_prefix.clone()),
+            ),
+            Derivation::Create3 {
+                deployer,
+                factory,
+                proxy_salt,
+                proxy_init_code_hash,
+                proxy_nonce,
+            } => {
+                // Keep the defaults of the miner for the omitted inputs
+                let mut miner =
+                    Create3Miner::new(deployer, factory).with_salt_prefix(self.salt_prefix.clone());
+                if let Some(proxy_salt) = proxy_salt {
+                    miner = miner.with_proxy_salt(proxy_salt);
+                }
+                if let Some(proxy_init_code_hash) = proxy_init_code_hash {
+                    miner = miner.with_proxy_init_code_hash(proxy_init_code_hash);
+                }
+                if let Some(proxy_nonce) = proxy_nonce {
+                    miner = miner.with_proxy_nonce(proxy_nonce);
+                }
+                JobMiner::Create3(miner)
+            }
+        }
+    }
+
+    /// Mines a salt for the job on the global thread pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if mining fails, see [`Miner::mine`].
+    pub fn mine(&self) -> Result<MiningResult> {
+        match self.miner() {
+            JobMiner::Create2(miner) => miner.mine(&self.pattern),
+            JobMiner::Create3(miner) => miner.mine(&self.pattern),
+        }
+    }
+}
+
+/// Reads a JSON mining job from a file, or from stdin when the path is `-`.
+///
+/// # Errors
+///
+/// Returns an error if the input cannot be read or is not a valid job.
+pub(super) fn read_job(path: &str) -> Result<MiningJob> {
+    let contents = if path == "-" {
+        let mut contents = Vec::new();
+        io::stdin().read_to_end(&mut contents).map(|_| contents)
+    } else {
+        fs::read(path)
+    }
+    .map_err(|source| Error::Read {
+        path: path.to_string(),
+        source,
+    })?;
+
+    serde_json::from_slice(&contents).map_err(|err| Error::InvalidJob {
+        path: path.to_string(),
+        reason: err.to_string(),
+    })
+}
+
+#[test]
+fn test_mining_job() {
+    use serde_json::json;
+
+    let job: MiningJob = serde_json::from_value(json!({
+        "method": "create3",
+        "deployer": Address::ZERO,
+        "factory": Address::ZERO,
+        "proxy_salt": "raw",
+        "flags": "0x80",
+    }))
+    .unwrap();
+    let JobMiner::Create3(miner) = job.miner() else {
+        panic!("the job is a CREATE3 one");
+    };
+    let result = job.mine().unwrap();
+    assert_eq!(miner.compute_address(&result.salt), result.address);
+    assert!(job.pattern.matcher().matches(&result.address));
+
+    // Jobs and results round-trip through their canonical JSON
+    let serialized = serde_json::to_value(&job).unwrap();
+    assert_eq!(serialized["flags"], "0x0080");
+    assert_eq!(
+        serde_json::from_value::<MiningJob>(serialized).unwrap(),
+        job
+    );
+    let serialized = serde_json::to_string(&result).unwrap();
+    assert_eq!(
+        serde_json::from_str::<MiningResult>(&serialized).unwrap(),
+        result
+    );
+
+    // Malformed patterns are rejected like on the command line
+    let job = json!({
+        "method": "create3",
+        "deployer": Address::ZERO,
+        "factory": Address::ZERO,
+        "flags": "0x4000",
+    });
+    assert!(serde_json::from_value::<MiningJob>(job).is_err());
+}
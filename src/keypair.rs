@@ -0,0 +1,142 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use alloy_primitives::{Address, hex, keccak256};
+use k256::{
+    NonZeroScalar, ProjectivePoint, Scalar, SecretKey,
+    elliptic_curve::{PrimeField, sec1::ToEncodedPoint},
+};
+use rand::RngCore;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{
+    error::{Error, Result},
+    pattern::Pattern,
+};
+
+/// Number of keys a worker tries between two checks of whether another
+/// worker found a match.
+const BATCH_SIZE: u64 = 1024;
+
+/// Finds a deployer key pair whose CREATE address at a nonce matches a
+/// pattern, returning its private key, its address and the contract address.
+///
+/// Each worker starts from a random key and steps through the following
+/// ones, which costs a point addition per key instead of a multiplication.
+/// The starting keys come from the thread-local CSPRNG of `rand`, seeded from
+/// the operating system.
+///
+/// # Arguments
+/// * `nonce` - The nonce the account deploys the contract with.
+/// * `pattern` - The pattern the contract address has to match.
+pub(super) fn find_keypair(nonce: u64, pattern: &Pattern) -> (SecretKey, Address, Address) {
+    let matcher = pattern.matcher();
+    let found = AtomicBool::new(false);
+    (0..rayon::current_num_threads())
+        .into_par_iter()
+        .find_map_any(|_| {
+            let mut key = random_key();
+            let mut point = ProjectivePoint::GENERATOR * key.as_ref();
+            while !found.load(Ordering::Relaxed) {
+                for _ in 0..BATCH_SIZE {
+                    let deployer = account_address(&point);
+                    let address = deployer.create(nonce);
+                    if matcher.matches(&address) {
+                        found.store(true, Ordering::Relaxed);
+                        return Some((SecretKey::from(key), deployer, address));
+                    }
+
+                    // The successor of a random key is never zero in practice
+                    key = NonZeroScalar::new(key.as_ref() + Scalar::ONE).unwrap_or_else(random_key);
+                    point += ProjectivePoint::GENERATOR;
+                }
+            }
+            None
+        })
+        .expect("a worker keeps searching until it finds a key pair")
+}
+
+/// Draws a uniformly random private key.
+fn random_key() -> NonZeroScalar {
+    let mut rng = rand::rng();
+    loop {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        if let Some(key) = Option::from(Scalar::from_repr(bytes.into()))
+            .and_then(|key| Option::from(NonZeroScalar::new(key)))
+        {
+            return key;
+        }
+    }
+}
+
+/// Returns the address of the account owning a public key, the last 20 bytes
+/// of the hash of its uncompressed coordinates.
+fn account_address(point: &ProjectivePoint) -> Address {
+    let encoded = point.to_affine().to_encoded_point(false);
+    Address::from_slice(&keccak256(&encoded.as_bytes()[1..])[12..])
+}
+
+/// Creates the file the private key is written to, failing if it already
+/// exists, so that mining does not start without a place to keep the key.
+///
+/// # Errors
+///
+/// Returns an error if the file exists or cannot be created.
+pub(super) fn create_key_file(path: &str) -> Result<File> {
+    open_private(OpenOptions::new().write(true).create_new(true), path).map_err(|source| {
+        Error::Write {
+            path: path.to_string(),
+            source,
+        }
+    })
+}
+
+/// Opens a file only readable by the current user.
+#[cfg(unix)]
+fn open_private(options: &mut OpenOptions, path: &str) -> std::io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    options.mode(0o600).open(path)
+}
+
+/// Opens a file with the default permissions, on platforms without Unix
+/// modes.
+#[cfg(not(unix))]
+fn open_private(options: &mut OpenOptions, path: &str) -> std::io::Result<File> {
+    options.open(path)
+}
+
+/// Writes a private key to its file as a `0x` prefixed hex string.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub(super) fn write_key(file: &mut File, path: &str, key: &SecretKey) -> Result<()> {
+    writeln!(file, "{}", hex::encode_prefixed(key.to_bytes())).map_err(|source| Error::Write {
+        path: path.to_string(),
+        source,
+    })
+}
+
+#[test]
+fn test_account_address() {
+    use alloy_primitives::address;
+
+    // The well-known account of the private key 1
+    let point = ProjectivePoint::GENERATOR;
+    assert_eq!(
+        account_address(&point),
+        address!("0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf")
+    );
+
+    // Stepping a key steps its public key
+    let two = ProjectivePoint::GENERATOR * Scalar::from(2u64);
+    assert_eq!(
+        account_address(&(point + ProjectivePoint::GENERATOR)),
+        account_address(&two)
+    );
+}
@@ -0,0 +1,849 @@
+//! Piwi mines CREATE2 and CREATE3 salts giving vanity contract addresses,
+//! e.g. Uniswap v4 hook addresses carrying their permission flags.
+//!
+//! The library exposes the miners behind the `piwi` command line tool, so
+//! deployment tooling written in Rust can mine salts in-process:
+//!
+//! ```no_run
+//! # #[cfg(feature = "std")]
+//! # fn main() {
+//! use alloy_primitives::{Address, B256};
+//! use piwi::{Create2Miner, Miner, MiningResult, Pattern};
+//!
+//! let pattern = Pattern::parse("0x0080", "").unwrap();
+//! let miner = Create2Miner::new(Address::ZERO, Address::ZERO, B256::ZERO);
+//! let MiningResult { address, salt, .. } = miner.mine(&pattern).unwrap();
+//! println!("{salt} deploys to {address}");
+//! # }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
+//! ```
+//!
+//! With the default `std` feature disabled, only the [`core`] module is
+//! built, which derives addresses and matches patterns without the standard
+//! library.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+mod affinity;
+#[cfg(feature = "std")]
+mod artifact;
+#[cfg(feature = "std")]
+mod bench;
+#[cfg(feature = "std")]
+mod cancel;
+#[cfg(feature = "std")]
+mod checkpoint;
+#[cfg(feature = "std")]
+mod cli;
+pub mod core;
+#[cfg(feature = "std")]
+mod create;
+#[cfg(feature = "std")]
+mod deploy;
+#[cfg(feature = "std")]
+mod devices;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+mod estimate;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "std")]
+mod gpu;
+#[cfg(feature = "std")]
+mod hash;
+#[cfg(feature = "std")]
+mod init_code;
+#[cfg(feature = "std")]
+mod job;
+#[cfg(feature = "std")]
+mod keypair;
+#[cfg(feature = "std")]
+mod mine;
+#[cfg(feature = "std")]
+mod observe;
+#[cfg(feature = "std")]
+mod output;
+#[cfg(feature = "std")]
+mod pattern;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "std")]
+mod registry;
+#[cfg(feature = "std")]
+mod salt;
+#[cfg(feature = "std")]
+mod sessions;
+#[cfg(feature = "std")]
+mod solidity;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "async")]
+mod task;
+#[cfg(feature = "std")]
+mod tune;
+#[cfg(feature = "std")]
+mod verify;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "std")]
+mod watch;
+#[cfg(feature = "std")]
+mod wizard;
+
+#[cfg(feature = "std")]
+use std::{process::ExitCode, time::Duration};
+
+#[cfg(feature = "std")]
+use alloy_primitives::{Address, B256, U256, address, b256, keccak256};
+#[cfg(feature = "std")]
+use clap::CommandFactory;
+#[cfg(feature = "std")]
+use {
+    cli::{Compute, FactoryPreset, Piwi, Target, Verify},
+    deploy::{Deployment, FactoryAbi},
+    job::JobMiner,
+    mine::{SaltGuard, TokenAccountMiner},
+};
+
+#[cfg(feature = "async")]
+pub use task::{Progress, mine_async};
+#[cfg(feature = "wasm")]
+pub use wasm::{Create2Search, compute_create2_address};
+#[cfg(feature = "std")]
+pub use {
+    cancel::CancellationToken,
+    core::{Matcher, Pattern, PatternError},
+    error::{Error, Result},
+    job::{Derivation, MiningJob},
+    mine::{Create2Miner, Create3Miner, Miner, MiningResult, ProxySalt},
+    observe::Observer,
+};
+
+/// The standard CREATE2 factory address on Ethereum
+/// See: https://github.com/Arachnid/deterministic-deployment-proxy
+#[cfg(feature = "std")]
+const CREATE2_DEFAULT_FACTORY: Address = address!("0x4e59b44847b379578588920cA78FbF26c0B4956C");
+
+/// The CreateX factory address, the same on every chain
+/// See: https://github.com/pcaversaccio/createx
+#[cfg(feature = "std")]
+const CREATEX_FACTORY: Address = address!("0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+
+/// 0age's ImmutableCreate2Factory address on Ethereum
+/// See: https://github.com/0age/metamorphic
+#[cfg(feature = "std")]
+const IMMUTABLE_CREATE2_FACTORY: Address = address!("0x0000000000FFe8B47B3e2130213B802212439497");
+
+/// The Safe Singleton Factory address, common on L2s
+/// See: https://github.com/safe-global/safe-singleton-factory
+#[cfg(feature = "std")]
+const SAFE_SINGLETON_FACTORY: Address = address!("0x914d7Fec6aaC8cd542e72Bca78B30650d45643d7");
+
+/// The ERC-2470 SingletonFactory address, the same on every chain
+/// See: https://eips.ethereum.org/EIPS/eip-2470
+#[cfg(feature = "std")]
+const EIP2470_SINGLETON_FACTORY: Address = address!("0xce0042B868300000d44A59004Da54A005ffdcf9f");
+
+/// The SafeProxyFactory v1.4.1 address
+/// See: https://github.com/safe-global/safe-deployments
+#[cfg(feature = "std")]
+const SAFE_PROXY_FACTORY: Address = address!("0x4e1DCf7AD4e460CfD30791CCC4F9c8a4f820ec67");
+
+/// Seaport's ConduitController address, the same on every chain
+/// See: https://github.com/ProjectOpenSea/seaport
+#[cfg(feature = "std")]
+const SEAPORT_CONDUIT_CONTROLLER: Address = address!("0x00000000F9490004C11Cef243f5400493c00Ad63");
+
+/// Creation code hash of the conduits deployed by Seaport's ConduitController
+/// See: https://github.com/ProjectOpenSea/seaport
+#[cfg(feature = "std")]
+const SEAPORT_CONDUIT_CODE_HASH: B256 =
+    b256!("0x023d904f2503c37127200ca07b976c3a53cc562623f67023115bf311f5805059");
+
+/// The ERC-6551 registry v0.3.1 address, the same on every chain
+/// See: https://eips.ethereum.org/EIPS/eip-6551
+#[cfg(feature = "std")]
+const ERC6551_REGISTRY: Address = address!("0x000000006551c19487814612e58FE06813775758");
+
+/// The standard CREATE3 factory address on Ethereum
+/// See: https://www.npmjs.com/package/@layerzerolabs/create3-factory
+#[cfg(feature = "std")]
+const CREATE3_DEFAULT_FACTORY: Address = address!("0x8Cad6A96B0a287e29bA719257d0eF431Ea6D888B");
+
+/// zeframlou's CREATE3 factory address, the same on every chain
+/// See: https://github.com/zeframlou/create3-factory
+#[cfg(feature = "std")]
+const ZEFRAMLOU_CREATE3_FACTORY: Address = address!("0x9fBB3DF7C40Da2e5A0dE984fFE2CCB7C47cd0ABf");
+
+/// Number of salts tried by Uniswap v4-periphery's `HookMiner.find` before it
+/// gives up.
+#[cfg(feature = "std")]
+const HOOK_MINER_MAX_LOOP: u64 = 160_444;
+
+/// Entry point for the Piwi smart contract address mining tool, parsing the
+/// command line and running the selected subcommand.
+///
+/// This application allows users to "mine" for vanity addresses for smart
+/// contracts by finding salt values that produce desirable contract addresses
+/// when used with either CREATE2 or CREATE3 deployment mechanisms.
+///
+/// # Error
+///
+/// Prints the error and exits with a non-zero status if any input is
+/// malformed or a verification fails.
+#[cfg(feature = "std")]
+pub fn cli_main() -> ExitCode {
+    exit_code(run(Piwi::parse_args()))
+}
+
+/// Prints the error of a run, if any, and returns the matching exit status.
+#[cfg(feature = "std")]
+fn exit_code(result: Result<()>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the subcommand selected on the command line.
+#[cfg(feature = "std")]
+fn run(command: Piwi) -> Result<()> {
+    let (address, salt, pattern) = match command {
+        Piwi::Create2 {
+            deployer,
+            factory,
+            preset,
+            chain,
+            factory_name,
+            also_factory,
+            also_init_code_hash,
+            chain_id,
+            salt_template,
+            create2_salt,
+            target,
+            init_code,
+            flags,
+            prefix,
+            no_deployer_prefix,
+            sequential,
+            watch,
+            salt,
+            mut runtime,
+            gpu,
+            output,
+        } => {
+            // Keep stderr to the results when they are printed as JSON
+            runtime.quiet |= output.json;
+
+            // Use the provided factory or fall back to the one of the preset,
+            // which the named factory or the chain may select
+            let preset = match factory_name {
+                Some(name) => name.preset()?,
+                None => preset
+                    .or_else(|| chain.and_then(registry::default_preset))
+                    .unwrap_or(FactoryPreset::Arachnid),
+            };
+            let factory = factory.unwrap_or(preset.factory());
+            if let Some(chain) = chain {
+                registry::check_deployed(factory, chain);
+            }
+            let salt_guard = match (salt_template, create2_salt) {
+                (Some(template), _) => SaltGuard::Template(template),
+                (None, Some(scheme)) => {
+                    scheme.salt_guard(deployer, chain_id, !no_deployer_prefix)?
+                }
+                (None, None) => preset.salt_guard(chain_id, !no_deployer_prefix, sequential)?,
+            };
+
+            // Use the provided prefix or fall back to an empty string
+            let prefix = prefix.unwrap_or_default();
+            let pattern = Pattern::parse(&flags, &prefix)?;
+
+            // Mine for an address matching the flags using CREATE2 deployment
+            // and print it, or only show what would be mined on dry runs
+            let artifact = init_code.source.artifact.clone();
+            let mine = |cancel| -> Result<()> {
+                // Use the provided init code hash or hash the provided init code,
+                // separately from the constructor arguments on zkSync Era
+                let (init_code_hash, creation_code, constructor_input_hash) = match target {
+                    Target::Evm => {
+                        let (init_code_hash, creation_code) = init_code.clone().resolve()?;
+                        (init_code_hash, creation_code, None)
+                    }
+                    Target::Zksync => {
+                        if output.emits_deployment() {
+                            return Err(Error::ZksyncDeployment);
+                        }
+                        let (bytecode_hash, input_hash) = init_code.clone().resolve_zksync()?;
+                        (bytecode_hash, None, Some(input_hash))
+                    }
+                };
+                output.check_creation_code(creation_code.as_deref())?;
+
+                let mut miner = Create2Miner::new(deployer, factory, init_code_hash)
+                    .with_deployer_prefix(!no_deployer_prefix)
+                    .with_sequential(sequential)
+                    .with_salt_guard(salt_guard.clone())
+                    .with_salt_prefix(salt.salt_prefix.clone().unwrap_or_default())
+                    .with_nonce_range(salt.start_nonce, salt.end_nonce)
+                    .with_exhaustive(salt.exhaustive)
+                    .with_max_cpu(runtime.max_cpu)
+                    .with_worker_nodes(runtime.worker_nodes()?)
+                    .with_gpus(gpu.devices()?)
+                    .with_hybrid(gpu.hybrid)
+                    .with_also_factories(also_factory.clone())
+                    .with_also_init_code_hashes(also_init_code_hash.clone())
+                    .with_cancel(cancel);
+                if let Some(input_hash) = constructor_input_hash {
+                    miner = miner.with_zksync(input_hash);
+                }
+
+                // Show what would be mined without mining it
+                if runtime.dry_run {
+                    output::print_dry_run(&miner.derivation()?, &pattern);
+                    return Ok(());
+                }
+
+                // Save the progress of long runs, continuing the resumed one
+                let checkpoint = runtime.checkpoint(&miner, &pattern)?;
+                let miner = miner.with_checkpoint(checkpoint);
+                let result = runtime.mine(&miner, &pattern)?;
+                let MiningResult { address, salt, .. } = result;
+
+                // Salts past the HookMiner limit cannot be re-derived in Solidity
+                if sequential && U256::from_be_bytes(salt.0) >= U256::from(HOOK_MINER_MAX_LOOP) {
+                    eprintln!(
+                        "warning: HookMiner.find gives up after {HOOK_MINER_MAX_LOOP} salts and will not find this one"
+                    );
+                }
+                output.emit_solidity(address, &salt, artifact.as_deref())?;
+                output.emit_deployment(&Deployment {
+                    abi: preset.abi(),
+                    factory,
+                    salt,
+                    address,
+                    creation_code,
+                })?;
+                if output.json {
+                    output::print_json(&result);
+                    return Ok(());
+                }
+
+                // Output the salt with the address of every factory, and the
+                // candidate init code it matched with if there were several
+                let matched = miner
+                    .matched_init_code_hash(&salt, address)
+                    .unwrap_or(init_code_hash);
+                let others = also_factory
+                    .iter()
+                    .map(|&factory| (factory, miner.derive_address(factory, matched, &salt)))
+                    .collect::<Vec<_>>();
+                let formatted = output::format_salt(&miner, &salt, output.salt_format);
+                if also_init_code_hash.is_empty() {
+                    output::print_result(address, &formatted, &pattern);
+                } else {
+                    output::print_candidate_result(address, &formatted, matched, &pattern);
+                }
+                output::print_other_factories(&others, &pattern);
+                Ok(())
+            };
+
+            // Mine again with the new init code each time the file changes
+            if watch {
+                let path = artifact
+                    .clone()
+                    .or_else(|| init_code.source.init_code_file.clone())
+                    .ok_or(Error::NothingToWatch)?;
+                return watch::watch(&path, mine);
+            }
+            return mine(CancellationToken::new());
+        }
+        Piwi::Create3 {
+            deployer,
+            factory,
+            flags,
+            prefix,
+            creation_code,
+            salt,
+            mut runtime,
+            output,
+        } => {
+            // Keep stderr to the results when they are printed as JSON
+            runtime.quiet |= output.json;
+
+            // Only the emitted deployment files need the creation code
+            let artifact = creation_code.artifact.clone();
+            let creation_code = creation_code.read()?;
+            output.check_creation_code(creation_code.as_deref())?;
+
+            // Use the provided prefix or fall back to an empty string
+            let prefix = prefix.unwrap_or_default();
+
+            // Mine for an address matching the flags using CREATE3 deployment
+            // Use the provided factory or fall back to the one of the variant
+            let miner = factory.miner(deployer)?;
+            let factory = miner.factory();
+            let miner = miner
+                .with_salt_prefix(salt.salt_prefix.unwrap_or_default())
+                .with_nonce_range(salt.start_nonce, salt.end_nonce)
+                .with_exhaustive(salt.exhaustive)
+                .with_max_cpu(runtime.max_cpu)
+                .with_worker_nodes(runtime.worker_nodes()?);
+
+            // Show what would be mined without mining it
+            let pattern = Pattern::parse(&flags, &prefix)?;
+            if runtime.dry_run {
+                output::print_dry_run(&miner.derivation()?, &pattern);
+                return Ok(());
+            }
+
+            // Save the progress of long runs, continuing the resumed one
+            let checkpoint = runtime.checkpoint(&miner, &pattern)?;
+            let miner = miner.with_checkpoint(checkpoint);
+            let result = runtime.mine(&miner, &pattern)?;
+            let MiningResult { address, salt, .. } = result;
+            output.emit_solidity(address, &salt, artifact.as_deref())?;
+            output.emit_deployment(&Deployment {
+                abi: FactoryAbi::Deploy,
+                factory,
+                salt,
+                address,
+                creation_code,
+            })?;
+            if output.json {
+                output::print_json(&result);
+                return Ok(());
+            }
+            (
+                address,
+                output::format_salt(&miner, &salt, output.salt_format),
+                pattern,
+            )
+        }
+        Piwi::SafeProxy {
+            singleton,
+            initializer,
+            flags,
+            prefix,
+            factory,
+            proxy_creation_code,
+            salt,
+            runtime,
+        } => {
+            // Use the provided factory or fall back to the v1.4.1 factory
+            let factory = factory.unwrap_or(SAFE_PROXY_FACTORY);
+
+            // The proxy is deployed with the singleton as constructor argument
+            let init_code_hash = init_code::hash_init_code(
+                proxy_creation_code.into(),
+                singleton.into_word().as_slice(),
+            );
+            let salt_guard = SaltGuard::SafeProxy {
+                initializer_hash: keccak256(&initializer),
+            };
+
+            // Use the provided prefix or fall back to an empty string
+            let prefix = prefix.unwrap_or_default();
+
+            // Mine for a salt nonce, which does not start with the deployer
+            let miner = Create2Miner::new(Address::ZERO, factory, init_code_hash)
+                .with_deployer_prefix(false)
+                .with_salt_guard(salt_guard)
+                .with_salt_prefix(salt.salt_prefix.unwrap_or_default())
+                .with_nonce_range(salt.start_nonce, salt.end_nonce)
+                .with_exhaustive(salt.exhaustive)
+                .with_max_cpu(runtime.max_cpu)
+                .with_worker_nodes(runtime.worker_nodes()?);
+
+            // Show what would be mined without mining it
+            let pattern = Pattern::parse(&flags, &prefix)?;
+            if runtime.dry_run {
+                output::print_dry_run(&miner.derivation()?, &pattern);
+                return Ok(());
+            }
+
+            // Save the progress of long runs, continuing the resumed one
+            let checkpoint = runtime.checkpoint(&miner, &pattern)?;
+            let miner = miner.with_checkpoint(checkpoint);
+            let MiningResult { address, salt, .. } = runtime.mine(&miner, &pattern)?;
+
+            // `createProxyWithNonce` takes the salt nonce as a uint256
+            (address, U256::from_be_bytes(salt.0).to_string(), pattern)
+        }
+        Piwi::Conduit {
+            deployer,
+            flags,
+            prefix,
+            conduit_code_hash,
+            controller,
+            salt,
+            runtime,
+        } => {
+            // Use the provided controller or fall back to Seaport's, along
+            // with the code hash of its conduits
+            let controller = controller.unwrap_or(SEAPORT_CONDUIT_CONTROLLER);
+            let conduit_code_hash = conduit_code_hash.unwrap_or(SEAPORT_CONDUIT_CODE_HASH);
+
+            // Use the provided prefix or fall back to an empty string
+            let prefix = prefix.unwrap_or_default();
+
+            // Mine for a conduit key, which the controller requires to start
+            // with its creator
+            let miner = Create2Miner::new(deployer, controller, conduit_code_hash)
+                .with_salt_prefix(salt.salt_prefix.unwrap_or_default())
+                .with_nonce_range(salt.start_nonce, salt.end_nonce)
+                .with_exhaustive(salt.exhaustive)
+                .with_max_cpu(runtime.max_cpu)
+                .with_worker_nodes(runtime.worker_nodes()?);
+
+            // Show what would be mined without mining it
+            let pattern = Pattern::parse(&flags, &prefix)?;
+            if runtime.dry_run {
+                output::print_dry_run(&miner.derivation()?, &pattern);
+                return Ok(());
+            }
+
+            // Save the progress of long runs, continuing the resumed one
+            let checkpoint = runtime.checkpoint(&miner, &pattern)?;
+            let miner = miner.with_checkpoint(checkpoint);
+            let MiningResult { address, salt, .. } = runtime.mine(&miner, &pattern)?;
+            (address, salt.to_string(), pattern)
+        }
+        Piwi::TokenAccount {
+            implementation,
+            token_contract,
+            token_id,
+            flags,
+            prefix,
+            chain_id,
+            registry,
+            salt,
+            runtime,
+        } => {
+            // Use the provided registry or fall back to the canonical one
+            let registry = registry.unwrap_or(ERC6551_REGISTRY);
+
+            // Use the provided prefix or fall back to an empty string
+            let prefix = prefix.unwrap_or_default();
+
+            // Mine for a salt, which does not start with the deployer as
+            // anybody may create the account
+            let miner = TokenAccountMiner::new(
+                registry,
+                implementation,
+                chain_id,
+                token_contract,
+                token_id,
+            )
+            .with_salt_prefix(salt.salt_prefix.unwrap_or_default())
+            .with_nonce_range(salt.start_nonce, salt.end_nonce)
+            .with_exhaustive(salt.exhaustive)
+            .with_max_cpu(runtime.max_cpu)
+            .with_worker_nodes(runtime.worker_nodes()?);
+
+            // Show what would be mined without mining it
+            let pattern = Pattern::parse(&flags, &prefix)?;
+            if runtime.dry_run {
+                output::print_dry_run(&miner.derivation()?, &pattern);
+                return Ok(());
+            }
+
+            // Save the progress of long runs, continuing the resumed one
+            let checkpoint = runtime.checkpoint(&miner, &pattern)?;
+            let miner = miner.with_checkpoint(checkpoint);
+            let MiningResult { address, salt, .. } = runtime.mine(&miner, &pattern)?;
+            (address, salt.to_string(), pattern)
+        }
+        Piwi::ProxyPair {
+            deployer,
+            implementation_artifact,
+            implementation_args,
+            proxy_artifact,
+            proxy_data,
+            flags,
+            prefix,
+            implementation_flags,
+            implementation_prefix,
+            factory,
+            salt,
+            runtime,
+        } => {
+            // Each stage would overwrite the checkpoint of the other
+            if runtime.checkpoint.is_some() || runtime.resume.is_some() {
+                return Err(Error::UnsupportedInPair("--checkpoint or --resume"));
+            }
+            let factory = factory.unwrap_or(CREATE2_DEFAULT_FACTORY);
+
+            // The implementation follows the proxy pattern unless given its own
+            let prefix = prefix.unwrap_or_default();
+            let implementation_flags = implementation_flags.unwrap_or_else(|| flags.clone());
+            let implementation_prefix = implementation_prefix.unwrap_or_else(|| prefix.clone());
+            let pattern = Pattern::parse(&flags, &prefix)?;
+            let implementation_pattern =
+                Pattern::parse(&implementation_flags, &implementation_prefix)?;
+
+            // Both stages search the same salt space of the deployer
+            let worker_nodes = runtime.worker_nodes()?;
+            let miner = |init_code_hash| {
+                Create2Miner::new(deployer, factory, init_code_hash)
+                    .with_salt_prefix(salt.salt_prefix.clone().unwrap_or_default())
+                    .with_nonce_range(salt.start_nonce, salt.end_nonce)
+                    .with_exhaustive(salt.exhaustive)
+                    .with_max_cpu(runtime.max_cpu)
+                    .with_worker_nodes(worker_nodes.clone())
+            };
+
+            // Mine the implementation first, as its address is part of the
+            // init code of the proxy
+            let implementation_code = [
+                artifact::read_artifact(&implementation_artifact)?,
+                init_code::encode_constructor_args(&implementation_args)?,
+            ]
+            .concat();
+            init_code::check_init_code(&implementation_code)?;
+            let implementation_miner = miner(keccak256(&implementation_code));
+            let proxy_artifact = artifact::read_artifact(&proxy_artifact)?;
+            let proxy_data = proxy_data.unwrap_or_default();
+            let proxy_code_hash = |implementation| -> Result<B256> {
+                let proxy_args = init_code::encode_proxy_args(implementation, &proxy_data);
+                let proxy_code = [proxy_artifact.as_slice(), &proxy_args].concat();
+                init_code::check_init_code(&proxy_code)?;
+                Ok(keccak256(&proxy_code))
+            };
+
+            // Derive the proxy with a placeholder implementation, which is
+            // only known once mined
+            if runtime.dry_run {
+                let placeholder = Address::ZERO;
+                output::print_pair_dry_run(
+                    (&implementation_miner.derivation()?, &implementation_pattern),
+                    (
+                        &miner(proxy_code_hash(placeholder)?).derivation()?,
+                        &pattern,
+                    ),
+                    placeholder,
+                );
+                return Ok(());
+            }
+            let MiningResult {
+                address: implementation,
+                salt: implementation_salt,
+                ..
+            } = runtime.mine(&implementation_miner, &implementation_pattern)?;
+
+            // Then mine the proxy delegating to the mined implementation
+            let MiningResult {
+                address: proxy,
+                salt: proxy_salt,
+                ..
+            } = runtime.mine(&miner(proxy_code_hash(implementation)?), &pattern)?;
+
+            output::print_pair_result(
+                (
+                    implementation,
+                    &implementation_salt.to_string(),
+                    &implementation_pattern,
+                ),
+                (proxy, &proxy_salt.to_string(), &pattern),
+            );
+            return Ok(());
+        }
+        Piwi::Create {
+            deployer,
+            flags,
+            prefix,
+            start_nonce,
+            end_nonce,
+        } => {
+            // Find the first nonce of the range deploying to a matching address
+            let prefix = prefix.unwrap_or_default();
+            let pattern = Pattern::parse(&flags, &prefix)?;
+            let nonces = start_nonce..end_nonce.unwrap_or(u64::MAX);
+            let (nonce, address) = create::find_nonce(deployer, nonces.clone(), &pattern).ok_or(
+                Error::NonceRangeExhausted {
+                    start: nonces.start,
+                    end: nonces.end,
+                },
+            )?;
+            output::print_nonce_result(address, nonce, &pattern);
+            return Ok(());
+        }
+        Piwi::Keypair {
+            flags,
+            prefix,
+            nonce,
+            key_file,
+        } => {
+            // Only mine once there is a place to keep the key
+            let prefix = prefix.unwrap_or_default();
+            let pattern = Pattern::parse(&flags, &prefix)?;
+            let mut file = keypair::create_key_file(&key_file)?;
+            let (key, deployer, address) = keypair::find_keypair(nonce, &pattern);
+            keypair::write_key(&mut file, &key_file, &key)?;
+            output::print_keypair_result(deployer, nonce, address, &pattern);
+            eprintln!("Private key written to {key_file}");
+            return Ok(());
+        }
+        Piwi::Job { path, mut runtime } => {
+            // Mine the job like the equivalent command, printing the result
+            // in the same schema as the job, as JSON
+            runtime.quiet = true;
+            let job = job::read_job(&path)?;
+            let result = match job.miner() {
+                JobMiner::Create2(miner) => {
+                    let miner = miner
+                        .with_max_cpu(runtime.max_cpu)
+                        .with_worker_nodes(runtime.worker_nodes()?);
+                    if runtime.dry_run {
+                        output::print_dry_run(&miner.derivation()?, &job.pattern);
+                        return Ok(());
+                    }
+                    let checkpoint = runtime.checkpoint(&miner, &job.pattern)?;
+                    runtime.mine(&miner.with_checkpoint(checkpoint), &job.pattern)?
+                }
+                JobMiner::Create3(miner) => {
+                    let miner = miner
+                        .with_max_cpu(runtime.max_cpu)
+                        .with_worker_nodes(runtime.worker_nodes()?);
+                    if runtime.dry_run {
+                        output::print_dry_run(&miner.derivation()?, &job.pattern);
+                        return Ok(());
+                    }
+                    let checkpoint = runtime.checkpoint(&miner, &job.pattern)?;
+                    runtime.mine(&miner.with_checkpoint(checkpoint), &job.pattern)?
+                }
+            };
+            output::print_json(&result);
+            return Ok(());
+        }
+        Piwi::Init => {
+            // Print the command assembled from the answers of the wizard
+            return wizard::wizard();
+        }
+        Piwi::Compute { method } => {
+            let address = match method {
+                Compute::Create2 {
+                    factory,
+                    init_code_hash,
+                    salt,
+                    target,
+                } => {
+                    let factory = factory.unwrap_or(CREATE2_DEFAULT_FACTORY);
+                    target.miner(factory, init_code_hash).compute_address(&salt)
+                }
+                Compute::Create3 {
+                    deployer,
+                    factory,
+                    salt,
+                } => factory.miner(deployer)?.compute_address(&salt),
+            };
+
+            // Output the checksummed address derived from the salt
+            println!("{address}");
+            return Ok(());
+        }
+        Piwi::Verify { method } => {
+            let (derived, proxy, expected) = match method {
+                Verify::Create2 {
+                    factory,
+                    init_code_hash,
+                    salt,
+                    target,
+                    expected,
+                } => {
+                    let factory = factory.unwrap_or(CREATE2_DEFAULT_FACTORY);
+                    let miner = target.miner(factory, init_code_hash);
+                    (miner.compute_address(&salt), None, expected)
+                }
+                Verify::Create3 {
+                    deployer,
+                    factory,
+                    salt,
+                    expected,
+                } => {
+                    // Also report the intermediate proxy for CREATE3
+                    let miner = factory.miner(deployer)?;
+                    let proxy = miner.compute_proxy_address(&salt);
+                    (miner.compute_address(&salt), Some(proxy), expected)
+                }
+            };
+
+            // Fail with a non-zero status if any check failed
+            let valid = verify::verify(
+                derived,
+                proxy,
+                expected.address,
+                expected.flags.as_deref(),
+                expected.prefix.as_deref(),
+            )?;
+            return if valid {
+                Ok(())
+            } else {
+                Err(Error::VerificationFailed)
+            };
+        }
+        Piwi::HashInitCode {
+            file,
+            constructor_args,
+        } => {
+            // Hash the bytecode with the constructor arguments appended
+            let init_code = init_code::read_init_code(&file)?;
+            let constructor_args = init_code::encode_constructor_args(&constructor_args)?;
+            println!(
+                "{}",
+                init_code::hash_init_code(init_code, &constructor_args)
+            );
+            return Ok(());
+        }
+        Piwi::Estimate { flags, prefix } => {
+            // Report the difficulty and expected mining time of the pattern
+            return estimate::estimate(flags.as_deref(), &prefix.unwrap_or_default());
+        }
+        Piwi::Bench {
+            threads,
+            duration,
+            hashers,
+        } => {
+            // Print the hashrates of the Keccak implementations, or the
+            // hashrate table for increasing thread counts
+            if hashers {
+                bench::bench_hashers(Duration::from_millis(duration));
+                return Ok(());
+            }
+            return bench::bench(threads, Duration::from_millis(duration));
+        }
+        Piwi::Devices { duration } => {
+            // Describe and probe every device Piwi can mine on
+            return devices::devices(Duration::from_millis(duration));
+        }
+        Piwi::FactoryTx => {
+            // Guide the deployment of Arachnid's proxy on a chain missing it
+            deploy::factory_tx();
+            return Ok(());
+        }
+        Piwi::Factories => {
+            // List the factories that can be selected by name
+            registry::factories();
+            return Ok(());
+        }
+        Piwi::Sessions { command } => {
+            // List the recorded runs or show one of them
+            return sessions::sessions(command);
+        }
+        Piwi::Completions { shell } => {
+            // Write the completion script for the requested shell to stdout
+            clap_complete::generate(shell, &mut Piwi::command(), "piwi", &mut std::io::stdout());
+            return Ok(());
+        }
+    };
+
+    // Output the discovered salt and resulting contract address
+    output::print_result(address, &salt, &pattern);
+    Ok(())
+}
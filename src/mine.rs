@@ -1,73 +1,830 @@
-use alloy_primitives::{Address, FixedBytes, address, hex::FromHex, keccak256};
-use rand::{Rng, rng};
-use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use std::{
+    collections::HashSet,
+    iter,
+    num::NonZeroU8,
+    ops::Range,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
 
-/// Maximum value for the nonce segment of the salt (6 bytes).
-const MAX_NONCE: u64 = u64::MAX >> 16;
+use alloy_primitives::{Address, B256, Bytes, FixedBytes, U256, hex, keccak256};
+use rand::{Rng, SeedableRng, rng, rngs::StdRng};
+use rayon::ThreadPoolBuilder;
+use serde::{Deserialize, Serialize};
 
-/// Bitmask that isolates the lower 14 bits of an Ethereum address.
-const FLAGS_MASK: Address = address!("0x0000000000000000000000000000000000003fFF");
+use crate::{
+    affinity,
+    cancel::CancellationToken,
+    checkpoint::{BestCandidate, Checkpoint, CheckpointState},
+    cli::{Hasher, Runtime},
+    core,
+    error::{Error, Result},
+    gpu,
+    hash::{self, Create1Preimage, Create2Preimage, LANES, Midstate},
+    observe::Observer,
+    pattern::Pattern,
+    salt::{SaltLayout, SaltTemplate},
+    sessions::Session,
+    stats::{self, LocalCount, Stats},
+    tune::Tuner,
+};
 
-/// Converts a hex string to an Ethereum address.
-///
-/// # Arguments
-/// * `hex` - The hex string to convert.
-/// * `pad_leading_zeros` - If true, pads the hex string with leading zeros to
-///   ensure it's 40 characters long, else pads with trailing zeros.
-fn hex_to_address(hex: &String, pad_leading_zeros: bool) -> Address {
-    // Pad the hex string with zeros to ensure it's 40 characters
-    let padded_hex = if pad_leading_zeros {
-        format!("{:0>40}", hex)
-    } else {
-        format!("{:0<40}", hex)
-    };
-
-    // Convert the padded hex string to address
-    Address::from_hex(&padded_hex).expect("Could not convert hex string to address")
-}
-
-/// Computes a bitmask that isolates the upper `prefix_len` bits of an address.
-fn compute_prefix_mask(prefix_len: usize) -> Address {
-    let mask_number = if prefix_len % 2 == 0 {
-        (1u64 << (prefix_len << 2)) - 1
-    } else {
-        (1u64 << ((prefix_len + 1) << 2)) - (15u64 << ((prefix_len - 1) << 2)) - 1
-    };
-    let mut mask_address = Address::default();
-    mask_address[0..8].copy_from_slice(&mask_number.to_le_bytes());
-    mask_address
-}
-
-/// Checks if a candidate address matches the specified flags and prefix.
+impl Runtime {
+    /// Runs a mining operation on a thread pool sized by the runtime options,
+    /// with the selected hasher and the workers pinned to CPU cores if
+    /// requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hasher is unavailable, the thread pool cannot
+    /// be created or the CPU cores cannot be listed.
+    pub(super) fn install<T: Send>(&self, op: impl FnOnce() -> T + Send) -> Result<T> {
+        // Log the hasher picked for the CPU, unless it was forced
+        let backend = hash::select(self.hasher)?;
+        if self.stats || (self.hasher == Hasher::Auto && !self.quiet) {
+            eprintln!("Hashing with {backend}");
+        }
+
+        // A zero thread count lets rayon pick the number of available cores
+        let mut builder = ThreadPoolBuilder::new().num_threads(self.threads.unwrap_or_default());
+
+        // Pin the workers to the cores in turn, one worker per core by default
+        if let Some(cpus) = self.pinned_cpus()? {
+            let cpus: Vec<usize> = cpus.into_iter().map(|(cpu, _)| cpu).collect();
+            builder = builder
+                .num_threads(self.threads.unwrap_or(cpus.len()))
+                .start_handler(move |index| {
+                    if let Err(err) = affinity::pin(cpus[index % cpus.len()]) {
+                        eprintln!("warning: could not pin worker {index}: {err}");
+                    }
+                });
+        }
+        Ok(builder.build()?.install(op))
+    }
+
+    /// Returns the CPUs the workers are pinned to in turn, with the NUMA
+    /// group of each, or nothing if the workers are not pinned. With NUMA
+    /// groups, the CPUs of a node follow each other.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the CPU cores cannot be listed.
+    fn pinned_cpus(&self) -> Result<Option<Vec<(usize, usize)>>> {
+        if !self.pin_cores {
+            return Ok(None);
+        }
+        let cpus = affinity::cpus(self.skip_smt)?;
+        let mut cpus: Vec<_> = if self.numa {
+            let nodes = affinity::nodes(&cpus);
+            cpus.into_iter().zip(nodes).collect()
+        } else {
+            cpus.into_iter().map(|cpu| (cpu, 0)).collect()
+        };
+        cpus.sort_by_key(|&(_, node)| node);
+        Ok(Some(cpus))
+    }
+
+    /// Returns the NUMA group of each worker, or nothing to search chunks as
+    /// a whole.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the CPU cores cannot be listed.
+    pub(super) fn worker_nodes(&self) -> Result<Vec<usize>> {
+        let Some(cpus) = self.pinned_cpus()?.filter(|_| self.numa) else {
+            return Ok(Vec::new());
+        };
+        let threads = self.threads.unwrap_or(cpus.len());
+        Ok((0..threads)
+            .map(|index| cpus[index % cpus.len()].1)
+            .collect())
+    }
+
+    /// Mines a salt on the runtime thread pool, optionally reporting the
+    /// progress, and records the run in the sessions history, unless disabled.
+    ///
+    /// Mining stops with [`Error::Cancelled`] on Ctrl-C or once the timeout,
+    /// if any, elapses, instead of the process being killed.
+    ///
+    /// The salt is checked against the full derivation of the miner before
+    /// it is returned, so that a bug in a fast hashing path or a GPU kernel
+    /// cannot hand out a salt deploying to another address.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thread pool cannot be created, mining fails or
+    /// the salt fails the check. Failing to record the session only prints a
+    /// warning.
+    pub(super) fn mine<M: Miner + Sync>(
+        &self,
+        miner: &M,
+        pattern: &Pattern,
+    ) -> Result<MiningResult> {
+        miner
+            .cancellation()
+            .cancel_on_interrupt(self.timeout.map(Duration::from_secs));
+        let started = SystemTime::now();
+        let result = if self.stats {
+            // Report the progress from another thread until mining is over
+            let (interval, done) = (
+                Duration::from_secs(self.stats_interval),
+                AtomicBool::new(false),
+            );
+            thread::scope(|scope| {
+                scope.spawn(|| stats::report(miner, interval, self.stats_per_thread, &done));
+                let result = self.install(|| miner.mine(pattern));
+                done.store(true, Ordering::Relaxed);
+                result
+            })
+        } else {
+            self.install(|| miner.mine(pattern))
+        }??;
+        let MiningResult { address, salt, .. } = result;
+
+        // Derive the address again without the fast paths of the search
+        let derived = miner.compute_addresses(&salt);
+        let reason = if !derived.contains(&address) {
+            let derived = derived.iter().map(Address::to_string).collect::<Vec<_>>();
+            Some(format!(
+                "it derives {}, not {address}",
+                derived.join(" or ")
+            ))
+        } else if !pattern.matcher().matches(&address) {
+            Some(format!("{address} does not match the flags and prefix"))
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            return Err(Error::UnverifiedSalt { salt, reason });
+        }
+
+        if !self.no_history {
+            let session = Session {
+                miner,
+                pattern,
+                started,
+                result,
+            };
+            if let Err(err) = session.record() {
+                eprintln!("warning: could not record the session: {err}");
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Salt buffer found by a search, with what it took to find it.
+struct Found<const N: usize> {
+    /// The matching address.
+    address: Address,
+    /// The salt buffer deriving the address.
+    salt: [u8; N],
+    /// The number of salts tried by the miner.
+    attempts: u64,
+    /// The time the search took.
+    elapsed: Duration,
+    /// The seed of the generator drawing the random segments.
+    seed: u64,
+}
+
+impl<const N: usize> Found<N> {
+    /// Builds the result of the search, with the salt passed to the factory.
+    fn result(&self, salt: FixedBytes<32>) -> MiningResult {
+        MiningResult {
+            address: self.address,
+            salt,
+            attempts: self.attempts,
+            elapsed: self.elapsed,
+            seed: self.seed,
+        }
+    }
+}
+
+/// Exploration of the salts of a layout, shared by every chunk of a search.
+struct Search<'a, const N: usize, F> {
+    /// The layout of the explored salt buffers.
+    layout: &'a SaltLayout<N>,
+    /// Whether the smallest matching nonce has to be returned.
+    ordered: bool,
+    /// Whether the random segments are explored in order rather than drawn.
+    exhaustive: bool,
+    /// The counters of salts tried by each worker.
+    stats: &'a Stats,
+    /// The token stopping the search.
+    cancel: &'a CancellationToken,
+    /// The observer notified after each chunk, if any.
+    observer: Option<&'a dyn Observer>,
+    /// The percentage of the time each worker spends hashing.
+    max_cpu: u8,
+    /// The NUMA group of each worker, empty to search chunks as a whole.
+    nodes: &'a [usize],
+    /// The GPU kernels searching the chunks instead of the CPU workers, if
+    /// any.
+    gpus: &'a [Mutex<Box<dyn gpu::Kernel<N>>>],
+    /// Whether the CPU workers keep searching alongside the GPU kernels.
+    hybrid: bool,
+    /// Creates, for the salt buffers sharing the given random segment, the
+    /// function each worker uses to derive the addresses produced by a batch
+    /// of salt buffers, returning the index and address of the first match.
+    /// Workers keep their own function so that it can reuse buffers and
+    /// hashing work between batches.
+    derive: F,
+}
+
+impl<const N: usize, F, D> Search<'_, N, F>
+where
+    F: Fn(&[u8; N]) -> D + Sync,
+    D: FnMut(&[[u8; N]; LANES]) -> Option<(usize, Address)>,
+{
+    /// Explores the salts until one produces a matching address.
+    ///
+    /// The random segment of each batch is drawn from a seeded generator and
+    /// the nonces are searched in chunks spread over every thread of the
+    /// pool, after each of which the progress is saved to the checkpoint, if
+    /// any. The first chunks calibrate the work sizes to the machine. A
+    /// resumed run continues from the saved random segment and nonce, drawing
+    /// the next segments from a new generator, and a resumed run that was
+    /// over returns its salt right away.
+    ///
+    /// # Arguments
+    /// * `nonces` - The nonces to explore for each random segment.
+    /// * `checkpoint` - Where to save the progress of the run, if anywhere.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checkpoint cannot be written, if the search
+    /// was cancelled, or if every salt was tried without finding a match.
+    fn run(&self, nonces: Range<u64>, checkpoint: Option<&Checkpoint>) -> Result<Found<N>> {
+        let layout = self.layout;
+        let search_started = Instant::now();
+        let mut salt_base = layout.base;
+        let resumed = checkpoint.and_then(|checkpoint| {
+            checkpoint
+                .resumed
+                .clone()
+                .map(|resumed| (checkpoint, resumed))
+        });
+        let (mut state, mut rng) = match resumed {
+            // Return the salt of a run that was over
+            Some((
+                checkpoint,
+                CheckpointState {
+                    seed,
+                    attempts,
+                    best: Some(best),
+                    ..
+                },
+            )) => {
+                let salt = best
+                    .salt
+                    .as_ref()
+                    .try_into()
+                    .map_err(|_| checkpoint.invalid("the salt does not fit the salt layout"))?;
+                return Ok(Found {
+                    address: best.address,
+                    salt,
+                    attempts,
+                    elapsed: search_started.elapsed(),
+                    seed,
+                });
+            }
+            // Continue with the saved random segment, and a new generator
+            // for the next ones
+            Some((checkpoint, mut state)) => {
+                if state.segment.len() != layout.random.len() {
+                    return Err(checkpoint.invalid("the segment does not fit the salt layout"));
+                }
+                salt_base[layout.random.clone()].copy_from_slice(&state.segment);
+                state.seed = rng().random();
+                let rng = StdRng::seed_from_u64(state.seed);
+                (state, rng)
+            }
+            None => {
+                let seed = rng().random();
+                let mut rng = StdRng::seed_from_u64(seed);
+                if !self.next_segment(&mut salt_base, &mut rng, 0) {
+                    return Err(Error::SaltSpaceExhausted);
+                }
+                let state = CheckpointState {
+                    seed,
+                    batch: 0,
+                    segment: Bytes::copy_from_slice(&salt_base[layout.random.clone()]),
+                    nonce: nonces.start,
+                    attempts: 0,
+                    best: None,
+                };
+                (state, rng)
+            }
+        };
+
+        let mut tuner = Tuner::new(rayon::current_num_threads());
+        let mut saved_at = Instant::now();
+        loop {
+            while state.nonce < nonces.end {
+                let sizes = tuner.sizes();
+                let chunk = state.nonce..nonces.end.min(state.nonce.saturating_add(sizes.chunk));
+                let started = Instant::now();
+
+                // If we found a match, save it so that resuming returns it,
+                // then return it and exit
+                if let Some((address, salt)) =
+                    self.search_chunk(&salt_base, chunk.clone(), sizes.unit)?
+                {
+                    if let Some(checkpoint) = checkpoint {
+                        state.best = Some(BestCandidate {
+                            address,
+                            salt: Bytes::copy_from_slice(&salt),
+                        });
+                        checkpoint.save(&state)?;
+                    }
+                    return Ok(Found {
+                        address,
+                        salt,
+                        attempts: self.stats.total(),
+                        elapsed: search_started.elapsed(),
+                        seed: state.seed,
+                    });
+                }
+
+                // A cancelled chunk may be partly unexplored, so it is not saved
+                if self.cancel.is_cancelled() {
+                    return Err(Error::Cancelled {
+                        attempts: self.stats.total(),
+                    });
+                }
+
+                tuner.record(sizes, chunk.end - chunk.start, started.elapsed());
+                if let Some(observer) = self.observer {
+                    let hashrate =
+                        (chunk.end - chunk.start) as f64 / started.elapsed().as_secs_f64();
+                    observer.on_batch_complete();
+                    observer.on_progress(self.stats.total(), hashrate);
+                }
+                state.attempts += chunk.end - chunk.start;
+                state.nonce = chunk.end;
+                if let Some(checkpoint) = checkpoint
+                    && saved_at.elapsed() >= checkpoint.interval
+                {
+                    checkpoint.save(&state)?;
+                    saved_at = Instant::now();
+                }
+            }
+
+            // Otherwise, the segment has no match, so report it and try the
+            // next one, unless there are none left
+            self.stats.segment_exhausted();
+            if let Some(observer) = self.observer {
+                observer.on_segment_exhausted(&salt_base[layout.random.clone()]);
+            }
+            state.batch += 1;
+            if !self.next_segment(&mut salt_base, &mut rng, state.batch) {
+                return Err(Error::SaltSpaceExhausted);
+            }
+            state.segment = Bytes::copy_from_slice(&salt_base[layout.random.clone()]);
+            state.nonce = nonces.start;
+        }
+    }
+
+    /// Sets the random segment of a batch, returning whether there is one
+    /// left to explore.
+    ///
+    /// Exhaustive searches write the batch index as a counter, so they run
+    /// out once it overflows the segment. Other searches draw the next
+    /// segment from the generator, and only run out without a segment.
+    ///
+    /// # Arguments
+    /// * `salt_base` - The salt buffer whose random segment is set.
+    /// * `rng` - The generator of the random segments.
+    /// * `batch` - The index of the batch.
+    fn next_segment(&self, salt_base: &mut [u8; N], rng: &mut StdRng, batch: u64) -> bool {
+        let random = self.layout.random.clone();
+        if self.exhaustive {
+            return self.layout.write_random_index(salt_base, batch);
+        }
+        if random.is_empty() {
+            return batch == 0;
+        }
+        rng.fill(&mut salt_base[random]);
+        true
+    }
+
+    /// Searches a chunk of nonces on every thread of the current pool, or on
+    /// the GPUs if there are any, or on both in hybrid searches.
+    ///
+    /// The chunk is split into one slice per group of workers sharing a NUMA
+    /// node, or a single slice without NUMA groups. Workers claim units of
+    /// consecutive nonces from the slice of their group, then from the other
+    /// slices, until the chunk is exhausted, the search is cancelled or a
+    /// match is found. Throttled workers sleep after each unit. Each GPU is
+    /// driven by its own thread, claiming a whole dispatch at once, so in
+    /// hybrid searches the CPU workers and the GPUs explore disjoint units
+    /// and share the counters and the best match.
+    /// When the smallest matching nonce is required, workers keep exploring
+    /// the units before the best match found so far and skip the ones after
+    /// it.
+    ///
+    /// # Arguments
+    /// * `salt_base` - The salt buffer with the random segment of the batch.
+    /// * `chunk` - The nonces to explore.
+    /// * `unit_size` - The number of consecutive nonces a worker claims at
+    ///   once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a GPU kernel fails.
+    fn search_chunk(
+        &self,
+        salt_base: &[u8; N],
+        chunk: Range<u64>,
+        unit_size: u64,
+    ) -> Result<Option<(Address, [u8; N])>> {
+        // Give each group an equal share of the units of the chunk
+        let groups = self.nodes.iter().max().map_or(1, |last| last + 1) as u64;
+        let units = (chunk.end - chunk.start).div_ceil(unit_size);
+        let bound = |group| {
+            chunk
+                .end
+                .min(chunk.start + units * group / groups * unit_size)
+        };
+        let slices: Vec<SliceCursor> = (0..groups)
+            .map(|group| SliceCursor {
+                next: AtomicU64::new(bound(group)),
+                end: bound(group + 1),
+            })
+            .collect();
+        let best_nonce = AtomicU64::new(u64::MAX);
+        let best: Mutex<Option<(u64, Address, [u8; N])>> = Mutex::new(None);
+
+        // Keep the match with the smallest nonce
+        let record = |nonce, address, salt| {
+            best_nonce.fetch_min(nonce, Ordering::Relaxed);
+            let mut best = best.lock().expect("workers do not panic");
+            if best.is_none_or(|(best_nonce, ..)| nonce < best_nonce) {
+                *best = Some((nonce, address, salt));
+            }
+        };
+
+        let search_gpu = |kernel: &Mutex<Box<dyn gpu::Kernel<N>>>| -> Result<()> {
+            let mut kernel = kernel.lock().expect("workers do not panic");
+            let mut count = self.stats.local();
+            let mut derive = (self.derive)(salt_base);
+            let mut salts = [*salt_base; LANES];
+            let mut position = 0;
+            // The batch size changes while the kernel is tuned
+            while let Some(unit) =
+                self.claim(&slices, 0, &mut position, kernel.batch_size(), &best_nonce)
+            {
+                count.add(unit.end - unit.start);
+                let Some(nonce) = kernel.search(salt_base, unit)? else {
+                    continue;
+                };
+
+                // The kernel only reports the nonce, so derive the address of
+                // the match again on the host
+                for salt in &mut salts {
+                    self.layout.write_nonce(salt, nonce);
+                }
+                let Some((_, address)) = derive(&salts) else {
+                    return Err(Error::Gpu(format!(
+                        "the kernel reported nonce {nonce}, which does not match"
+                    )));
+                };
+                record(nonce, address, salts[0]);
+            }
+            Ok(())
+        };
+
+        let search_cpu = |context: rayon::BroadcastContext<'_>| {
+            let mut count = self.stats.local();
+            let mut derive = (self.derive)(salt_base);
+
+            // Fill the salt buffers of the worker once, only their nonce
+            // segments change between batches
+            let mut salts = [*salt_base; LANES];
+
+            // Drain the slice of the own group before helping the other ones
+            let own = self.nodes.get(context.index()).copied().unwrap_or_default();
+            let mut position = 0;
+            while let Some(unit) = self.claim(&slices, own, &mut position, unit_size, &best_nonce) {
+                let started = Instant::now();
+                if let Some((nonce, address, salt)) =
+                    self.search_unit(&mut salts, unit, &best_nonce, &mut derive, &mut count)
+                {
+                    record(nonce, address, salt);
+                }
+
+                // Rest in proportion to the time spent hashing the unit to
+                // keep the worker busy only the requested share of the time
+                if self.max_cpu < 100 {
+                    let idle = 100 - u32::from(self.max_cpu);
+                    thread::sleep(started.elapsed() * idle / u32::from(self.max_cpu));
+                }
+            }
+        };
+
+        if self.gpus.is_empty() {
+            rayon::broadcast(search_cpu);
+        } else {
+            thread::scope(|scope| {
+                let threads: Vec<_> = self
+                    .gpus
+                    .iter()
+                    .map(|kernel| scope.spawn(|| search_gpu(kernel)))
+                    .collect();
+                if self.hybrid {
+                    rayon::broadcast(search_cpu);
+                }
+                threads
+                    .into_iter()
+                    .try_for_each(|thread| thread.join().expect("workers do not panic"))
+            })?;
+        }
+
+        let best = best.into_inner().expect("workers do not panic");
+        Ok(best.map(|(_, address, salt)| (address, salt)))
+    }
+
+    /// Claims the next unit of nonces for a worker, from the slice of its
+    /// group first and then from the other slices in turn, returning nothing
+    /// once the chunk is exhausted, the search is cancelled or no unit left
+    /// can improve on a match.
+    ///
+    /// # Arguments
+    /// * `slices` - The slices of the chunk.
+    /// * `own` - The index of the slice of the group of the worker.
+    /// * `position` - The number of slices the worker already drained.
+    /// * `unit_size` - The number of consecutive nonces to claim.
+    /// * `best_nonce` - The smallest matching nonce found by any worker.
+    fn claim(
+        &self,
+        slices: &[SliceCursor],
+        own: usize,
+        position: &mut usize,
+        unit_size: u64,
+        best_nonce: &AtomicU64,
+    ) -> Option<Range<u64>> {
+        while *position < slices.len() {
+            // Claim the next unit, unless it cannot improve on a match
+            let slice = &slices[(own + *position) % slices.len()];
+            let start = slice.next.fetch_add(unit_size, Ordering::Relaxed);
+            let best_nonce = best_nonce.load(Ordering::Relaxed);
+            if self.cancel.is_cancelled() || best_nonce != u64::MAX && !self.ordered {
+                return None;
+            }
+            if start >= slice.end || start > best_nonce {
+                *position += 1;
+                continue;
+            }
+            return Some(start..slice.end.min(start + unit_size));
+        }
+        None
+    }
+
+    /// Searches a unit of consecutive nonces in batches, returning the first
+    /// match with its nonce, as the rest of the unit only has larger ones.
+    ///
+    /// The search stops early, within a batch, once the search is cancelled
+    /// or the rest of the unit cannot improve on a match of another worker.
+    ///
+    /// # Arguments
+    /// * `salts` - The salt buffers of the worker, whose nonce segments are
+    ///   overwritten.
+    /// * `unit` - The nonces to explore.
+    /// * `best_nonce` - The smallest matching nonce found by any worker.
+    /// * `derive` - The address derivation of the worker.
+    /// * `count` - The counter of the salts tried by the worker.
+    fn search_unit(
+        &self,
+        salts: &mut [[u8; N]; LANES],
+        unit: Range<u64>,
+        best_nonce: &AtomicU64,
+        derive: &mut D,
+        count: &mut LocalCount<'_>,
+    ) -> Option<(u64, Address, [u8; N])> {
+        for first in unit.clone().step_by(LANES) {
+            let best_nonce = best_nonce.load(Ordering::Relaxed);
+            if self.cancel.is_cancelled()
+                || best_nonce != u64::MAX && (!self.ordered || first > best_nonce)
+            {
+                return None;
+            }
+            let last = unit.end.min(first + LANES as u64) - 1;
+
+            // Set the nonce segments, repeating the last nonce of the unit to
+            // fill the final batch
+            for (salt, nonce) in salts.iter_mut().zip(first..) {
+                self.layout.write_nonce(salt, nonce.min(last));
+            }
+            count.add(last - first + 1);
+
+            if let Some((index, address)) = derive(salts) {
+                return Some((first + index as u64, address, salts[index]));
+            }
+        }
+        None
+    }
+}
+
+/// Cursor over the slice of a chunk a group of workers searches first,
+/// padded to its own cache line.
+#[repr(align(128))]
+struct SliceCursor {
+    /// Start of the next unit to claim.
+    next: AtomicU64,
+    /// End of the slice.
+    end: u64,
+}
+
+/// Salt found by a miner, with the address it deploys the contract to and
+/// what it took to find it.
 ///
-/// # Arguments
-/// * `flags` - The flags to match.
-/// * `prefix` - The prefix to match.
-/// * `prefix_mask` - The bitmask for the prefix.
-/// * `candidate` - The candidate address to check.
-fn check_candidate(
-    flags: &Address,
-    prefix: &Address,
-    prefix_mask: &Address,
-    candidate: &Address,
-) -> bool {
-    (candidate.bit_and(FLAGS_MASK) == *flags) && (candidate.bit_and(*prefix_mask) == *prefix)
+/// Serialized as the JSON object printed by `--json` and `piwi job`, with the
+/// elapsed time in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MiningResult {
+    /// Address the contract is deployed at with the salt.
+    pub address: Address,
+    /// Salt passed to the factory contract.
+    pub salt: FixedBytes<32>,
+    /// Number of salts tried by the miner, across its searches.
+    pub attempts: u64,
+    /// Time the search took.
+    #[serde(with = "seconds")]
+    pub elapsed: Duration,
+    /// Seed of the generator drawing the random segments of the salts, which
+    /// replays the search along with the derivation inputs.
+    pub seed: u64,
+}
+
+/// Serializes durations as fractional seconds.
+mod seconds {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        duration: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error> {
+        let seconds = f64::deserialize(deserializer)?;
+        Duration::try_from_secs_f64(seconds).map_err(serde::de::Error::custom)
+    }
 }
 
 /// Defines the interface for address mining algorithms.
 ///
 /// Implementations must be thread-safe to enable parallel mining.
-pub(super) trait Miner {
+pub trait Miner {
     /// Searches for a salt value that, when used for deployment, produces a
-    /// contract address matching the specified pattern in its lower bits.
+    /// contract address matching the pattern.
+    ///
+    /// The pattern is validated when it is built, e.g. by [`Pattern::parse`],
+    /// so malformed flags or prefixes are rejected before mining.
     ///
     /// The mining process:
-    /// 1. Create a salt with the deployer address
+    /// 1. Create a salt with the deployer address and any pinned bytes
     /// 2. Fill the middle section with random bytes
     /// 3. Systematically try different nonce values in the final section
     /// 4. Use parallel processing to speed up the search
     /// 5. Return the first matching address and its corresponding salt
-    fn mine(&self, flags: &String, prefix: &String) -> (Address, FixedBytes<32>);
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pinned salt bytes leave nothing to mine, if the
+    /// search was cancelled, or if every salt was tried without finding a
+    /// match.
+    fn mine(&self, pattern: &Pattern) -> Result<MiningResult>;
+
+    /// Mines salts matching a pattern one after the other, so that callers
+    /// can take several of them, filter them or stop on their own criteria.
+    ///
+    /// Each candidate comes from a new search over freshly drawn random salt
+    /// segments. Exhaustive searches start over from the first segment each
+    /// time, so the candidates end once a salt is found again, and they also
+    /// end after the first error.
+    fn candidates(&self, pattern: &Pattern) -> impl Iterator<Item = Result<MiningResult>> + '_
+    where
+        Self: Sized,
+    {
+        let pattern = *pattern;
+        let mut seen = HashSet::new();
+        let mut done = false;
+        iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            match self.mine(&pattern) {
+                Ok(result) if seen.insert(result.salt) => Some(Ok(result)),
+                Ok(_) => {
+                    done = true;
+                    None
+                }
+                Err(err) => {
+                    done = true;
+                    Some(Err(err))
+                }
+            }
+        })
+    }
+
+    /// Mines the candidates of a pattern on a background thread, streaming
+    /// them as they are found while the searches keep using every worker.
+    ///
+    /// One candidate is kept ready ahead of the receiver. Dropping the
+    /// receiver stops the stream once the search under way ends.
+    fn stream(self, pattern: &Pattern) -> Receiver<Result<MiningResult>>
+    where
+        Self: Sized + Send + 'static,
+    {
+        let pattern = *pattern;
+        let (sender, receiver) = mpsc::sync_channel(1);
+        thread::spawn(move || {
+            for candidate in self.candidates(&pattern) {
+                if sender.send(candidate).is_err() {
+                    break;
+                }
+            }
+        });
+        receiver
+    }
+
+    /// Computes the contract address produced by deploying with the given
+    /// salt, as it is passed to the factory contract.
+    fn compute_address(&self, salt: &FixedBytes<32>) -> Address;
+
+    /// Computes every contract address a mined salt may have matched with,
+    /// for miners accepting several candidate contracts.
+    fn compute_addresses(&self, salt: &FixedBytes<32>) -> Vec<Address> {
+        vec![self.compute_address(salt)]
+    }
+
+    /// Returns the internal buffer the factory builds from the given salt,
+    /// or the salt itself if the factory uses it as is.
+    fn internal_salt(&self, salt: &FixedBytes<32>) -> Vec<u8> {
+        salt.to_vec()
+    }
+
+    /// Returns the salt seen by the CREATE2 opcode for the given salt.
+    fn raw_salt(&self, salt: &FixedBytes<32>) -> FixedBytes<32> {
+        *salt
+    }
+
+    /// Returns the inputs of the address derivation as labeled values, as
+    /// they would be used while mining.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pinned salt bytes leave nothing to mine or the
+    /// nonce range is invalid.
+    fn derivation(&self) -> Result<Vec<(&'static str, String)>>;
+
+    /// Returns the number of salts tried by the miner so far.
+    fn attempts(&self) -> u64;
+
+    /// Returns the number of salts tried by each worker thread so far.
+    fn worker_attempts(&self) -> Vec<u64>;
+
+    /// Returns the number of random salt segments searched without a match
+    /// so far.
+    fn exhausted_segments(&self) -> u64;
+
+    /// Returns the token stopping the searches of the miner and its clones.
+    fn cancellation(&self) -> &CancellationToken;
+}
+
+/// Ways a factory turns the salt it is given into the CREATE2 salt.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) enum SaltGuard {
+    /// The salt is used as is.
+    #[default]
+    None,
+    /// CreateX hashes the salt with the caller, and with the chain id if
+    /// byte 20 of the salt is `0x01`, so that only the caller can deploy
+    /// with it, on that chain only if given.
+    CreateX {
+        /// The chain the salt is restricted to, if any.
+        chain_id: Option<u64>,
+    },
+    /// The SafeProxyFactory hashes the salt nonce with the hash of the
+    /// initializer, tying the address to the owners set up by it.
+    SafeProxy {
+        /// Hash of the `setup` calldata initializing the Safe.
+        initializer_hash: B256,
+    },
+    /// The factory hashes the salt between fixed bytes, e.g. the owner of an
+    /// ERC-4337 account.
+    Template(SaltTemplate),
 }
 
 /// Implementation for mining vanity addresses using the CREATE2 deployment
@@ -86,14 +843,60 @@ pub(super) trait Miner {
 /// - Bytes 0-19: Deployer address (prevents frontrunning by other users)
 /// - Bytes 20-25: Random values (prevents collisions between mining sessions)
 /// - Bytes 26-31: Nonce values (systematically explored during mining)
-#[derive(Debug, Clone, Copy)]
-pub(super) struct Create2Miner {
+///
+/// Pinned salt bytes are placed right after the deployer address, shrinking
+/// the random segment first and then the nonce segment. For factories that do
+/// not enforce sender-bound salts, the deployer address can be left out, in
+/// which case the whole salt is free to mine.
+///
+/// In sequential mode, the salt is instead a plain `uint256` counter starting
+/// at zero and the first matching salt is returned, which reproduces
+/// `HookMiner.find` from Uniswap v4-periphery byte for byte.
+#[derive(Debug, Clone)]
+pub struct Create2Miner {
     /// Address of the account that will call the factory
     deployer: Address,
     /// Address of the factory contract that will perform the CREATE2 deployment
     factory: Address,
     /// Keccak256 hash of the contract's initialization bytecode
     init_code_hash: FixedBytes<32>,
+    /// Hashes of other init code any of which may match instead
+    also_init_code_hashes: Vec<B256>,
+    /// Bytes pinned right after the deployer address in the salt
+    salt_prefix: Bytes,
+    /// First nonce explored for each random segment
+    start_nonce: u64,
+    /// Nonce the exploration stops before, defaulting to the whole segment
+    end_nonce: Option<u64>,
+    /// Whether the salt starts with the deployer address
+    deployer_prefix: bool,
+    /// Whether salts are explored as a counter in increasing order
+    sequential: bool,
+    /// Where the mining progress is saved, if anywhere
+    checkpoint: Option<Checkpoint>,
+    /// Number of salts tried by each worker, shared between clones
+    stats: Arc<Stats>,
+    /// Token stopping the search, shared between clones
+    cancel: CancellationToken,
+    /// Receiver of the progress and candidates of the search, if any
+    observer: Option<Arc<dyn Observer>>,
+    /// Percentage of the time each worker spends hashing
+    max_cpu: u8,
+    /// NUMA group of each worker, empty to search chunks as a whole
+    worker_nodes: Vec<usize>,
+    /// Whether the random segments are explored in order
+    exhaustive: bool,
+    /// GPUs searching instead of the CPU workers, if any
+    gpus: Vec<gpu::Device>,
+    /// Whether the CPU workers keep mining alongside the GPUs
+    hybrid: bool,
+    /// How the factory turns the salt into the CREATE2 salt
+    salt_guard: SaltGuard,
+    /// Hash of the constructor arguments on zkSync Era, whose CREATE2
+    /// formula is followed if set
+    constructor_input_hash: Option<B256>,
+    /// Other factories whose addresses must match the pattern too
+    also_factories: Vec<Address>,
 }
 
 impl Create2Miner {
@@ -104,56 +907,517 @@ impl Create2Miner {
     /// * `factory` - The address of the CREATE2 factory contract
     /// * `init_code_hash` - The keccak256 hash of the contract initialization
     ///   code
-    pub(super) fn new(deployer: Address, factory: Address, init_code_hash: FixedBytes<32>) -> Self {
+    pub fn new(deployer: Address, factory: Address, init_code_hash: FixedBytes<32>) -> Self {
         Self {
             deployer,
             factory,
             init_code_hash,
+            also_init_code_hashes: Vec::new(),
+            salt_prefix: Bytes::new(),
+            start_nonce: 0,
+            end_nonce: None,
+            deployer_prefix: true,
+            sequential: false,
+            checkpoint: None,
+            stats: Arc::default(),
+            cancel: CancellationToken::default(),
+            observer: None,
+            max_cpu: 100,
+            worker_nodes: Vec::new(),
+            exhaustive: false,
+            gpus: Vec::new(),
+            hybrid: false,
+            salt_guard: SaltGuard::None,
+            constructor_input_hash: None,
+            also_factories: Vec::new(),
+        }
+    }
+
+    /// Sets whether salts are explored as a `uint256` counter starting at
+    /// zero, returning the smallest matching salt like `HookMiner.find`.
+    ///
+    /// This overrides the deployer prefix and the pinned salt bytes.
+    pub fn with_sequential(mut self, sequential: bool) -> Self {
+        self.sequential = sequential;
+        self
+    }
+
+    /// Sets whether the salt starts with the deployer address, which protects
+    /// the salt from being used by anyone else on factories enforcing it.
+    pub fn with_deployer_prefix(mut self, deployer_prefix: bool) -> Self {
+        self.deployer_prefix = deployer_prefix;
+        self
+    }
+
+    /// Pins the given bytes right after the deployer address in the salt.
+    pub fn with_salt_prefix(mut self, salt_prefix: Bytes) -> Self {
+        self.salt_prefix = salt_prefix;
+        self
+    }
+
+    /// Restricts the nonces explored for each random segment to
+    /// `start_nonce..end_nonce`, e.g. to split work across machines.
+    pub fn with_nonce_range(mut self, start_nonce: u64, end_nonce: Option<u64>) -> Self {
+        self.start_nonce = start_nonce;
+        self.end_nonce = end_nonce;
+        self
+    }
+
+    /// Sets whether the random segments are explored in order, as a counter,
+    /// instead of being drawn at random, so that every salt is tried at most
+    /// once and mining fails once all of them were.
+    pub fn with_exhaustive(mut self, exhaustive: bool) -> Self {
+        self.exhaustive = exhaustive;
+        self
+    }
+
+    /// Saves the mining progress to the given checkpoint, resuming from the
+    /// state it was loaded with.
+    pub(super) fn with_checkpoint(mut self, checkpoint: Option<Checkpoint>) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    /// Stops mining with [`Error::Cancelled`] once the given token is
+    /// cancelled.
+    pub fn with_cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Notifies the given observer of the progress of the search and of the
+    /// salts it finds.
+    pub fn with_observer(mut self, observer: Arc<dyn Observer>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Limits the share of the time each worker spends hashing to the given
+    /// percentage, sleeping the rest of the time. Percentages above 100 are
+    /// taken as 100.
+    pub fn with_max_cpu(mut self, max_cpu: NonZeroU8) -> Self {
+        self.max_cpu = max_cpu.get().min(100);
+        self
+    }
+
+    /// Splits each chunk between the NUMA groups of the workers, given by
+    /// worker index, so that workers mostly search nonces near each other.
+    pub fn with_worker_nodes(mut self, worker_nodes: Vec<usize>) -> Self {
+        self.worker_nodes = worker_nodes;
+        self
+    }
+
+    /// Mines on the given GPUs instead of the CPU workers.
+    pub(super) fn with_gpus(mut self, gpus: Vec<gpu::Device>) -> Self {
+        self.gpus = gpus;
+        self
+    }
+
+    /// Sets how the factory turns the salt into the CREATE2 salt.
+    ///
+    /// CreateX salts start with the deployer address followed by the flag
+    /// byte of the guard, before the pinned bytes.
+    pub(super) fn with_salt_guard(mut self, salt_guard: SaltGuard) -> Self {
+        self.salt_guard = salt_guard;
+        self
+    }
+
+    /// Sets whether the CPU workers keep mining alongside the GPUs, instead
+    /// of leaving the whole search to them.
+    pub(super) fn with_hybrid(mut self, hybrid: bool) -> Self {
+        self.hybrid = hybrid;
+        self
+    }
+
+    /// Follows the CREATE2 formula of zkSync Era, taking the init code hash
+    /// as the zkSync Era bytecode hash.
+    ///
+    /// # Arguments
+    /// * `constructor_input_hash` - The keccak256 hash of the constructor
+    ///   arguments
+    pub fn with_zksync(mut self, constructor_input_hash: B256) -> Self {
+        self.constructor_input_hash = Some(constructor_input_hash);
+        self
+    }
+
+    /// Sets other factories whose addresses must match the pattern along with
+    /// the one of the main factory, for salts usable with all of them.
+    pub fn with_also_factories(mut self, also_factories: Vec<Address>) -> Self {
+        self.also_factories = also_factories;
+        self
+    }
+
+    /// Sets the hashes of other init code, e.g. of the contract compiled
+    /// with other settings, any of which may give the matching address
+    /// instead of the main one.
+    pub fn with_also_init_code_hashes(mut self, also_init_code_hashes: Vec<B256>) -> Self {
+        self.also_init_code_hashes = also_init_code_hashes;
+        self
+    }
+
+    /// Returns the main init code hash followed by the other candidates.
+    fn init_code_hashes(&self) -> impl Iterator<Item = B256> + '_ {
+        std::iter::once(self.init_code_hash).chain(self.also_init_code_hashes.iter().copied())
+    }
+
+    /// Returns the candidate init code hash deploying to the address with a
+    /// salt, if any.
+    pub(super) fn matched_init_code_hash(
+        &self,
+        salt: &FixedBytes<32>,
+        address: Address,
+    ) -> Option<B256> {
+        self.init_code_hashes().find(|&init_code_hash| {
+            self.derive_address(self.factory, init_code_hash, salt) == address
+        })
+    }
+
+    /// Computes the address a factory deploys the init code with the given
+    /// hash to with a salt, following the salt rules and chain of the miner.
+    pub(super) fn derive_address(
+        &self,
+        factory: Address,
+        init_code_hash: B256,
+        salt: &FixedBytes<32>,
+    ) -> Address {
+        let raw_salt = self.raw_salt(salt);
+        match self.constructor_input_hash {
+            Some(input_hash) => hash::zksync_create2(factory, raw_salt, init_code_hash, input_hash),
+            None => core::create2_address(factory, raw_salt, init_code_hash),
+        }
+    }
+
+    /// Absorbs the fixed bytes of the preimage of the addresses the init code
+    /// with the given hash is deployed to with CREATE2 salts sharing all but
+    /// the `varying` bytes of `salt`.
+    fn preimage(
+        &self,
+        init_code_hash: B256,
+        salt: &[u8; 32],
+        varying: Range<usize>,
+    ) -> Create2Preimage {
+        match self.constructor_input_hash {
+            Some(input_hash) => {
+                Create2Preimage::zksync(self.factory, salt, init_code_hash, input_hash, varying)
+            }
+            None => Create2Preimage::new(self.factory, salt, init_code_hash, varying),
+        }
+    }
+
+    /// Builds the layout of the salts explored while mining.
+    fn salt_layout(&self) -> Result<SaltLayout<32>> {
+        // A sequential counter only varies the trailing nonce bytes
+        if self.sequential {
+            return SaltLayout::new(&[0; 26]);
+        }
+
+        // CreateX reads whether the salt is restricted to a chain from the
+        // byte following the deployer address
+        let guard: &[u8] = match self.salt_guard {
+            SaltGuard::None | SaltGuard::SafeProxy { .. } | SaltGuard::Template(_) => &[],
+            SaltGuard::CreateX { chain_id } => &[u8::from(chain_id.is_some())],
+        };
+
+        // Start the salt with the deployer address, unless disabled, and the
+        // pinned bytes
+        let deployer: &[u8] = if self.deployer_prefix {
+            self.deployer.as_slice()
+        } else {
+            &[]
+        };
+        SaltLayout::new(&[deployer, guard, &self.salt_prefix].concat())
+    }
+
+    /// Returns the message the factory hashes the salt in to build the
+    /// CREATE2 salt, if it does not use the salt as is.
+    fn guard_template(&self) -> Option<SaltTemplate> {
+        match &self.salt_guard {
+            SaltGuard::None => None,
+            // `abi.encode(msg.sender, block.chainid, salt)` or
+            // `abi.encode(msg.sender, salt)`
+            SaltGuard::CreateX { chain_id } => {
+                let mut before = self.deployer.into_word().to_vec();
+                if let Some(chain_id) = chain_id {
+                    before.extend(U256::from(*chain_id).to_be_bytes::<32>());
+                }
+                Some(SaltTemplate {
+                    before: before.into(),
+                    after: Bytes::new(),
+                })
+            }
+            // `abi.encodePacked(keccak256(initializer), saltNonce)`
+            SaltGuard::SafeProxy { initializer_hash } => Some(SaltTemplate {
+                before: initializer_hash.to_vec().into(),
+                after: Bytes::new(),
+            }),
+            SaltGuard::Template(template) => Some(template.clone()),
         }
     }
 }
 
 impl Miner for Create2Miner {
-    fn mine(&self, flags: &String, prefix: &String) -> (Address, FixedBytes<32>) {
-        // Convert the flags and prefix from hex strings to addresses
-        let prefix_mask = compute_prefix_mask(prefix.len());
-        let flags = hex_to_address(flags, true);
-        let prefix = hex_to_address(prefix, false);
+    fn mine(&self, pattern: &Pattern) -> Result<MiningResult> {
+        // Compile the pattern to the words checked against candidates
+        let matcher = pattern.matcher();
 
-        // Create a random number generator
-        let mut rng = rng();
+        let layout = self.salt_layout()?;
+        let nonces = layout.nonce_range(self.start_nonce, self.end_nonce)?;
 
-        // Fill the first 20 bytes with the deployer address
-        let mut salt_base = [0u8; 32];
-        salt_base[0..20].copy_from_slice(self.deployer.as_slice());
+        // The GPU kernel only derives EVM addresses of salts used as is
+        let guard_template = self.guard_template();
+        if guard_template.is_some() && !self.gpus.is_empty() {
+            return Err(Error::Gpu(
+                "salts guarded by the factory cannot be mined on GPUs yet".to_string(),
+            ));
+        }
+        if self.constructor_input_hash.is_some() && !self.gpus.is_empty() {
+            return Err(Error::Gpu(
+                "zkSync Era addresses cannot be mined on GPUs yet".to_string(),
+            ));
+        }
+        let several = !self.also_factories.is_empty() || !self.also_init_code_hashes.is_empty();
+        if several && !self.gpus.is_empty() {
+            return Err(Error::Gpu(
+                "salts for several factories or init codes cannot be mined on GPUs yet".to_string(),
+            ));
+        }
 
-        loop {
-            // Fill the random segment (bytes 20-25) with new random values
-            // for each batch of nonce attempts
-            rng.fill(salt_base[20..26].as_mut());
+        // Build the search kernel on each GPU
+        let gpus = self
+            .gpus
+            .iter()
+            .map(|device| {
+                let kernel = gpu::Create2Kernel::new(
+                    device,
+                    self.factory,
+                    self.init_code_hash,
+                    layout.nonce.clone(),
+                    pattern,
+                )?;
+                Ok(Mutex::new(Box::new(kernel) as Box<dyn gpu::Kernel<32>>))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-            // Parallelize the search across different nonce values
-            let mining_result = (0..MAX_NONCE).into_par_iter().find_map_any(move |nonce| {
-                let mut salt = salt_base;
+        // Only the sequential mode needs the smallest matching nonce
+        let search = Search {
+            layout: &layout,
+            ordered: self.sequential,
+            exhaustive: self.exhaustive,
+            stats: &self.stats,
+            cancel: &self.cancel,
+            observer: self.observer.as_deref(),
+            max_cpu: self.max_cpu,
+            nodes: &self.worker_nodes,
+            gpus: &gpus,
+            hybrid: self.hybrid,
+            derive: |salt_base: &[u8; 32]| {
+                // Absorb the factory, the fixed salt bytes and each candidate
+                // init code hash once per worker, leaving only the nonces to
+                // hash
+                let nonce = layout.nonce.clone();
+                let preimages = self
+                    .init_code_hashes()
+                    .map(|init_code_hash| {
+                        (
+                            init_code_hash,
+                            self.preimage(init_code_hash, salt_base, nonce.clone()),
+                        )
+                    })
+                    .collect::<Vec<_>>();
 
-                // Set the nonce segment (bytes 26-31) with the current nonce value
-                salt[26..32].copy_from_slice(&nonce.to_be_bytes()[2..]);
+                // Guarded salts are hashed in their template first, whose
+                // hash is then the whole varying CREATE2 salt
+                let guard = guard_template.as_ref().map(|template| {
+                    let message = template.fill(salt_base);
+                    let shifted =
+                        nonce.start + template.before.len()..nonce.end + template.before.len();
+                    let guarded = self
+                        .init_code_hashes()
+                        .map(|init_code_hash| {
+                            (
+                                init_code_hash,
+                                self.preimage(init_code_hash, &[0; 32], 0..32),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    let midstate =
+                        Midstate::fits(&shifted).then(|| Midstate::new(&message, shifted.clone()));
+                    (midstate, message, shifted, guarded)
+                });
+                move |salts: &[[u8; 32]; LANES]| {
+                    // Calculate the CREATE2 salts, and the preimages deriving
+                    // addresses from them
+                    let (raw_salts, preimages) = match &guard {
+                        Some((midstate, message, shifted, guarded)) => {
+                            let nonces = salts.each_ref().map(|salt| &salt[nonce.clone()]);
+                            let hashes = match midstate {
+                                Some(midstate) => midstate.hash_batch(nonces),
+                                // Nonces crossing a Keccak block are hashed
+                                // one message at a time
+                                None => nonces.map(|nonce| {
+                                    let mut message = message.clone();
+                                    message[shifted.clone()].copy_from_slice(nonce);
+                                    keccak256(message)
+                                }),
+                            };
+                            (hashes.map(|hash| hash.0), guarded)
+                        }
+                        None => (*salts, &preimages),
+                    };
 
-                // Calculate the resulting contract address
-                let candidate = self.factory.create2(salt, self.init_code_hash);
+                    // Return the candidate of the first lane matching the flags
+                    // and prefix for any init code, so that ordered searches
+                    // get the smallest salt, checking the other factories only
+                    // for the rare matches
+                    preimages
+                        .iter()
+                        .filter_map(|(init_code_hash, preimage)| {
+                            preimage.addresses(&raw_salts).into_iter().enumerate().find(
+                                |(lane, candidate)| {
+                                    matcher.matches(candidate)
+                                        && self.also_factories.iter().all(|&factory| {
+                                            let salt = FixedBytes::from(salts[*lane]);
+                                            let address = self.derive_address(
+                                                factory,
+                                                *init_code_hash,
+                                                &salt,
+                                            );
+                                            matcher.matches(&address)
+                                        })
+                                },
+                            )
+                        })
+                        .min_by_key(|(lane, _)| *lane)
+                }
+            },
+        };
+        let found = search.run(nonces, self.checkpoint.as_ref())?;
+        let result = found.result(FixedBytes::from(found.salt));
+        if let Some(observer) = &self.observer {
+            observer.on_candidate(&result);
+        }
+        Ok(result)
+    }
 
-                // Return the candidate if it matches the flags and prefix
-                check_candidate(&flags, &prefix, &prefix_mask, &candidate)
-                    .then(|| (candidate, FixedBytes::from_slice(&salt)))
-            });
+    fn compute_address(&self, salt: &FixedBytes<32>) -> Address {
+        self.derive_address(self.factory, self.init_code_hash, salt)
+    }
 
-            // If we found a match, return it and exit
-            if let Some(answer) = mining_result {
-                break answer;
-            }
-            // Otherwise, try with a new set of random bytes
+    fn compute_addresses(&self, salt: &FixedBytes<32>) -> Vec<Address> {
+        self.init_code_hashes()
+            .map(|init_code_hash| self.derive_address(self.factory, init_code_hash, salt))
+            .collect()
+    }
+
+    fn internal_salt(&self, salt: &FixedBytes<32>) -> Vec<u8> {
+        self.guard_template()
+            .unwrap_or_default()
+            .fill(salt.as_slice())
+    }
+
+    fn raw_salt(&self, salt: &FixedBytes<32>) -> FixedBytes<32> {
+        match self.guard_template() {
+            Some(template) => keccak256(template.fill(salt.as_slice())),
+            None => *salt,
         }
     }
+
+    fn attempts(&self) -> u64 {
+        self.stats.total()
+    }
+
+    fn worker_attempts(&self) -> Vec<u64> {
+        self.stats.per_worker()
+    }
+
+    fn exhausted_segments(&self) -> u64 {
+        self.stats.exhausted_segments()
+    }
+
+    fn cancellation(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    fn derivation(&self) -> Result<Vec<(&'static str, String)>> {
+        let layout = self.salt_layout()?;
+        let nonces = layout.nonce_range(self.start_nonce, self.end_nonce)?;
+        let deployer = if self.deployer_prefix && !self.sequential {
+            self.deployer.to_string()
+        } else {
+            "not embedded in the salt".to_string()
+        };
+        let search = if self.sequential {
+            "sequential, smallest salt first"
+        } else if self.exhaustive {
+            "exhaustive, random segments in order"
+        } else {
+            "random"
+        };
+        let salt_guard = match &self.salt_guard {
+            SaltGuard::None => "none, the salt is used as is".to_string(),
+            SaltGuard::CreateX { chain_id: None } => "CreateX, deployer only".to_string(),
+            SaltGuard::CreateX {
+                chain_id: Some(chain_id),
+            } => format!("CreateX, deployer on chain {chain_id} only"),
+            SaltGuard::SafeProxy { initializer_hash } => {
+                format!("SafeProxyFactory, initializer hash {initializer_hash}")
+            }
+            SaltGuard::Template(template) => format!(
+                "hashed as {}{{salt}}{}",
+                template.before,
+                hex::encode(&template.after)
+            ),
+        };
+        let target = match self.constructor_input_hash {
+            Some(input_hash) => format!("zkSync Era, constructor input hash {input_hash}"),
+            None => "EVM".to_string(),
+        };
+        let also_factories = if self.also_factories.is_empty() {
+            "none".to_string()
+        } else {
+            self.also_factories
+                .iter()
+                .map(Address::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        Ok(vec![
+            ("Method", "CREATE2".to_string()),
+            ("Target", target),
+            ("Factory", self.factory.to_string()),
+            ("Also factories", also_factories),
+            ("Deployer", deployer),
+            ("Salt guard", salt_guard),
+            (
+                "Init code hash",
+                self.init_code_hashes()
+                    .map(|init_code_hash| init_code_hash.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" or "),
+            ),
+            ("Salt layout", layout.describe()),
+            ("Nonce range", format!("{nonces:?}")),
+            ("Search", search.to_string()),
+        ])
+    }
+}
+
+/// Ways a CREATE3 factory derives the proxy salt from the salt it is given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProxySalt {
+    /// The salt is used as is.
+    Raw,
+    /// The salt is hashed after the caller.
+    Sender,
+    /// The salt is hashed after the caller padded to 32 bytes.
+    SenderAbi,
+    /// The salt is hashed after the caller and the given chain id.
+    SenderChain(u64),
+    /// The salt is hashed after the given chain id.
+    Chain(u64),
+    /// The salt is used as is, and starts with the caller.
+    Caller,
 }
 
 /// Implementation for mining vanity addresses using the CREATE3 deployment
@@ -170,12 +1434,41 @@ impl Miner for Create2Miner {
 /// - Bytes 0-19: Deployer address (prevents frontrunning by other users)
 /// - Bytes 20-45: Random values (prevents collisions between mining sessions)
 /// - Bytes 46-51: Nonce values (systematically explored during mining)
-#[derive(Debug, Clone, Copy)]
-pub(super) struct Create3Miner {
+///
+/// Pinned salt bytes are placed right after the deployer address, shrinking
+/// the random segment first and then the nonce segment.
+#[derive(Debug, Clone)]
+pub struct Create3Miner {
     /// Address of the account that will call the factory
     deployer: Address,
     /// Address of the factory contract that will perform the deployment
     factory: Address,
+    /// Bytes pinned right after the deployer address in the salt
+    salt_prefix: Bytes,
+    /// First nonce explored for each random segment
+    start_nonce: u64,
+    /// Nonce the exploration stops before, defaulting to the whole segment
+    end_nonce: Option<u64>,
+    /// Where the mining progress is saved, if anywhere
+    checkpoint: Option<Checkpoint>,
+    /// Number of salts tried by each worker, shared between clones
+    stats: Arc<Stats>,
+    /// Token stopping the search, shared between clones
+    cancel: CancellationToken,
+    /// Receiver of the progress and candidates of the search, if any
+    observer: Option<Arc<dyn Observer>>,
+    /// Percentage of the time each worker spends hashing
+    max_cpu: u8,
+    /// NUMA group of each worker, empty to search chunks as a whole
+    worker_nodes: Vec<usize>,
+    /// Whether the random segments are explored in order
+    exhaustive: bool,
+    /// How the factory derives the proxy salt
+    proxy_salt: ProxySalt,
+    /// Hash of the init code of the proxy
+    proxy_init_code_hash: B256,
+    /// Nonce the proxy deploys the contract at
+    proxy_nonce: u64,
 }
 
 impl Create3Miner {
@@ -189,73 +1482,594 @@ impl Create3Miner {
 
     /// Creates a new CREATE3 miner with the specified parameters.
     pub fn new(deployer: Address, factory: Address) -> Self {
-        Self { deployer, factory }
+        Self {
+            deployer,
+            factory,
+            salt_prefix: Bytes::new(),
+            start_nonce: 0,
+            end_nonce: None,
+            checkpoint: None,
+            stats: Arc::default(),
+            cancel: CancellationToken::default(),
+            observer: None,
+            max_cpu: 100,
+            worker_nodes: Vec::new(),
+            exhaustive: false,
+            proxy_salt: ProxySalt::Sender,
+            proxy_init_code_hash: Self::PROXY_INIT_CODE_HASH.into(),
+            proxy_nonce: 1,
+        }
+    }
+
+    /// Returns the address of the factory contract.
+    pub fn factory(&self) -> Address {
+        self.factory
+    }
+
+    /// Sets the hash of the init code of the proxy deployed with CREATE2,
+    /// for factories not using the usual 16-byte proxy.
+    pub fn with_proxy_init_code_hash(mut self, proxy_init_code_hash: B256) -> Self {
+        self.proxy_init_code_hash = proxy_init_code_hash;
+        self
+    }
+
+    /// Sets how the factory derives the proxy salt from the salt it is
+    /// given.
+    pub fn with_proxy_salt(mut self, proxy_salt: ProxySalt) -> Self {
+        self.proxy_salt = proxy_salt;
+        self
+    }
+
+    /// Sets the nonce the proxy deploys the contract at with CREATE, for
+    /// proxies that deploy other contracts first.
+    pub fn with_proxy_nonce(mut self, proxy_nonce: u64) -> Self {
+        self.proxy_nonce = proxy_nonce;
+        self
+    }
+
+    /// Pins the given bytes right after the deployer address in the salt.
+    pub fn with_salt_prefix(mut self, salt_prefix: Bytes) -> Self {
+        self.salt_prefix = salt_prefix;
+        self
+    }
+
+    /// Restricts the nonces explored for each random segment to
+    /// `start_nonce..end_nonce`, e.g. to split work across machines.
+    pub fn with_nonce_range(mut self, start_nonce: u64, end_nonce: Option<u64>) -> Self {
+        self.start_nonce = start_nonce;
+        self.end_nonce = end_nonce;
+        self
+    }
+
+    /// Sets whether the random segments are explored in order, as a counter,
+    /// instead of being drawn at random, so that every salt is tried at most
+    /// once and mining fails once all of them were.
+    pub fn with_exhaustive(mut self, exhaustive: bool) -> Self {
+        self.exhaustive = exhaustive;
+        self
+    }
+
+    /// Saves the mining progress to the given checkpoint, resuming from the
+    /// state it was loaded with.
+    pub(super) fn with_checkpoint(mut self, checkpoint: Option<Checkpoint>) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    /// Stops mining with [`Error::Cancelled`] once the given token is
+    /// cancelled.
+    pub fn with_cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Notifies the given observer of the progress of the search and of the
+    /// salts it finds.
+    pub fn with_observer(mut self, observer: Arc<dyn Observer>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Limits the share of the time each worker spends hashing to the given
+    /// percentage, sleeping the rest of the time. Percentages above 100 are
+    /// taken as 100.
+    pub fn with_max_cpu(mut self, max_cpu: NonZeroU8) -> Self {
+        self.max_cpu = max_cpu.get().min(100);
+        self
+    }
+
+    /// Splits each chunk between the NUMA groups of the workers, given by
+    /// worker index, so that workers mostly search nonces near each other.
+    pub fn with_worker_nodes(mut self, worker_nodes: Vec<usize>) -> Self {
+        self.worker_nodes = worker_nodes;
+        self
+    }
+
+    /// Derives the CREATE3 address for the given internal salt buffer.
+    #[inline]
+    fn derive_address(&self, salt: &[u8; 52]) -> Address {
+        core::create3_address(
+            self.factory,
+            self.proxy_salt(salt),
+            self.proxy_init_code_hash,
+            self.proxy_nonce,
+        )
     }
 
-    /// Computes the contract address that would result from deploying with the given salt.
+    /// Derives the address of the intermediate proxy for the given proxy
+    /// salt.
     #[inline]
-    fn compute_create3_address(&self, salt: &[u8; 52]) -> Address {
-        use std::sync::atomic::{AtomicU64, Ordering};
+    fn derive_proxy_address(&self, proxy_salt: B256) -> Address {
+        core::create2_address(self.factory, proxy_salt, self.proxy_init_code_hash)
+    }
 
-        static ITERATION: AtomicU64 = AtomicU64::new(0);
+    /// Returns the bytes the factory hashes before the salt to build the
+    /// proxy salt, or nothing if it uses the salt as is.
+    fn salt_namespace(&self) -> Option<Vec<u8>> {
+        let deployer = self.deployer.as_slice();
+        match self.proxy_salt {
+            ProxySalt::Raw | ProxySalt::Caller => None,
+            // `abi.encodePacked(msg.sender, salt)`
+            ProxySalt::Sender => Some(deployer.to_vec()),
+            // `abi.encode(msg.sender, salt)`
+            ProxySalt::SenderAbi => Some(self.deployer.into_word().to_vec()),
+            // `abi.encodePacked(msg.sender, block.chainid, salt)`
+            ProxySalt::SenderChain(chain_id) => {
+                Some([deployer, &U256::from(chain_id).to_be_bytes::<32>()].concat())
+            }
+            // `abi.encodePacked(block.chainid, salt)`
+            ProxySalt::Chain(chain_id) => Some(U256::from(chain_id).to_be_bytes::<32>().to_vec()),
+        }
+    }
 
-        // Print the current iteration value for debugging
-        let current_iteration = ITERATION.fetch_add(1, Ordering::Relaxed);
-        if current_iteration % 1000000 == 0 {
-            println!("iteration: {}", current_iteration);
+    /// Returns the message the factory hashes into the proxy salt for the
+    /// given internal salt buffer, or the salt itself if it is used as is.
+    fn proxy_salt_message(&self, salt: &[u8; 52]) -> Vec<u8> {
+        [
+            self.salt_namespace().unwrap_or_default().as_slice(),
+            &salt[20..],
+        ]
+        .concat()
+    }
+
+    /// Returns the salt the proxy is deployed with for the given internal
+    /// salt buffer.
+    fn proxy_salt(&self, salt: &[u8; 52]) -> B256 {
+        match self.proxy_salt {
+            ProxySalt::Raw | ProxySalt::Caller => B256::from_slice(&salt[20..]),
+            _ => keccak256(self.proxy_salt_message(salt)),
         }
+    }
+
+    /// Computes the address of the intermediate proxy deployed by the factory
+    /// for the given salt.
+    pub(super) fn compute_proxy_address(&self, salt: &FixedBytes<32>) -> Address {
+        self.derive_proxy_address(self.proxy_salt(&self.salt_buffer(salt)))
+    }
 
-        // First deploy the proxy using CREATE2
-        let proxy = self
-            .factory
-            .create2(keccak256(salt), Self::PROXY_INIT_CODE_HASH);
+    /// Builds the layout of the internal salt buffers explored while mining.
+    fn salt_layout(&self) -> Result<SaltLayout<52>> {
+        // Start the salt with the deployer address and the pinned bytes, and
+        // the salt itself with the deployer again for factories requiring it
+        let caller = match self.proxy_salt {
+            ProxySalt::Caller => self.deployer.as_slice(),
+            _ => &[],
+        };
+        SaltLayout::new(&[self.deployer.as_slice(), caller, &self.salt_prefix].concat())
+    }
 
-        // Then compute the address the proxy would deploy using CREATE
-        proxy.create(0x1)
+    /// Builds the internal salt buffer hashed by the factory, made of the
+    /// deployer address followed by the salt.
+    fn salt_buffer(&self, salt: &FixedBytes<32>) -> [u8; 52] {
+        let mut buffer = [0u8; 52];
+        buffer[0..20].copy_from_slice(self.deployer.as_slice());
+        buffer[20..52].copy_from_slice(salt.as_slice());
+        buffer
     }
 }
 
 impl Miner for Create3Miner {
-    fn mine(&self, flags: &String, prefix: &String) -> (Address, FixedBytes<32>) {
-        // Convert the flags and prefix from hex strings to addresses
-        let prefix_mask = compute_prefix_mask(prefix.len());
-        let flags = hex_to_address(flags, true);
-        let prefix = hex_to_address(prefix, false);
+    fn mine(&self, pattern: &Pattern) -> Result<MiningResult> {
+        // Compile the pattern to the words checked against candidates
+        let matcher = pattern.matcher();
 
-        // Create a random number generator
-        let mut rng = rng();
+        let layout = self.salt_layout()?;
+        let nonces = layout.nonce_range(self.start_nonce, self.end_nonce)?;
 
-        // Fill the first 20 bytes with the deployer address
-        let mut salt_base = [0u8; 52];
-        salt_base[0..20].copy_from_slice(self.deployer.as_slice());
+        // Absorb the factory and the proxy init code hash, which only leave
+        // the hashed salt to vary in the proxy deployment, and the RLP of the
+        // proxy nonce once for the whole run
+        let proxy_create2 =
+            Create2Preimage::new(self.factory, &[0; 32], self.proxy_init_code_hash, 0..32);
+        let proxy_create = Create1Preimage::new(self.proxy_nonce);
 
-        loop {
-            // Fill the random segment (bytes 20-25) with new random values
-            // for each batch of nonce attempts
-            rng.fill(salt_base[20..46].as_mut());
+        let search = Search {
+            layout: &layout,
+            ordered: false,
+            exhaustive: self.exhaustive,
+            stats: &self.stats,
+            cancel: &self.cancel,
+            observer: self.observer.as_deref(),
+            max_cpu: self.max_cpu,
+            nodes: &self.worker_nodes,
+            gpus: &[],
+            hybrid: false,
+            derive: |salt_base: &[u8; 52]| {
+                // Absorb the deployer address and the random segment once per
+                // worker, leaving only the nonces to hash
+                let nonce = layout.nonce.clone();
+                let namespace = self.salt_namespace().unwrap_or_default().len();
+                let salt_hash = Midstate::new(
+                    &self.proxy_salt_message(salt_base),
+                    namespace + nonce.start - 20..namespace + nonce.end - 20,
+                );
 
-            // Parallelize the search across different nonce values
-            let mining_result = (0..MAX_NONCE).into_par_iter().find_map_any(move |nonce| {
-                let mut salt = salt_base;
+                // Salts used as is only leave their nonce to vary in the proxy
+                // deployment
+                let unhashed = Create2Preimage::new(
+                    self.factory,
+                    salt_base[20..].try_into().expect("salts are 32 bytes"),
+                    self.proxy_init_code_hash,
+                    nonce.start - 20..nonce.end - 20,
+                );
+                let (proxy_create2, proxy_create) = (&proxy_create2, &proxy_create);
+                move |salts: &[[u8; 52]; LANES]| {
+                    // Calculate the resulting contract addresses, deployed by
+                    // the proxies at the proxy nonce
+                    let proxies = match self.proxy_salt {
+                        ProxySalt::Sender
+                        | ProxySalt::SenderAbi
+                        | ProxySalt::SenderChain(_)
+                        | ProxySalt::Chain(_) => {
+                            let nonces = salts.each_ref().map(|salt| &salt[nonce.clone()]);
+                            let raw_salts = salt_hash.hash_batch(nonces).map(|hash| hash.0);
+                            proxy_create2.addresses(&raw_salts)
+                        }
+                        ProxySalt::Raw | ProxySalt::Caller => unhashed.addresses(
+                            &salts.map(|salt| salt[20..].try_into().expect("salts are 32 bytes")),
+                        ),
+                    };
+                    let candidates = proxy_create.addresses(&proxies);
 
-                // Set the nonce segment (bytes 26-31) with the current nonce value
-                salt[46..52].copy_from_slice(&nonce.to_be_bytes()[2..]);
+                    // Return the first candidate matching the flags and prefix
+                    candidates
+                        .into_iter()
+                        .enumerate()
+                        .find(|(_, candidate)| matcher.matches(candidate))
+                }
+            },
+        };
+        let found = search.run(nonces, self.checkpoint.as_ref())?;
 
-                // Calculate the resulting contract address
-                let candidate = self.compute_create3_address(&salt);
+        // The deployer address is added by the factory, so only the last 32
+        // bytes of the buffer are passed to it
+        let result = found.result(FixedBytes::from_slice(&found.salt[20..52]));
+        if let Some(observer) = &self.observer {
+            observer.on_candidate(&result);
+        }
+        Ok(result)
+    }
 
-                // Return the candidate if it matches the flags and prefix
-                check_candidate(&flags, &prefix, &prefix_mask, &candidate)
-                    .then(|| (candidate, FixedBytes::from_slice(&salt[20..52])))
-            });
+    fn compute_address(&self, salt: &FixedBytes<32>) -> Address {
+        self.derive_address(&self.salt_buffer(salt))
+    }
 
-            // If we found a match, return it and exit
-            if let Some(answer) = mining_result {
-                break answer;
-            }
-            // Otherwise, try with a new set of random bytes
+    fn internal_salt(&self, salt: &FixedBytes<32>) -> Vec<u8> {
+        self.proxy_salt_message(&self.salt_buffer(salt))
+    }
+
+    fn raw_salt(&self, salt: &FixedBytes<32>) -> FixedBytes<32> {
+        self.proxy_salt(&self.salt_buffer(salt))
+    }
+
+    fn attempts(&self) -> u64 {
+        self.stats.total()
+    }
+
+    fn worker_attempts(&self) -> Vec<u64> {
+        self.stats.per_worker()
+    }
+
+    fn exhausted_segments(&self) -> u64 {
+        self.stats.exhausted_segments()
+    }
+
+    fn cancellation(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    fn derivation(&self) -> Result<Vec<(&'static str, String)>> {
+        // The layout covers the whole buffer, whose first 20 bytes are the
+        // deployer address
+        let layout = self.salt_layout()?;
+        let nonces = layout.nonce_range(self.start_nonce, self.end_nonce)?;
+        Ok(vec![
+            ("Method", "CREATE3".to_string()),
+            (
+                "Proxy salt",
+                match self.proxy_salt {
+                    ProxySalt::Raw => "salt".to_string(),
+                    ProxySalt::Caller => "salt, starting with deployer".to_string(),
+                    ProxySalt::Sender => "keccak256(deployer ++ salt)".to_string(),
+                    ProxySalt::SenderAbi => "keccak256(abi.encode(deployer, salt))".to_string(),
+                    ProxySalt::SenderChain(chain_id) => {
+                        format!("keccak256(deployer ++ chain id {chain_id} ++ salt)")
+                    }
+                    ProxySalt::Chain(chain_id) => format!("keccak256(chain id {chain_id} ++ salt)"),
+                },
+            ),
+            ("Factory", self.factory.to_string()),
+            ("Deployer", self.deployer.to_string()),
+            (
+                "Proxy init code hash",
+                self.proxy_init_code_hash.to_string(),
+            ),
+            ("Proxy nonce", self.proxy_nonce.to_string()),
+            (
+                "Salt layout",
+                format!("{} of deployer ++ salt", layout.describe()),
+            ),
+            ("Nonce range", format!("{nonces:?}")),
+            (
+                "Search",
+                if self.exhaustive {
+                    "exhaustive, random segments in order"
+                } else {
+                    "random"
+                }
+                .to_string(),
+            ),
+        ])
+    }
+}
+
+/// Implementation for mining vanity token-bound account addresses deployed
+/// by the ERC-6551 registry.
+///
+/// The registry deploys each account with CREATE2 as an ERC-1167 proxy whose
+/// creation code embeds the implementation, the salt, the chain id and the
+/// token, so the init code hash changes with every salt. The salt is free,
+/// as deploying an account with it can only be done for the token it is
+/// bound to, and is structured as follows:
+/// - Pinned bytes (if any)
+/// - Random values (prevents collisions between mining sessions)
+/// - Nonce values (systematically explored during mining)
+#[derive(Debug, Clone)]
+pub(super) struct TokenAccountMiner {
+    /// Address of the ERC-6551 registry deploying the account
+    registry: Address,
+    /// Address of the account implementation the proxy delegates to
+    implementation: Address,
+    /// Chain the token lives on
+    chain_id: u64,
+    /// Address of the token contract the account is bound to
+    token_contract: Address,
+    /// Id of the token the account is bound to
+    token_id: U256,
+    /// Bytes pinned at the start of the salt
+    salt_prefix: Bytes,
+    /// First nonce explored for each random segment
+    start_nonce: u64,
+    /// Nonce the exploration stops before, defaulting to the whole segment
+    end_nonce: Option<u64>,
+    /// Where the mining progress is saved, if anywhere
+    checkpoint: Option<Checkpoint>,
+    /// Number of salts tried by each worker, shared between clones
+    stats: Arc<Stats>,
+    /// Token stopping the search, shared between clones
+    cancel: CancellationToken,
+    /// Percentage of the time each worker spends hashing
+    max_cpu: u8,
+    /// NUMA group of each worker, empty to search chunks as a whole
+    worker_nodes: Vec<usize>,
+    /// Whether the random segments are explored in order
+    exhaustive: bool,
+}
+
+impl TokenAccountMiner {
+    /// Offset of the salt in the creation code of an account, after the
+    /// ERC-1167 constructor, header, implementation and footer.
+    const SALT_OFFSET: usize = 55;
+
+    /// Creates a new token-bound account miner with the specified
+    /// parameters.
+    pub fn new(
+        registry: Address,
+        implementation: Address,
+        chain_id: u64,
+        token_contract: Address,
+        token_id: U256,
+    ) -> Self {
+        Self {
+            registry,
+            implementation,
+            chain_id,
+            token_contract,
+            token_id,
+            salt_prefix: Bytes::new(),
+            start_nonce: 0,
+            end_nonce: None,
+            checkpoint: None,
+            stats: Arc::default(),
+            cancel: CancellationToken::default(),
+            max_cpu: 100,
+            worker_nodes: Vec::new(),
+            exhaustive: false,
         }
     }
+
+    /// Pins the given bytes at the start of the salt.
+    pub(super) fn with_salt_prefix(mut self, salt_prefix: Bytes) -> Self {
+        self.salt_prefix = salt_prefix;
+        self
+    }
+
+    /// Restricts the nonces explored for each random segment to
+    /// `start_nonce..end_nonce`, e.g. to split work across machines.
+    pub(super) fn with_nonce_range(mut self, start_nonce: u64, end_nonce: Option<u64>) -> Self {
+        self.start_nonce = start_nonce;
+        self.end_nonce = end_nonce;
+        self
+    }
+
+    /// Sets whether the random segments are explored in order, as a counter,
+    /// instead of being drawn at random, so that every salt is tried at most
+    /// once and mining fails once all of them were.
+    pub(super) fn with_exhaustive(mut self, exhaustive: bool) -> Self {
+        self.exhaustive = exhaustive;
+        self
+    }
+
+    /// Saves the mining progress to the given checkpoint, resuming from the
+    /// state it was loaded with.
+    pub(super) fn with_checkpoint(mut self, checkpoint: Option<Checkpoint>) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    /// Limits the share of the time each worker spends hashing to the given
+    /// percentage, sleeping the rest of the time. Percentages above 100 are
+    /// taken as 100.
+    pub(super) fn with_max_cpu(mut self, max_cpu: NonZeroU8) -> Self {
+        self.max_cpu = max_cpu.get().min(100);
+        self
+    }
+
+    /// Splits each chunk between the NUMA groups of the workers, given by
+    /// worker index, so that workers mostly search nonces near each other.
+    pub(super) fn with_worker_nodes(mut self, worker_nodes: Vec<usize>) -> Self {
+        self.worker_nodes = worker_nodes;
+        self
+    }
+
+    /// Builds the creation code the registry deploys the account with for
+    /// the given salt, `ERC6551BytecodeLib.getCreationCode`.
+    fn creation_code(&self, salt: &[u8; 32]) -> Vec<u8> {
+        [
+            hex!("3d60ad80600a3d3981f3363d3d373d3d3d363d73").as_slice(),
+            self.implementation.as_slice(),
+            &hex!("5af43d82803e903d91602b57fd5bf3"),
+            salt,
+            &U256::from(self.chain_id).to_be_bytes::<32>(),
+            self.token_contract.into_word().as_slice(),
+            &self.token_id.to_be_bytes::<32>(),
+        ]
+        .concat()
+    }
+
+    /// Builds the layout of the salts explored while mining.
+    fn salt_layout(&self) -> Result<SaltLayout<32>> {
+        // Start the salt with the pinned bytes
+        SaltLayout::new(&self.salt_prefix)
+    }
+}
+
+impl Miner for TokenAccountMiner {
+    fn mine(&self, pattern: &Pattern) -> Result<MiningResult> {
+        // Compile the pattern to the words checked against candidates
+        let matcher = pattern.matcher();
+
+        let layout = self.salt_layout()?;
+        let nonces = layout.nonce_range(self.start_nonce, self.end_nonce)?;
+
+        let search = Search {
+            layout: &layout,
+            ordered: false,
+            exhaustive: self.exhaustive,
+            stats: &self.stats,
+            cancel: &self.cancel,
+            observer: None,
+            max_cpu: self.max_cpu,
+            nodes: &self.worker_nodes,
+            gpus: &[],
+            hybrid: false,
+            derive: |salt_base: &[u8; 32]| {
+                // Absorb the creation code and the CREATE2 preimage once per
+                // worker, leaving the nonces to hash into the creation code,
+                // and the nonces and the init code hash into the preimage
+                let nonce = layout.nonce.clone();
+                let init_code = Midstate::new(
+                    &self.creation_code(salt_base),
+                    Self::SALT_OFFSET + nonce.start..Self::SALT_OFFSET + nonce.end,
+                );
+                let preimage = [&[0xff], self.registry.as_slice(), salt_base, &[0; 32]].concat();
+                let create2 = Midstate::new(&preimage, 21 + nonce.start..preimage.len());
+                move |salts: &[[u8; 32]; LANES]| {
+                    // Hash the creation code of each account, then derive its
+                    // address from the nonce and the init code hash
+                    let nonces = salts.each_ref().map(|salt| &salt[nonce.clone()]);
+                    let hashes = init_code.hash_batch(nonces);
+                    let length = nonce.len() + 32;
+                    let mut varying = [[0u8; 64]; LANES];
+                    for ((varying, salt), hash) in varying.iter_mut().zip(salts).zip(hashes) {
+                        varying[..nonce.len()].copy_from_slice(&salt[nonce.clone()]);
+                        varying[nonce.len()..length].copy_from_slice(hash.as_slice());
+                    }
+                    let candidates = create2
+                        .hash_batch(varying.each_ref().map(|varying| &varying[..length]))
+                        .map(Address::from_word);
+
+                    // Return the first candidate matching the flags and prefix
+                    candidates
+                        .into_iter()
+                        .enumerate()
+                        .find(|(_, candidate)| matcher.matches(candidate))
+                }
+            },
+        };
+        let found = search.run(nonces, self.checkpoint.as_ref())?;
+        Ok(found.result(FixedBytes::from(found.salt)))
+    }
+
+    fn compute_address(&self, salt: &FixedBytes<32>) -> Address {
+        self.registry
+            .create2(salt, keccak256(self.creation_code(salt)))
+    }
+
+    fn attempts(&self) -> u64 {
+        self.stats.total()
+    }
+
+    fn worker_attempts(&self) -> Vec<u64> {
+        self.stats.per_worker()
+    }
+
+    fn exhausted_segments(&self) -> u64 {
+        self.stats.exhausted_segments()
+    }
+
+    fn cancellation(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    fn derivation(&self) -> Result<Vec<(&'static str, String)>> {
+        let layout = self.salt_layout()?;
+        let nonces = layout.nonce_range(self.start_nonce, self.end_nonce)?;
+        Ok(vec![
+            ("Method", "CREATE2 by the ERC-6551 registry".to_string()),
+            ("Registry", self.registry.to_string()),
+            ("Implementation", self.implementation.to_string()),
+            ("Chain id", self.chain_id.to_string()),
+            ("Token contract", self.token_contract.to_string()),
+            ("Token id", self.token_id.to_string()),
+            (
+                "Init code",
+                "ERC-1167 proxy ++ abi.encode(salt, chain id, token contract, token id)"
+                    .to_string(),
+            ),
+            ("Salt layout", layout.describe()),
+            ("Nonce range", format!("{nonces:?}")),
+            (
+                "Search",
+                if self.exhaustive {
+                    "exhaustive, random segments in order"
+                } else {
+                    "random"
+                }
+                .to_string(),
+            ),
+        ])
+    }
 }
 
 #[test]
@@ -267,9 +2081,306 @@ fn test_compute_create3_address() {
     let miner = Create3Miner::new(deployer, factory);
     let mut salt = [2u8; 52];
     salt[0..20].copy_from_slice(deployer.as_slice());
-    let computed = miner.compute_create3_address(&salt);
+    let computed = miner.derive_address(&salt);
     assert_eq!(
         computed,
         address!("0x1298be70f771753b5490b4708513d9f0F513dd36")
     );
 }
+
+#[test]
+fn test_compute_address() {
+    use alloy_primitives::address;
+
+    let deployer = address!("0x9fC3dc011b461664c835F2527fffb1169b3C213e");
+    let factory = crate::CREATE3_DEFAULT_FACTORY;
+    let miner = Create3Miner::new(deployer, factory);
+    let computed = miner.compute_address(&FixedBytes::repeat_byte(2));
+    assert_eq!(
+        computed,
+        address!("0x1298be70f771753b5490b4708513d9f0F513dd36")
+    );
+
+    // Proxies deploying from another nonce are mined with it
+    let miner = miner.with_proxy_nonce(0x80);
+    let MiningResult { address, salt, .. } =
+        miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+    assert_eq!(miner.compute_proxy_address(&salt).create(0x80), address);
+}
+
+#[test]
+fn test_sequential_create2() {
+    use crate::pattern::FLAGS_MASK;
+
+    let miner = Create2Miner::new(
+        Address::ZERO,
+        crate::CREATE2_DEFAULT_FACTORY,
+        FixedBytes::ZERO,
+    )
+    .with_sequential(true);
+    let MiningResult { address, salt, .. } =
+        miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+    assert_eq!(miner.compute_address(&salt), address);
+
+    // No smaller counter value may produce matching flags
+    let counter = u64::from_be_bytes(salt[24..32].try_into().unwrap());
+    assert!(salt[0..24].iter().all(|&byte| byte == 0));
+    for smaller in 0..counter {
+        let candidate =
+            miner.compute_address(&FixedBytes::left_padding_from(&smaller.to_be_bytes()));
+        assert_ne!(candidate.bit_and(FLAGS_MASK)[18..20], [0x00, 0x80]);
+    }
+}
+
+#[test]
+fn test_createx_salt_guard() {
+    use alloy_dyn_abi::DynSolValue;
+    use alloy_primitives::address;
+
+    let deployer = address!("0x9fC3dc011b461664c835F2527fffb1169b3C213e");
+    let miner = Create2Miner::new(deployer, crate::CREATEX_FACTORY, FixedBytes::ZERO)
+        .with_salt_guard(SaltGuard::CreateX { chain_id: Some(1) });
+    let MiningResult { address, salt, .. } =
+        miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+    assert_eq!(salt[0..20], deployer[..]);
+    assert_eq!(salt[20], 0x01);
+
+    // CreateX hashes `abi.encode(msg.sender, block.chainid, salt)`
+    let guarded = keccak256(
+        DynSolValue::Tuple(vec![
+            DynSolValue::Address(deployer),
+            DynSolValue::Uint(U256::from(1), 256),
+            DynSolValue::FixedBytes(salt, 32),
+        ])
+        .abi_encode_params(),
+    );
+    assert_eq!(
+        crate::CREATEX_FACTORY.create2(guarded, FixedBytes::<32>::ZERO),
+        address
+    );
+}
+
+#[test]
+fn test_safe_proxy_salt_guard() {
+    let initializer_hash = keccak256([0xb6, 0x3e, 0x80, 0x0d]);
+    let miner = Create2Miner::new(Address::ZERO, crate::SAFE_PROXY_FACTORY, FixedBytes::ZERO)
+        .with_deployer_prefix(false)
+        .with_salt_guard(SaltGuard::SafeProxy { initializer_hash });
+    let MiningResult {
+        address,
+        salt: salt_nonce,
+        ..
+    } = miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+
+    // The factory salts the proxy with `keccak256(initializer) ++ saltNonce`
+    let salt = keccak256([initializer_hash.as_slice(), salt_nonce.as_slice()].concat());
+    assert_eq!(
+        crate::SAFE_PROXY_FACTORY.create2(salt, FixedBytes::<32>::ZERO),
+        address
+    );
+}
+
+#[test]
+fn test_salt_template() {
+    // The nonce bytes cross the first Keccak block after 105 bytes
+    for before in [vec![0x11; 20], vec![0x22; 105]] {
+        let template = SaltTemplate {
+            before: before.into(),
+            after: Bytes::from_static(&[0x33; 40]),
+        };
+        let miner = Create2Miner::new(Address::ZERO, Address::ZERO, FixedBytes::ZERO)
+            .with_deployer_prefix(false)
+            .with_salt_guard(SaltGuard::Template(template.clone()));
+        let MiningResult { address, salt, .. } =
+            miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+        let raw_salt = keccak256(template.fill(salt.as_slice()));
+        assert_eq!(
+            Address::ZERO.create2(raw_salt, FixedBytes::<32>::ZERO),
+            address
+        );
+    }
+}
+
+#[test]
+fn test_solady_create3() {
+    let factory = Address::repeat_byte(0x11);
+    let miner = Create3Miner::new(Address::ZERO, factory).with_proxy_salt(ProxySalt::Raw);
+    let MiningResult { address, salt, .. } =
+        miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+
+    // Solady salts the proxy with the salt as is
+    let proxy = factory.create2(salt, Create3Miner::PROXY_INIT_CODE_HASH);
+    assert_eq!(proxy.create(1), address);
+
+    // Metamorphic factories require the salt to start with the caller
+    let deployer = Address::repeat_byte(0x22);
+    let transient_init_code_hash = keccak256([0x58, 0x60]);
+    let miner = Create3Miner::new(deployer, factory)
+        .with_proxy_salt(ProxySalt::Caller)
+        .with_proxy_init_code_hash(transient_init_code_hash)
+        .with_proxy_nonce(2);
+    let MiningResult { address, salt, .. } =
+        miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+    assert_eq!(&salt[..20], deployer.as_slice());
+    let transient = factory.create2(salt, transient_init_code_hash);
+    assert_eq!(transient.create(2), address);
+}
+
+#[test]
+fn test_axelar_create3() {
+    let (deployer, factory) = (Address::repeat_byte(0x22), Address::repeat_byte(0x11));
+    let proxy_init_code_hash = keccak256([0x60, 0x80]);
+    let miner = Create3Miner::new(deployer, factory)
+        .with_proxy_salt(ProxySalt::SenderAbi)
+        .with_proxy_init_code_hash(proxy_init_code_hash);
+    let MiningResult { address, salt, .. } =
+        miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+
+    // Axelar salts its proxy with `keccak256(abi.encode(msg.sender, salt))`
+    let proxy_salt = keccak256([deployer.into_word(), salt].concat());
+    let proxy = factory.create2(proxy_salt, proxy_init_code_hash);
+    assert_eq!(proxy.create(1), address);
+
+    // Chain-bound factories also hash the chain id after the caller
+    let miner = Create3Miner::new(deployer, factory).with_proxy_salt(ProxySalt::SenderChain(10));
+    let MiningResult { address, salt, .. } =
+        miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+    let chain_id = U256::from(10).to_be_bytes::<32>();
+    let proxy_salt = keccak256([deployer.as_slice(), &chain_id, salt.as_slice()].concat());
+    let proxy = factory.create2(proxy_salt, Create3Miner::PROXY_INIT_CODE_HASH);
+    assert_eq!(proxy.create(1), address);
+
+    // Chain-scoped factories hash the chain id without the caller
+    let miner = Create3Miner::new(deployer, factory).with_proxy_salt(ProxySalt::Chain(10));
+    let MiningResult { address, salt, .. } =
+        miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+    let proxy_salt = keccak256([chain_id.as_slice(), salt.as_slice()].concat());
+    let proxy = factory.create2(proxy_salt, Create3Miner::PROXY_INIT_CODE_HASH);
+    assert_eq!(proxy.create(1), address);
+}
+
+#[test]
+fn test_token_account() {
+    let (registry, implementation) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+    let token_contract = Address::repeat_byte(0x33);
+    let miner = TokenAccountMiner::new(registry, implementation, 1, token_contract, U256::from(7));
+    let MiningResult { address, salt, .. } =
+        miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+
+    // The registry deploys an ERC-1167 proxy with the salt, chain id and
+    // token appended
+    let creation_code = [
+        &hex!("3d60ad80600a3d3981f3363d3d373d3d3d363d73")[..],
+        implementation.as_slice(),
+        &hex!("5af43d82803e903d91602b57fd5bf3"),
+        salt.as_slice(),
+        &U256::from(1).to_be_bytes::<32>(),
+        token_contract.into_word().as_slice(),
+        &U256::from(7).to_be_bytes::<32>(),
+    ]
+    .concat();
+    assert_eq!(creation_code.len(), 183);
+    assert_eq!(registry.create2(salt, keccak256(creation_code)), address);
+}
+
+#[test]
+fn test_candidates() {
+    let miner = Create2Miner::new(Address::ZERO, Address::ZERO, FixedBytes::ZERO);
+    let pattern = Pattern::parse("0x0080", "").unwrap();
+    let candidates = miner
+        .candidates(&pattern)
+        .take(2)
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(candidates.len(), 2);
+    assert_ne!(candidates[0].salt, candidates[1].salt);
+    for MiningResult { address, salt, .. } in candidates {
+        assert_eq!(miner.compute_address(&salt), address);
+        assert!(pattern.matcher().matches(&address));
+    }
+
+    // Streamed candidates come from a background thread
+    let candidate = miner.stream(&pattern).recv().unwrap().unwrap();
+    assert!(pattern.matcher().matches(&candidate.address));
+}
+
+#[test]
+fn test_observer() {
+    #[derive(Default)]
+    struct Recorder {
+        batches: AtomicU64,
+        reports: AtomicU64,
+        candidates: Mutex<Vec<MiningResult>>,
+        segments: Mutex<Vec<Vec<u8>>>,
+    }
+    impl Observer for Recorder {
+        fn on_progress(&self, _attempts: u64, _hashrate: f64) {
+            self.reports.fetch_add(1, Ordering::Relaxed);
+        }
+        fn on_candidate(&self, result: &MiningResult) {
+            self.candidates.lock().unwrap().push(*result);
+        }
+        fn on_batch_complete(&self) {
+            self.batches.fetch_add(1, Ordering::Relaxed);
+        }
+        fn on_segment_exhausted(&self, segment: &[u8]) {
+            self.segments.lock().unwrap().push(segment.to_vec());
+        }
+    }
+
+    let recorder = Arc::new(Recorder::default());
+    let miner = Create2Miner::new(Address::ZERO, Address::ZERO, FixedBytes::ZERO)
+        .with_observer(recorder.clone());
+    let result = miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+    assert_eq!(*recorder.candidates.lock().unwrap(), [result]);
+
+    // Each chunk without a match is reported along with the progress
+    assert_eq!(
+        recorder.batches.load(Ordering::Relaxed),
+        recorder.reports.load(Ordering::Relaxed)
+    );
+    assert!(recorder.segments.lock().unwrap().is_empty());
+
+    // Random segments searched without a match are reported and counted
+    // rather than printed
+    let recorder = Arc::new(Recorder::default());
+    let miner = Create2Miner::new(Address::ZERO, Address::ZERO, FixedBytes::ZERO)
+        .with_nonce_range(0, Some(16))
+        .with_observer(recorder.clone());
+    miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+    let segments = recorder.segments.lock().unwrap();
+    assert!(!segments.is_empty());
+    assert_eq!(segments.len() as u64, miner.exhausted_segments());
+}
+
+#[test]
+fn test_max_cpu() {
+    // Throttled workers still find a matching salt, and percentages above
+    // 100 do not throttle at all
+    let pattern = Pattern::parse("0x0080", "").unwrap();
+    for max_cpu in [50, 255] {
+        let miner = Create2Miner::new(Address::ZERO, Address::ZERO, FixedBytes::ZERO)
+            .with_max_cpu(NonZeroU8::new(max_cpu).unwrap());
+        assert_eq!(miner.max_cpu, max_cpu.min(100));
+        let MiningResult { address, .. } = miner.mine(&pattern).unwrap();
+        assert!(pattern.matcher().matches(&address));
+    }
+}
+
+#[test]
+fn test_also_init_code_hashes() {
+    // Both init code hashes match in the batch of 8 nonces from 180664, the
+    // second one first, so sequential searches return its salt
+    let init_code_hashes = [B256::ZERO, B256::from(U256::from(4174))];
+    let miner = Create2Miner::new(Address::ZERO, Address::ZERO, init_code_hashes[0])
+        .with_sequential(true)
+        .with_nonce_range(180_664, None)
+        .with_also_init_code_hashes(init_code_hashes[1..].to_vec());
+    let result = miner.mine(&Pattern::parse("0x0080", "").unwrap()).unwrap();
+    assert_eq!(result.salt, B256::from(U256::from(180_669)));
+
+    // The salt is labeled with the hash it matched with
+    let derived = miner.compute_addresses(&result.salt);
+    assert_eq!(derived[1], result.address);
+    assert_ne!(derived[0], result.address);
+}
@@ -0,0 +1,48 @@
+use std::fmt;
+
+use crate::mine::MiningResult;
+
+/// Receives the events of a search, so that applications built on Piwi can
+/// report its progress their own way.
+///
+/// Every method does nothing by default, so observers only implement the
+/// events they are interested in. Events are sent from the thread running
+/// the search, between two chunks of work, so observers should return
+/// quickly not to slow it down.
+pub trait Observer: Send + Sync {
+    /// Called after each chunk of nonces, about four times per second once
+    /// the work sizes are tuned.
+    ///
+    /// # Arguments
+    /// * `attempts` - The number of salts tried by the miner so far.
+    /// * `hashrate` - The number of salts tried per second during the chunk.
+    fn on_progress(&self, attempts: u64, hashrate: f64) {
+        let _ = (attempts, hashrate);
+    }
+
+    /// Called when the search finds a salt producing a matching address.
+    ///
+    /// # Arguments
+    /// * `result` - The salt found, with the address it deploys to.
+    fn on_candidate(&self, result: &MiningResult) {
+        let _ = result;
+    }
+
+    /// Called once every worker is done with a chunk of nonces.
+    fn on_batch_complete(&self) {}
+
+    /// Called when every nonce of a random segment of the salt was tried
+    /// without a match, before the search moves on to the next segment.
+    ///
+    /// # Arguments
+    /// * `segment` - The random bytes of the salt searched without a match.
+    fn on_segment_exhausted(&self, segment: &[u8]) {
+        let _ = segment;
+    }
+}
+
+impl fmt::Debug for dyn Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Observer")
+    }
+}
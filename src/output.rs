@@ -0,0 +1,245 @@
+use alloy_primitives::{Address, B256, FixedBytes, hex};
+use anstyle::{AnsiColor, Style};
+
+use crate::{
+    cli::SaltFormat,
+    mine::{Miner, MiningResult},
+    pattern::{FLAGS_MASK, Pattern, flags_value},
+};
+
+/// Style of the address characters matched by the prefix.
+const PREFIX_STYLE: Style = Style::new()
+    .bold()
+    .fg_color(Some(anstyle::Color::Ansi(AnsiColor::Green)));
+
+/// Style of the address characters holding the flags.
+const FLAGS_STYLE: Style = Style::new()
+    .bold()
+    .fg_color(Some(anstyle::Color::Ansi(AnsiColor::Cyan)));
+
+/// Style of the field labels.
+const LABEL_STYLE: Style = Style::new().dimmed();
+
+/// Formats a mined salt for display.
+///
+/// # Arguments
+/// * `miner` - The miner that found the salt.
+/// * `salt` - The salt passed to the factory contract.
+/// * `format` - The format to print the salt in.
+pub(super) fn format_salt<M: Miner>(
+    miner: &M,
+    salt: &FixedBytes<32>,
+    format: SaltFormat,
+) -> String {
+    match format {
+        SaltFormat::Factory => salt.to_string(),
+        SaltFormat::Raw => miner.raw_salt(salt).to_string(),
+        SaltFormat::Internal => hex::encode_prefixed(miner.internal_salt(salt)),
+        SaltFormat::Solidity => format!("bytes32({salt})"),
+    }
+}
+
+/// Prints a mining result as aligned fields, highlighting the parts of the
+/// checksummed address matched by the prefix and the flags.
+///
+/// Colors are stripped when stdout is not a terminal or `NO_COLOR` is set.
+///
+/// # Arguments
+/// * `address` - The mined address.
+/// * `salt` - The formatted salt.
+/// * `pattern` - The pattern the address was mined for.
+pub(super) fn print_result(address: Address, salt: &str, pattern: &Pattern) {
+    print_match(&[("Salt", salt.to_string())], address, pattern);
+}
+
+/// Prints a mining result as a JSON object on a single line, in the schema
+/// of [`MiningResult`], for scripts and logs.
+pub(super) fn print_json(result: &MiningResult) {
+    println!(
+        "{}",
+        serde_json::to_string(result).expect("results serialize to JSON")
+    );
+}
+
+/// Prints a mined CREATE nonce like [`print_result`] prints salts.
+///
+/// # Arguments
+/// * `address` - The mined address.
+/// * `nonce` - The deployment nonce of the address.
+/// * `pattern` - The pattern the address was mined for.
+pub(super) fn print_nonce_result(address: Address, nonce: u64, pattern: &Pattern) {
+    print_match(&[("Nonce", nonce.to_string())], address, pattern);
+}
+
+/// Prints a mined deployer key pair like [`print_result`] prints salts,
+/// without its private key.
+///
+/// # Arguments
+/// * `deployer` - The address of the mined account.
+/// * `nonce` - The nonce the account deploys the contract with.
+/// * `address` - The mined contract address.
+/// * `pattern` - The pattern the address was mined for.
+pub(super) fn print_keypair_result(
+    deployer: Address,
+    nonce: u64,
+    address: Address,
+    pattern: &Pattern,
+) {
+    print_match(
+        &[
+            ("Deployer", deployer.to_string()),
+            ("Nonce", nonce.to_string()),
+        ],
+        address,
+        pattern,
+    );
+}
+
+/// Prints a mined salt like [`print_result`], along with the hash of the
+/// candidate init code it matched with.
+///
+/// # Arguments
+/// * `address` - The mined address.
+/// * `salt` - The formatted salt.
+/// * `init_code_hash` - The hash of the matching candidate init code.
+/// * `pattern` - The pattern the address was mined for.
+pub(super) fn print_candidate_result(
+    address: Address,
+    salt: &str,
+    init_code_hash: B256,
+    pattern: &Pattern,
+) {
+    print_match(
+        &[
+            ("Salt", salt.to_string()),
+            ("Hash", init_code_hash.to_string()),
+        ],
+        address,
+        pattern,
+    );
+}
+
+/// Prints the addresses other factories deploy to with a salt printed by
+/// [`print_result`], each after its factory.
+///
+/// # Arguments
+/// * `others` - Each other factory with the address it deploys to.
+/// * `pattern` - The pattern the addresses were mined for.
+pub(super) fn print_other_factories(others: &[(Address, Address)], pattern: &Pattern) {
+    for (factory, address) in others {
+        print_match(&[("Factory", factory.to_string())], *address, pattern);
+    }
+}
+
+/// Prints the salts of an implementation and of the proxy delegating to it
+/// like [`print_result`] prints a salt, under a heading each.
+///
+/// # Arguments
+/// * `implementation` - The mined implementation address, salt and pattern.
+/// * `proxy` - The mined proxy address, salt and pattern.
+pub(super) fn print_pair_result(
+    implementation: (Address, &str, &Pattern),
+    proxy: (Address, &str, &Pattern),
+) {
+    for (title, (address, salt, pattern)) in [("Implementation", implementation), ("Proxy", proxy)]
+    {
+        anstream::println!("{title}");
+        print_result(address, salt, pattern);
+        println!();
+    }
+}
+
+/// Prints the inputs of an implementation and of the proxy delegating to it
+/// like [`print_dry_run`] prints those of a mining run, under a heading each.
+///
+/// # Arguments
+/// * `implementation` - The derivation inputs and pattern of the
+///   implementation.
+/// * `proxy` - The derivation inputs and pattern of the proxy, derived with
+///   the placeholder implementation.
+/// * `placeholder` - The implementation address the proxy is derived with,
+///   standing in for the one to be mined.
+pub(super) fn print_pair_dry_run(
+    implementation: (&[(&str, String)], &Pattern),
+    proxy: (&[(&str, String)], &Pattern),
+    placeholder: Address,
+) {
+    let proxy_title = format!("Proxy, with the placeholder implementation {placeholder}");
+    for (title, (derivation, pattern)) in [
+        ("Implementation", implementation),
+        (proxy_title.as_str(), proxy),
+    ] {
+        anstream::println!("{title}");
+        print_dry_run(derivation, pattern);
+        println!();
+    }
+}
+
+/// Prints what an address was mined with, then the address and its flags.
+///
+/// # Arguments
+/// * `fields` - The labeled values the address was mined with.
+/// * `address` - The mined address.
+/// * `pattern` - The pattern the address was mined for.
+fn print_match(fields: &[(&str, String)], address: Address, pattern: &Pattern) {
+    let checksummed = address.to_checksum(None);
+    let digits = &checksummed[2..];
+
+    // The prefix covers whole nibbles, and the flags the last four nibbles
+    let prefix_len = pattern.prefix_nibbles();
+    let flags_start = digits.len() - 4;
+    let highlighted = if prefix_len > flags_start {
+        format!("{PREFIX_STYLE}{digits}{PREFIX_STYLE:#}")
+    } else {
+        format!(
+            "{PREFIX_STYLE}{}{PREFIX_STYLE:#}{}{FLAGS_STYLE}{}{FLAGS_STYLE:#}",
+            &digits[..prefix_len],
+            &digits[prefix_len..flags_start],
+            &digits[flags_start..]
+        )
+    };
+
+    for (label, value) in fields {
+        anstream::println!("{LABEL_STYLE}{label:<8}{LABEL_STYLE:#} {value}");
+    }
+    anstream::println!("{LABEL_STYLE}Address{LABEL_STYLE:#}  0x{highlighted}");
+    anstream::println!(
+        "{LABEL_STYLE}Flags{LABEL_STYLE:#}    {:#06x} (mask {:#06x})",
+        flags_value(pattern.flags),
+        flags_value(FLAGS_MASK)
+    );
+}
+
+/// Prints the inputs of a mining run along with the difficulty of its
+/// pattern, as aligned fields.
+///
+/// # Arguments
+/// * `derivation` - The labeled derivation inputs of the miner.
+/// * `pattern` - The pattern to mine for.
+pub(super) fn print_dry_run(derivation: &[(&str, String)], pattern: &Pattern) {
+    let bits = pattern.difficulty_bits();
+    let fields = derivation.iter().cloned().chain([
+        ("Flags", format!("{:#06x}", flags_value(pattern.flags))),
+        ("Flags mask", format!("{:#06x}", flags_value(FLAGS_MASK))),
+        ("Prefix", hex::encode_prefixed(pattern.prefix)),
+        ("Prefix mask", hex::encode_prefixed(pattern.prefix_mask)),
+        (
+            "Difficulty",
+            format!(
+                "{bits} bits, {:.3e} expected attempts",
+                2f64.powi(bits as i32)
+            ),
+        ),
+    ]);
+
+    // Align the values on the longest label
+    let width = derivation
+        .iter()
+        .map(|(label, _)| label.len())
+        .chain([11])
+        .max()
+        .unwrap_or_default();
+    for (label, value) in fields {
+        anstream::println!("{LABEL_STYLE}{label:<width$}{LABEL_STYLE:#}  {value}");
+    }
+}
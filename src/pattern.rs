@@ -0,0 +1,159 @@
+use std::array;
+
+use alloy_primitives::{Address, hex};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+pub(super) use crate::core::{FLAGS_MASK, Pattern, parse_flags, parse_prefix};
+
+/// Names of the Uniswap V4 hook flags, from the highest flag bit to the
+/// lowest.
+pub(super) const HOOK_FLAGS: [&str; 14] = [
+    "beforeInitialize",
+    "afterInitialize",
+    "beforeAddLiquidity",
+    "afterAddLiquidity",
+    "beforeRemoveLiquidity",
+    "afterRemoveLiquidity",
+    "beforeSwap",
+    "afterSwap",
+    "beforeDonate",
+    "afterDonate",
+    "beforeSwapReturnDelta",
+    "afterSwapReturnDelta",
+    "afterAddLiquidityReturnDelta",
+    "afterRemoveLiquidityReturnDelta",
+];
+
+impl Pattern {
+    /// Returns the number of address nibbles covered by the prefix.
+    pub(super) fn prefix_nibbles(&self) -> usize {
+        self.prefix_mask
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum::<usize>()
+            / 4
+    }
+
+    /// Compiles the pattern to masks and values over lanes 1 to 3 of a
+    /// Keccak256 state, as little-endian words, whose last 20 bytes are the
+    /// address derived from the hash.
+    pub(super) fn hash_lanes(&self) -> ([u64; 3], [u64; 3]) {
+        let lanes = |address: Address| {
+            let mut bytes = [0u8; 24];
+            bytes[4..].copy_from_slice(address.as_slice());
+            array::from_fn(|lane| {
+                u64::from_le_bytes(
+                    bytes[8 * lane..8 * lane + 8]
+                        .try_into()
+                        .expect("lanes are 8 bytes"),
+                )
+            })
+        };
+        (
+            lanes(FLAGS_MASK.bit_or(self.prefix_mask)),
+            lanes(self.flags.bit_or(self.prefix)),
+        )
+    }
+
+    /// Returns the flags as a hex string, e.g. `0x0080`.
+    pub(super) fn flags_hex(&self) -> String {
+        format!("{:#06x}", flags_value(self.flags))
+    }
+
+    /// Returns the prefix as a hex string of its nibbles, e.g. `0xbadc0de`,
+    /// or an empty string without a prefix.
+    pub(super) fn prefix_hex(&self) -> String {
+        match self.prefix_nibbles() {
+            0 => String::new(),
+            nibbles => format!("0x{}", &hex::encode(self.prefix)[..nibbles]),
+        }
+    }
+
+    /// Returns the number of address bits constrained by the pattern.
+    pub(super) fn difficulty_bits(&self) -> u32 {
+        FLAGS_MASK
+            .bit_or(self.prefix_mask)
+            .iter()
+            .map(|byte| byte.count_ones())
+            .sum()
+    }
+}
+
+/// Flags and prefix hex strings a pattern is serialized as.
+#[derive(Serialize, Deserialize)]
+struct PatternFields {
+    /// The flags expected in the lower 14 bits of the address.
+    flags: String,
+    /// The prefix expected at the start of the address.
+    #[serde(default)]
+    prefix: String,
+}
+
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PatternFields {
+            flags: self.flags_hex(),
+            prefix: self.prefix_hex(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Validate the strings like the command line arguments
+        let PatternFields { flags, prefix } = PatternFields::deserialize(deserializer)?;
+        Pattern::parse(&flags, &prefix).map_err(de::Error::custom)
+    }
+}
+
+/// Returns the flags stored in the last two bytes of an address.
+pub(super) fn flags_value(address: Address) -> u16 {
+    u16::from_be_bytes([address[18], address[19]])
+}
+
+/// Returns the flag bit of a Uniswap V4 hook flag name.
+///
+/// Names are matched case-insensitively, so both the `Hooks.Permissions`
+/// field names, e.g. `beforeSwapReturnDelta`, and the `Hooks` constant names,
+/// e.g. `BEFORE_SWAP_RETURNS_DELTA_FLAG`, are accepted.
+pub(super) fn hook_flag(name: &str) -> Option<u16> {
+    let normalized = name.replace('_', "").to_ascii_lowercase();
+    let normalized = normalized.strip_suffix("flag").unwrap_or(&normalized);
+    let normalized = normalized.replace("returnsdelta", "returndelta");
+    HOOK_FLAGS
+        .iter()
+        .position(|flag| flag.to_ascii_lowercase() == normalized)
+        .map(|index| 1 << (HOOK_FLAGS.len() - 1 - index))
+}
+
+#[test]
+fn test_parse_pattern() {
+    use alloy_primitives::address;
+
+    let pattern = Pattern::parse("0x2fff", "0xbadc0de").unwrap();
+    assert_eq!(
+        pattern.prefix,
+        address!("0xbadc0de000000000000000000000000000000000")
+    );
+    assert_eq!(
+        pattern.prefix_mask,
+        address!("0xfffffff000000000000000000000000000000000")
+    );
+    assert_eq!(pattern.difficulty_bits(), 28 + 14);
+    assert_eq!(
+        (pattern.flags_hex(), pattern.prefix_hex()),
+        ("0x2fff".to_string(), "0xbadc0de".to_string())
+    );
+
+    let matcher = pattern.matcher();
+    assert!(matcher.matches(&address!("0xbadc0de000000000000000000000000000002fff")));
+    assert!(matcher.matches(&address!("0xbadc0defffffffffffffffffffffffffffffefff")));
+    assert!(!matcher.matches(&address!("0xbadc0df000000000000000000000000000002fff")));
+    assert!(!matcher.matches(&address!("0xbadc0de000000000000000000000000000002ffe")));
+
+    assert!(Pattern::parse("4000", "").is_err());
+    assert!(Pattern::parse("2fff", "12g4").is_err());
+    assert!(Pattern::parse("1", &"0".repeat(40)).is_err());
+    assert!(Pattern::parse("0", &"0".repeat(41)).is_err());
+}
@@ -0,0 +1,234 @@
+use std::{str::FromStr, sync::Arc};
+
+use alloy_primitives::{Address, Bytes, FixedBytes};
+use pyo3::{
+    exceptions::{PyKeyboardInterrupt, PyRuntimeError, PyValueError},
+    prelude::*,
+};
+
+use crate::{
+    cancel::CancellationToken,
+    core::Pattern,
+    error::Error,
+    mine::{Create2Miner, Create3Miner, Miner, MiningResult},
+    observe::Observer,
+};
+
+/// Salt found by a miner, with the address it deploys the contract to, as
+/// hex strings.
+#[derive(Debug)]
+#[pyclass(name = "MiningResult", frozen, get_all)]
+struct PyMiningResult {
+    /// Address the contract is deployed at with the salt.
+    address: String,
+    /// Salt passed to the factory contract.
+    salt: String,
+}
+
+#[pymethods]
+impl PyMiningResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "MiningResult(address='{}', salt='{}')",
+            self.address, self.salt
+        )
+    }
+}
+
+impl From<MiningResult> for PyMiningResult {
+    fn from(MiningResult { address, salt, .. }: MiningResult) -> Self {
+        Self {
+            address: address.to_string(),
+            salt: salt.to_string(),
+        }
+    }
+}
+
+/// Reports the progress of a search to a Python callback, and cancels it
+/// once Python has a pending signal, e.g. from Ctrl-C.
+struct Callback {
+    /// The callable given the attempts and the hashrate, if any.
+    progress: Option<Py<PyAny>>,
+    /// The token stopping the search.
+    cancel: CancellationToken,
+}
+
+impl Observer for Callback {
+    fn on_progress(&self, attempts: u64, hashrate: f64) {
+        let Some(progress) = &self.progress else {
+            return;
+        };
+
+        // Exceptions raised by the callback stop the search like a signal
+        Python::attach(|py| {
+            if let Err(err) = progress.call1(py, (attempts, hashrate)) {
+                err.restore(py);
+                self.cancel.cancel();
+            }
+        });
+    }
+
+    fn on_batch_complete(&self) {
+        Python::attach(|py| {
+            if py.check_signals().is_err() {
+                self.cancel.cancel();
+            }
+        });
+    }
+}
+
+/// Runs a search without holding the GIL, reporting its progress to the
+/// callback and turning its errors into Python exceptions.
+///
+/// # Arguments
+/// * `miner` - Builds the miner to search with from the observer and the
+///   cancellation token to give it.
+/// * `flags` - The flags expected in the lower 14 bits of the address.
+/// * `prefix` - The prefix expected at the start of the address.
+/// * `progress` - The callable given the attempts and the hashrate, if any.
+fn mine<M: Miner + Send + Sync>(
+    miner: impl FnOnce(Arc<dyn Observer>, CancellationToken) -> M,
+    flags: &str,
+    prefix: &str,
+    progress: Option<Py<PyAny>>,
+) -> PyResult<PyMiningResult> {
+    let pattern =
+        Pattern::parse(flags, prefix).map_err(|err| PyValueError::new_err(err.to_string()))?;
+    let cancel = CancellationToken::new();
+    let callback = Arc::new(Callback {
+        progress,
+        cancel: cancel.clone(),
+    });
+    let miner = miner(callback, cancel);
+    Python::attach(|py| match py.detach(|| miner.mine(&pattern)) {
+        Ok(result) => Ok(result.into()),
+        // Raise the exception of the callback, or a KeyboardInterrupt for the
+        // signal
+        Err(Error::Cancelled { .. }) => {
+            Err(PyErr::take(py)
+                .unwrap_or_else(|| PyKeyboardInterrupt::new_err("mining was cancelled")))
+        }
+        Err(err) => Err(PyRuntimeError::new_err(err.to_string())),
+    })
+}
+
+/// Mines a CREATE2 salt deploying the init code with the given hash to an
+/// address matching the flags and prefix.
+///
+/// The progress callback, if any, is called about four times per second
+/// with the number of attempts and the hashrate.
+#[pyfunction]
+#[pyo3(signature = (deployer, factory, init_code_hash, flags, prefix = "", *, salt_prefix = None, progress = None))]
+fn mine_create2(
+    deployer: &str,
+    factory: &str,
+    init_code_hash: &str,
+    flags: &str,
+    prefix: &str,
+    salt_prefix: Option<&str>,
+    progress: Option<Py<PyAny>>,
+) -> PyResult<PyMiningResult> {
+    let (deployer, factory) = (parse("deployer", deployer)?, parse("factory", factory)?);
+    let init_code_hash = parse("init code hash", init_code_hash)?;
+    let salt_prefix: Bytes = salt_prefix
+        .map(|salt_prefix| parse("salt prefix", salt_prefix))
+        .transpose()?
+        .unwrap_or_default();
+    let miner = |observer, cancel| {
+        Create2Miner::new(deployer, factory, init_code_hash)
+            .with_salt_prefix(salt_prefix)
+            .with_observer(observer)
+            .with_cancel(cancel)
+    };
+    mine(miner, flags, prefix, progress)
+}
+
+/// Mines a CREATE3 salt deploying a contract to an address matching the
+/// flags and prefix, with a factory hashing the salt after the deployer.
+///
+/// The progress callback, if any, is called about four times per second
+/// with the number of attempts and the hashrate.
+#[pyfunction]
+#[pyo3(signature = (deployer, factory, flags, prefix = "", *, salt_prefix = None, progress = None))]
+fn mine_create3(
+    deployer: &str,
+    factory: &str,
+    flags: &str,
+    prefix: &str,
+    salt_prefix: Option<&str>,
+    progress: Option<Py<PyAny>>,
+) -> PyResult<PyMiningResult> {
+    let (deployer, factory) = (parse("deployer", deployer)?, parse("factory", factory)?);
+    let salt_prefix: Bytes = salt_prefix
+        .map(|salt_prefix| parse("salt prefix", salt_prefix))
+        .transpose()?
+        .unwrap_or_default();
+    let miner = |observer, cancel| {
+        Create3Miner::new(deployer, factory)
+            .with_salt_prefix(salt_prefix)
+            .with_observer(observer)
+            .with_cancel(cancel)
+    };
+    mine(miner, flags, prefix, progress)
+}
+
+/// Computes the address a factory deploys the init code with the given hash
+/// to with CREATE2.
+#[pyfunction]
+fn compute_create2_address(factory: &str, salt: &str, init_code_hash: &str) -> PyResult<String> {
+    let miner = Create2Miner::new(
+        Address::ZERO,
+        parse("factory", factory)?,
+        parse("init code hash", init_code_hash)?,
+    );
+    Ok(miner.compute_address(&parse("salt", salt)?).to_string())
+}
+
+/// Computes the address a CREATE3 factory hashing the salt after the
+/// deployer deploys a contract to.
+#[pyfunction]
+fn compute_create3_address(deployer: &str, factory: &str, salt: &str) -> PyResult<String> {
+    let miner = Create3Miner::new(parse("deployer", deployer)?, parse("factory", factory)?);
+    let salt: FixedBytes<32> = parse("salt", salt)?;
+    Ok(miner.compute_address(&salt).to_string())
+}
+
+/// Parses a hex argument, raising a `ValueError` naming it if malformed.
+fn parse<T>(name: &str, value: &str) -> PyResult<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|err| PyValueError::new_err(format!("invalid {name} {value:?}: {err}")))
+}
+
+/// Mines CREATE2 and CREATE3 salts giving vanity contract addresses.
+#[pymodule]
+fn piwi(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyMiningResult>()?;
+    module.add_function(wrap_pyfunction!(mine_create2, module)?)?;
+    module.add_function(wrap_pyfunction!(mine_create3, module)?)?;
+    module.add_function(wrap_pyfunction!(compute_create2_address, module)?)?;
+    module.add_function(wrap_pyfunction!(compute_create3_address, module)?)?;
+    Ok(())
+}
+
+#[test]
+fn test_python() {
+    use alloy_primitives::B256;
+
+    Python::initialize();
+    Python::attach(|py| {
+        let zero = "0x0000000000000000000000000000000000000000";
+        let hash = B256::ZERO.to_string();
+        let result = mine_create2(zero, zero, &hash, "0x0080", "", None, None).unwrap();
+        let address = compute_create2_address(zero, &result.salt, &hash).unwrap();
+        assert_eq!(address, result.address);
+
+        // Malformed arguments raise a ValueError
+        let err = mine_create3("0x12", zero, "0x0080", "", None, None).unwrap_err();
+        assert!(err.is_instance_of::<PyValueError>(py));
+    });
+}
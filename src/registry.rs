@@ -0,0 +1,226 @@
+use alloy_primitives::Address;
+use clap::ValueEnum;
+
+use crate::{
+    cli::{Chain, Create3Variant, FactoryName, FactoryPreset},
+    error::{Error, Result},
+};
+
+/// How a registered factory deploys contracts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FactoryKind {
+    /// With CREATE2, following the salt rules and calling convention of a
+    /// preset.
+    Create2(FactoryPreset),
+    /// With CREATE3, following the proxy derivation of a variant.
+    Create3(Create3Variant),
+}
+
+impl FactoryName {
+    /// Returns the name of the factory, as given to `--factory-name`.
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::Arachnid => "arachnid",
+            Self::SafeSingleton => "safe-singleton",
+            Self::ImmutableCreate2 => "immutable-create2",
+            Self::CreateX => "createx",
+            Self::Eip2470 => "eip2470",
+            Self::LayerZeroCreate3 => "layerzero-create3",
+            Self::ZeframlouCreate3 => "zeframlou-create3",
+            Self::SoladyCreate3 => "solady-create3",
+            Self::AxelarCreate3 => "axelar-create3",
+        }
+    }
+
+    /// Returns how the factory deploys contracts.
+    pub(super) fn kind(self) -> FactoryKind {
+        match self {
+            Self::Arachnid => FactoryKind::Create2(FactoryPreset::Arachnid),
+            Self::SafeSingleton => FactoryKind::Create2(FactoryPreset::SafeSingleton),
+            Self::ImmutableCreate2 => FactoryKind::Create2(FactoryPreset::ImmutableCreate2),
+            Self::CreateX => FactoryKind::Create2(FactoryPreset::CreateX),
+            Self::Eip2470 => FactoryKind::Create2(FactoryPreset::Eip2470),
+            Self::LayerZeroCreate3 => FactoryKind::Create3(Create3Variant::LayerZero),
+            Self::ZeframlouCreate3 => FactoryKind::Create3(Create3Variant::Zeframlou),
+            Self::SoladyCreate3 => FactoryKind::Create3(Create3Variant::Solady),
+            Self::AxelarCreate3 => FactoryKind::Create3(Create3Variant::Axelar),
+        }
+    }
+
+    /// Returns the address the factory is deployed at, if it has a canonical
+    /// deployment.
+    pub(super) fn address(self) -> Option<Address> {
+        match self.kind() {
+            FactoryKind::Create2(preset) => Some(preset.factory()),
+            FactoryKind::Create3(variant) => variant.factory(),
+        }
+    }
+
+    /// Returns the chains the factory is known to be deployed on at its
+    /// address.
+    fn chains(self) -> &'static [Chain] {
+        use Chain::{Arbitrum, Base, Bsc, Mainnet, Optimism, Polygon, Sepolia};
+        match self {
+            Self::Arachnid | Self::SafeSingleton | Self::CreateX => {
+                &[Mainnet, Sepolia, Base, Arbitrum, Optimism, Polygon, Bsc]
+            }
+            Self::ImmutableCreate2 => &[Mainnet, Sepolia, Base, Arbitrum, Optimism, Polygon],
+            Self::Eip2470 | Self::LayerZeroCreate3 => &[Mainnet],
+            Self::ZeframlouCreate3 => &[Mainnet, Arbitrum, Optimism, Polygon, Bsc],
+            Self::SoladyCreate3 | Self::AxelarCreate3 => &[],
+        }
+    }
+
+    /// Returns the rules the factory applies to salts.
+    fn salt_rules(self) -> &'static str {
+        match self {
+            Self::Arachnid | Self::SafeSingleton | Self::Eip2470 => "salt used as is",
+            Self::ImmutableCreate2 => "salt starts with the caller or 20 zero bytes",
+            Self::CreateX => "salt hashed with the caller, and the chain id with --chain-id",
+            Self::LayerZeroCreate3 | Self::ZeframlouCreate3 => {
+                "proxy salted with keccak256(caller ++ salt)"
+            }
+            Self::SoladyCreate3 => "proxy salted with the salt as is",
+            Self::AxelarCreate3 => "proxy salted with keccak256(abi.encode(caller, salt))",
+        }
+    }
+
+    /// Returns the preset of a CREATE2 factory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the factory deploys with CREATE3.
+    pub(super) fn preset(self) -> Result<FactoryPreset> {
+        match self.kind() {
+            FactoryKind::Create2(preset) => Ok(preset),
+            FactoryKind::Create3(_) => Err(Error::FactoryMethodMismatch {
+                name: self.name(),
+                method: "create3",
+            }),
+        }
+    }
+
+    /// Returns the variant of a CREATE3 factory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the factory deploys with CREATE2.
+    pub(super) fn create3_variant(self) -> Result<Create3Variant> {
+        match self.kind() {
+            FactoryKind::Create3(variant) => Ok(variant),
+            FactoryKind::Create2(_) => Err(Error::FactoryMethodMismatch {
+                name: self.name(),
+                method: "create2",
+            }),
+        }
+    }
+}
+
+impl Chain {
+    /// Returns the name of the chain, as given to `--chain`.
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::Mainnet => "mainnet",
+            Self::Sepolia => "sepolia",
+            Self::Base => "base",
+            Self::Arbitrum => "arbitrum",
+            Self::Optimism => "optimism",
+            Self::Polygon => "polygon",
+            Self::Bsc => "bsc",
+        }
+    }
+}
+
+/// Returns the registered factories known to be deployed on a chain, in the
+/// order they are preferred.
+fn deployed_on(chain: Chain) -> impl Iterator<Item = FactoryName> {
+    FactoryName::value_variants()
+        .iter()
+        .copied()
+        .filter(move |name| name.chains().contains(&chain))
+}
+
+/// Returns the preset of the preferred CREATE2 factory deployed on a chain.
+pub(super) fn default_preset(chain: Chain) -> Option<FactoryPreset> {
+    deployed_on(chain).find_map(|name| name.preset().ok())
+}
+
+/// Returns the variant of the preferred CREATE3 factory deployed on a chain.
+pub(super) fn default_create3_variant(chain: Chain) -> Option<Create3Variant> {
+    deployed_on(chain).find_map(|name| name.create3_variant().ok())
+}
+
+/// Warns if a registered factory is not known to be deployed on a chain,
+/// where the mined salt could not be used until it is. Factories missing
+/// from the registry are not checked.
+pub(super) fn check_deployed(factory: Address, chain: Chain) {
+    let registered = FactoryName::value_variants()
+        .iter()
+        .find(|name| name.address() == Some(factory));
+    if let Some(name) = registered.filter(|name| !name.chains().contains(&chain)) {
+        eprintln!(
+            "warning: {} is not known to be deployed on {}, check that it is before deploying",
+            name.name(),
+            chain.name()
+        );
+        if *name == FactoryName::Arachnid {
+            eprintln!("  `piwi factory-tx` prints the presigned transaction deploying it");
+        }
+    }
+}
+
+/// Prints every registered factory with its deployment method, address and
+/// salt rules.
+pub(super) fn factories() {
+    let width = FactoryName::value_variants()
+        .iter()
+        .map(|name| name.name().len())
+        .max()
+        .unwrap_or_default();
+    for name in FactoryName::value_variants() {
+        let method = match name.kind() {
+            FactoryKind::Create2(_) => "CREATE2",
+            FactoryKind::Create3(_) => "CREATE3",
+        };
+        let address = name.address().map_or_else(
+            || "give --factory".to_string(),
+            |address| address.to_string(),
+        );
+        let chains = name
+            .chains()
+            .iter()
+            .map(|chain| chain.name())
+            .collect::<Vec<_>>();
+        println!(
+            "{:<width$}  {method}  {address:<42}  {}",
+            name.name(),
+            name.salt_rules()
+        );
+        if !chains.is_empty() {
+            println!("{:<width$}  deployed on {}", "", chains.join(", "));
+        }
+    }
+}
+
+#[test]
+fn test_factory_names() {
+    // Every factory is listed under the name clap accepts
+    for name in FactoryName::value_variants() {
+        assert_eq!(FactoryName::from_str(name.name(), false), Ok(*name));
+    }
+
+    assert_eq!(
+        FactoryName::CreateX.preset().unwrap(),
+        FactoryPreset::CreateX
+    );
+    assert!(FactoryName::CreateX.create3_variant().is_err());
+    assert!(FactoryName::AxelarCreate3.address().is_none());
+
+    // Chains without LayerZero's CREATE3 factory fall back to zeframlou's
+    assert_eq!(default_preset(Chain::Base), Some(FactoryPreset::Arachnid));
+    assert_eq!(
+        default_create3_variant(Chain::Arbitrum),
+        Some(Create3Variant::Zeframlou)
+    );
+    assert_eq!(default_create3_variant(Chain::Base), None);
+}
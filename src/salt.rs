@@ -0,0 +1,194 @@
+use std::ops::Range;
+
+use alloy_primitives::{Bytes, hex};
+
+use crate::error::{Error, Result};
+
+/// Maximum length of the nonce segment of the salt (6 bytes).
+const NONCE_LEN: usize = 6;
+
+/// Layout of a salt buffer explored while mining.
+///
+/// The buffer is split into three consecutive segments:
+/// - Fixed bytes: the deployer address and any pinned bytes
+/// - Random bytes: refilled for each batch of nonce attempts
+/// - Nonce bytes: systematically explored during mining
+///
+/// The nonce segment takes up to 6 bytes of the free region, and the random
+/// segment takes whatever is left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SaltLayout<const N: usize> {
+    /// Buffer holding the fixed bytes, with the other segments zeroed.
+    pub(super) base: [u8; N],
+    /// Range of the random segment.
+    pub(super) random: Range<usize>,
+    /// Range of the nonce segment.
+    pub(super) nonce: Range<usize>,
+}
+
+impl<const N: usize> SaltLayout<N> {
+    /// Creates a layout whose buffer starts with the given fixed bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fixed bytes leave no byte of the buffer free.
+    pub(super) fn new(fixed: &[u8]) -> Result<Self> {
+        if fixed.len() >= N {
+            return Err(Error::NoFreeSaltBytes);
+        }
+
+        let mut base = [0u8; N];
+        base[..fixed.len()].copy_from_slice(fixed);
+
+        // The nonce segment shrinks once less than 6 bytes are free
+        let nonce_start = N - NONCE_LEN.min(N - fixed.len());
+        Ok(Self {
+            base,
+            random: fixed.len()..nonce_start,
+            nonce: nonce_start..N,
+        })
+    }
+
+    /// Returns the number of distinct nonce values.
+    pub(super) fn nonce_count(&self) -> u64 {
+        1 << (8 * self.nonce.len())
+    }
+
+    /// Returns the range of nonces to explore for each random segment.
+    ///
+    /// # Arguments
+    /// * `start` - The first nonce to explore.
+    /// * `end` - The nonce to stop before. Defaults to the number of distinct
+    ///   nonce values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the range is empty or exceeds the nonce segment.
+    pub(super) fn nonce_range(&self, start: u64, end: Option<u64>) -> Result<Range<u64>> {
+        let count = self.nonce_count();
+        let end = end.unwrap_or(count);
+        if start >= end || end > count {
+            return Err(Error::InvalidNonceRange { start, end, count });
+        }
+        Ok(start..end)
+    }
+
+    /// Describes the byte ranges of the segments, leaving out empty ones.
+    pub(super) fn describe(&self) -> String {
+        [
+            ("fixed", 0..self.random.start),
+            ("random", self.random.clone()),
+            ("nonce", self.nonce.clone()),
+        ]
+        .into_iter()
+        .filter(|(_, range)| !range.is_empty())
+        .map(|(name, range)| format!("{name} {range:?}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+
+    /// Writes an index into the random segment of a salt buffer as a
+    /// big-endian counter, returning whether the index fits in the segment.
+    pub(super) fn write_random_index(&self, salt: &mut [u8; N], index: u64) -> bool {
+        let len = self.random.len().min(8);
+        let bytes = index.to_be_bytes();
+        if bytes[..8 - len].iter().any(|&byte| byte != 0) {
+            return false;
+        }
+        salt[self.random.clone()].fill(0);
+        salt[self.random.end - len..self.random.end].copy_from_slice(&bytes[8 - len..]);
+        true
+    }
+
+    /// Writes a nonce into the nonce segment of a salt buffer.
+    #[inline]
+    pub(super) fn write_nonce(&self, salt: &mut [u8; N], nonce: u64) {
+        salt[self.nonce.clone()].copy_from_slice(&nonce.to_be_bytes()[8 - self.nonce.len()..]);
+    }
+}
+
+/// Parses the bytes pinned at the start of the free region of the salt.
+///
+/// A `0x` prefixed value is decoded as hex, anything else is taken as the
+/// UTF-8 bytes of the string, e.g. a project tag.
+pub(super) fn parse_salt_prefix(value: &str) -> Result<Bytes, String> {
+    if value.starts_with("0x") {
+        hex::decode(value)
+            .map(Bytes::from)
+            .map_err(|err| err.to_string())
+    } else {
+        Ok(Bytes::copy_from_slice(value.as_bytes()))
+    }
+}
+
+/// Message a factory hashes into the CREATE2 salt, made of the salt it is
+/// given between fixed bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct SaltTemplate {
+    /// Bytes hashed before the salt.
+    pub(super) before: Bytes,
+    /// Bytes hashed after the salt.
+    pub(super) after: Bytes,
+}
+
+impl SaltTemplate {
+    /// Returns the message hashed into the CREATE2 salt for a salt.
+    pub(super) fn fill(&self, salt: &[u8]) -> Vec<u8> {
+        [&self.before, salt, &self.after].concat()
+    }
+}
+
+/// Parses a salt template, given as hex with `{salt}` standing for the
+/// 32-byte salt, e.g. `0x<OWNER>{salt}` for a factory hashing
+/// `abi.encodePacked(owner, salt)`.
+pub(super) fn parse_salt_template(value: &str) -> Result<SaltTemplate, String> {
+    let Some((before, after)) = value.split_once("{salt}") else {
+        return Err("the template has no {salt} placeholder".to_string());
+    };
+    let decode = |value: &str| {
+        hex::decode(value)
+            .map(Bytes::from)
+            .map_err(|err| err.to_string())
+    };
+    Ok(SaltTemplate {
+        before: decode(before)?,
+        after: decode(after)?,
+    })
+}
+
+#[test]
+fn test_salt_layout() {
+    let layout = SaltLayout::<32>::new(&[1; 20]).unwrap();
+    assert_eq!((layout.random, layout.nonce), (20..26, 26..32));
+
+    // Pinned bytes shrink the random segment first, then the nonce segment
+    let layout = SaltLayout::<32>::new(&[1; 28]).unwrap();
+    assert_eq!(
+        (layout.random.clone(), layout.nonce.clone()),
+        (28..28, 28..32)
+    );
+    assert_eq!(layout.nonce_count(), 1 << 32);
+    assert_eq!(layout.describe(), "fixed 0..28, nonce 28..32");
+
+    let mut salt = layout.base;
+    layout.write_nonce(&mut salt, 0x01020304);
+    assert_eq!(salt[28..32], [1, 2, 3, 4]);
+
+    let template = parse_salt_template("0x0102{salt}03").unwrap();
+    assert_eq!(template.fill(&[0xaa]), [1, 2, 0xaa, 3]);
+    assert!(parse_salt_template("0x0102").is_err());
+
+    // Without a random segment, only the first index fits
+    assert!(layout.write_random_index(&mut salt, 0));
+    assert!(!layout.write_random_index(&mut salt, 1));
+    let short = SaltLayout::<32>::new(&[1; 24]).unwrap();
+    assert!(short.write_random_index(&mut salt, 0x0203));
+    assert_eq!(salt[24..26], [2, 3]);
+    assert!(!short.write_random_index(&mut salt, 0x010000));
+
+    assert_eq!(layout.nonce_range(5, None).unwrap(), 5..1 << 32);
+    assert!(layout.nonce_range(5, Some(5)).is_err());
+    assert!(layout.nonce_range(0, Some((1 << 32) + 1)).is_err());
+
+    assert!(SaltLayout::<32>::new(&[1; 32]).is_err());
+}
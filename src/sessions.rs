@@ -0,0 +1,284 @@
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    bench::format_hashrate,
+    cli::Sessions,
+    error::{Error, Result},
+    mine::{Miner, MiningResult},
+    pattern::Pattern,
+};
+
+/// Name of the file holding one JSON session record per line.
+const SESSIONS_FILE: &str = "sessions.jsonl";
+
+/// Mining run whose inputs and result are recorded in the sessions store.
+pub(super) struct Session<'a, M> {
+    /// The miner that found the salt.
+    pub(super) miner: &'a M,
+    /// The pattern the address was mined for.
+    pub(super) pattern: &'a Pattern,
+    /// When mining started.
+    pub(super) started: SystemTime,
+    /// The salt found, with the address it deploys to.
+    pub(super) result: MiningResult,
+}
+
+impl<M: Miner> Session<'_, M> {
+    /// Appends the session to the sessions store.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read or written.
+    pub(super) fn record(&self) -> Result<()> {
+        // Derive the hashrate from the attempts of this run only
+        let duration = self.started.elapsed().unwrap_or_default();
+        let attempts = self.miner.attempts();
+        let derivation: Vec<Value> = self
+            .miner
+            .derivation()?
+            .into_iter()
+            .map(|(label, value)| json!([label, value]))
+            .collect();
+        let session = json!({
+            "started": self.started.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            "duration": duration.as_secs_f64(),
+            "attempts": attempts,
+            "hashrate": attempts as f64 / duration.as_secs_f64().max(f64::EPSILON),
+            "command": env::args().collect::<Vec<_>>().join(" "),
+            "derivation": derivation,
+            "flags": self.pattern.flags_hex(),
+            "prefix": self.pattern.prefix_hex(),
+            "address": self.result.address.to_string(),
+            "salt": self.result.salt,
+            "seed": self.result.seed,
+        });
+        append_session(&sessions_path()?, session)
+    }
+}
+
+/// Appends a session to the store under the next id.
+///
+/// The store stays locked from reading the recorded sessions to writing the
+/// new one, so that concurrent runs get distinct ids.
+///
+/// # Errors
+///
+/// Returns an error if the store cannot be read or written.
+///
+/// # Arguments
+/// * `path` - The path of the sessions store.
+/// * `session` - The JSON object of the session, without its id.
+fn append_session(path: &Path, mut session: Value) -> Result<()> {
+    let read_error = |source| Error::Read {
+        path: path.display().to_string(),
+        source,
+    };
+    let write_error = |source| Error::Write {
+        path: path.display().to_string(),
+        source,
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(write_error)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)
+        .map_err(write_error)?;
+    file.lock().map_err(write_error)?;
+
+    // Number the session after the ones recorded so far, the lock being
+    // released when the file is closed
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).map_err(read_error)?;
+    session["id"] = json!(parse_sessions(&contents).len() + 1);
+    writeln!(file, "{session}").map_err(write_error)
+}
+
+/// Lists the recorded sessions or shows the details of one of them.
+///
+/// # Errors
+///
+/// Returns an error if the sessions store cannot be read, or if no session
+/// has the requested id.
+pub(super) fn sessions(command: Option<Sessions>) -> Result<()> {
+    let sessions = read_sessions(&sessions_path()?)?;
+
+    match command.unwrap_or(Sessions::List) {
+        Sessions::List => {
+            println!(
+                "{:>4}  {:<19}  {:>10}  {:<42}  Command",
+                "ID", "Started (UTC)", "Duration", "Address"
+            );
+            for session in &sessions {
+                println!(
+                    "{:>4}  {:<19}  {:>9.1}s  {:<42}  {}",
+                    session["id"].as_u64().unwrap_or_default(),
+                    format_timestamp(session["started"].as_u64().unwrap_or_default()),
+                    session["duration"].as_f64().unwrap_or_default(),
+                    session["address"].as_str().unwrap_or_default(),
+                    session["command"].as_str().unwrap_or_default()
+                );
+            }
+        }
+        Sessions::Show { id } => {
+            let session = sessions
+                .iter()
+                .find(|session| session["id"].as_u64() == Some(id))
+                .ok_or(Error::UnknownSession(id))?;
+
+            let field = |name: &str| session[name].as_str().unwrap_or_default().to_string();
+            let mut fields = vec![
+                (
+                    "Started",
+                    format_timestamp(session["started"].as_u64().unwrap_or_default()),
+                ),
+                (
+                    "Duration",
+                    format!("{:.1}s", session["duration"].as_f64().unwrap_or_default()),
+                ),
+                ("Attempts", session["attempts"].to_string()),
+                (
+                    "Hashrate",
+                    format_hashrate(session["hashrate"].as_f64().unwrap_or_default()),
+                ),
+                ("Command", field("command")),
+            ];
+            for pair in session["derivation"].as_array().into_iter().flatten() {
+                let label = pair[0].as_str().unwrap_or_default();
+                fields.push((label, pair[1].as_str().unwrap_or_default().to_string()));
+            }
+            fields.extend([
+                ("Flags", field("flags")),
+                ("Prefix", field("prefix")),
+                ("Address", field("address")),
+                ("Salt", field("salt")),
+            ]);
+
+            // Align the values on the longest label
+            let width = fields
+                .iter()
+                .map(|(label, _)| label.len())
+                .max()
+                .unwrap_or_default();
+            for (label, value) in fields {
+                println!("{label:<width$}  {value}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the directory Piwi keeps its state in, `$PIWI_HOME` or `~/.piwi`.
+///
+/// # Errors
+///
+/// Returns an error if neither `PIWI_HOME` nor `HOME` is set.
+pub(super) fn piwi_home() -> Result<PathBuf> {
+    env::var_os("PIWI_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".piwi")))
+        .ok_or(Error::MissingHome)
+}
+
+/// Returns the path of the sessions store, under [`piwi_home`].
+///
+/// # Errors
+///
+/// Returns an error if neither `PIWI_HOME` nor `HOME` is set.
+fn sessions_path() -> Result<PathBuf> {
+    Ok(piwi_home()?.join(SESSIONS_FILE))
+}
+
+/// Reads every session recorded in the store, skipping malformed lines.
+fn read_sessions(path: &Path) -> Result<Vec<Value>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => {
+            return Err(Error::Read {
+                path: path.display().to_string(),
+                source,
+            });
+        }
+    };
+    Ok(parse_sessions(&contents))
+}
+
+/// Parses the sessions of a store, one per line, skipping malformed lines.
+fn parse_sessions(contents: &str) -> Vec<Value> {
+    contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Formats a Unix timestamp as a UTC date and time.
+fn format_timestamp(timestamp: u64) -> String {
+    let days = timestamp / 86_400;
+    let seconds = timestamp % 86_400;
+
+    // Convert the days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        seconds / 3_600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[test]
+fn test_format_timestamp() {
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+    assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00");
+    assert_eq!(format_timestamp(1_790_000_000), "2026-09-21 14:13:20");
+}
+
+#[test]
+fn test_append_session() {
+    use std::thread;
+
+    let path = env::temp_dir().join(format!("piwi-sessions-{}.jsonl", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    // Concurrent runs are numbered one after the other
+    thread::scope(|scope| {
+        for run in 0..8 {
+            let path = &path;
+            scope.spawn(move || append_session(path, json!({ "run": run })).unwrap());
+        }
+    });
+    let mut ids: Vec<u64> = read_sessions(&path)
+        .unwrap()
+        .iter()
+        .map(|session| session["id"].as_u64().unwrap())
+        .collect();
+    ids.sort_unstable();
+    assert_eq!(ids, (1..=8).collect::<Vec<_>>());
+    fs::remove_file(&path).unwrap();
+}
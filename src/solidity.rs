@@ -0,0 +1,189 @@
+use std::{fs, io::ErrorKind, path::Path};
+
+use alloy_primitives::{Address, FixedBytes};
+
+use crate::{
+    cli::Output,
+    error::{Error, Result},
+};
+
+/// Constant name used when neither a name nor an artifact is given.
+const DEFAULT_NAME: &str = "HOOK";
+
+impl Output {
+    /// Writes the mined salt and address as Solidity constants, if requested.
+    ///
+    /// The constants are added to the library in the file, replacing any
+    /// constants of the same name, so results of several runs can be
+    /// collected in one file.
+    ///
+    /// # Arguments
+    /// * `address` - The mined address.
+    /// * `salt` - The salt passed to the factory contract.
+    /// * `artifact` - The artifact the init code was read from, whose contract
+    ///   name is used when no constant name is given.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or written.
+    pub(super) fn emit_solidity(
+        &self,
+        address: Address,
+        salt: &FixedBytes<32>,
+        artifact: Option<&str>,
+    ) -> Result<()> {
+        let Some(path) = &self.emit_solidity else {
+            return Ok(());
+        };
+
+        // Name the constants after the contract of the artifact by default
+        let name = match (&self.solidity_name, artifact) {
+            (Some(name), _) => name.clone(),
+            (None, Some(artifact)) => constant_name(file_stem(artifact)),
+            (None, None) => DEFAULT_NAME.to_string(),
+        };
+
+        let existing = match fs::read_to_string(path) {
+            Ok(contents) => Some(contents),
+            Err(err) if err.kind() == ErrorKind::NotFound => None,
+            Err(source) => {
+                return Err(Error::Read {
+                    path: path.clone(),
+                    source,
+                });
+            }
+        };
+        let contents = add_constants(existing.as_deref(), file_stem(path), &name, address, salt);
+        fs::write(path, contents).map_err(|source| Error::Write {
+            path: path.clone(),
+            source,
+        })
+    }
+}
+
+/// Adds the salt and address constants to a Solidity library, creating the
+/// library if there is none yet.
+///
+/// # Arguments
+/// * `existing` - The current contents of the file, if it exists.
+/// * `library` - The name of the library to create.
+/// * `name` - The name of the address constant, suffixed with `_SALT` for the
+///   salt constant.
+/// * `address` - The mined address.
+/// * `salt` - The salt passed to the factory contract.
+fn add_constants(
+    existing: Option<&str>,
+    library: &str,
+    name: &str,
+    address: Address,
+    salt: &FixedBytes<32>,
+) -> String {
+    let existing = existing.map(str::to_string).unwrap_or_else(|| {
+        format!(
+            "// SPDX-License-Identifier: MIT\n\
+             pragma solidity ^0.8.0;\n\
+             \n\
+             /// @notice Salts and addresses mined with piwi.\n\
+             library {library} {{\n\
+             }}\n"
+        )
+    });
+
+    // Drop the constants of an earlier run with the same name
+    let salt_declaration = format!("bytes32 internal constant {name}_SALT =");
+    let address_declaration = format!("address internal constant {name} =");
+    let mut lines: Vec<String> = existing
+        .lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            !line.starts_with(&salt_declaration) && !line.starts_with(&address_declaration)
+        })
+        .map(str::to_string)
+        .collect();
+
+    // Insert the new constants before the closing brace of the library
+    let end = lines
+        .iter()
+        .rposition(|line| line.trim() == "}")
+        .unwrap_or(lines.len());
+    lines.splice(
+        end..end,
+        [
+            format!("    {salt_declaration} {salt};"),
+            format!("    {address_declaration} {address};"),
+        ],
+    );
+    lines.join("\n") + "\n"
+}
+
+/// Returns the file name of a path without its extensions.
+fn file_stem(path: &str) -> &str {
+    let name = Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path);
+    name.split('.').next().unwrap_or(name)
+}
+
+/// Converts a contract name to a Solidity constant name, e.g. `MyHook` to
+/// `MY_HOOK`.
+fn constant_name(contract: &str) -> String {
+    let mut name = String::new();
+    let mut previous: Option<char> = None;
+    for c in contract.chars() {
+        // Start a new word at each lowercase to uppercase transition
+        if c.is_ascii_uppercase()
+            && previous
+                .is_some_and(|previous| previous.is_ascii_lowercase() || previous.is_ascii_digit())
+        {
+            name.push('_');
+        }
+        name.push(if c.is_ascii_alphanumeric() {
+            c.to_ascii_uppercase()
+        } else {
+            '_'
+        });
+        previous = Some(c);
+    }
+    name
+}
+
+#[test]
+fn test_add_constants() {
+    use alloy_primitives::address;
+
+    assert_eq!(
+        constant_name(file_stem("out/MyHook.sol/MyHook.json")),
+        "MY_HOOK"
+    );
+
+    let address = address!("0x0000000000000000000000000000000000002fff");
+    let contents = add_constants(None, "Salts", "MY_HOOK", address, &FixedBytes::ZERO);
+    let contents = add_constants(
+        Some(&contents),
+        "Salts",
+        "OTHER",
+        address,
+        &FixedBytes::ZERO,
+    );
+    let contents = add_constants(
+        Some(&contents),
+        "Salts",
+        "MY_HOOK",
+        address,
+        &FixedBytes::ZERO,
+    );
+    assert_eq!(
+        contents,
+        "// SPDX-License-Identifier: MIT\n\
+         pragma solidity ^0.8.0;\n\
+         \n\
+         /// @notice Salts and addresses mined with piwi.\n\
+         library Salts {\n    \
+             bytes32 internal constant OTHER_SALT = 0x0000000000000000000000000000000000000000000000000000000000000000;\n    \
+             address internal constant OTHER = 0x0000000000000000000000000000000000002ffF;\n    \
+             bytes32 internal constant MY_HOOK_SALT = 0x0000000000000000000000000000000000000000000000000000000000000000;\n    \
+             address internal constant MY_HOOK = 0x0000000000000000000000000000000000002ffF;\n\
+         }\n"
+    );
+}
@@ -0,0 +1,172 @@
+use std::{
+    iter,
+    num::NonZero,
+    sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{bench::format_hashrate, mine::Miner};
+
+/// How often the reporter checks whether mining is over.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Counters of the salts tried by each mining worker.
+///
+/// Workers count locally and add to their own cache-padded counter once per
+/// job, so the hot loop never contends on a shared cache line.
+#[derive(Debug)]
+pub(super) struct Stats {
+    /// One counter per worker thread.
+    workers: Box<[WorkerCounter]>,
+    /// Number of counters workers have added to so far.
+    active: AtomicUsize,
+    /// Number of random segments searched without a match.
+    segments: AtomicU64,
+}
+
+/// Counter of a single worker, padded to its own cache line.
+#[derive(Debug, Default)]
+#[repr(align(128))]
+struct WorkerCounter(AtomicU64);
+
+impl Default for Stats {
+    fn default() -> Self {
+        let workers = thread::available_parallelism().map_or(1, NonZero::get);
+        Self {
+            workers: (0..workers).map(|_| WorkerCounter::default()).collect(),
+            active: AtomicUsize::new(0),
+            segments: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Stats {
+    /// Returns a counter for a job of the current worker, which is added to
+    /// the worker counter when dropped.
+    pub(super) fn local(&self) -> LocalCount<'_> {
+        LocalCount {
+            stats: self,
+            count: 0,
+        }
+    }
+
+    /// Returns the number of salts tried by every worker together.
+    pub(super) fn total(&self) -> u64 {
+        self.workers
+            .iter()
+            .map(|worker| worker.0.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Counts a random segment searched without a match.
+    pub(super) fn segment_exhausted(&self) {
+        self.segments.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the number of random segments searched without a match.
+    pub(super) fn exhausted_segments(&self) -> u64 {
+        self.segments.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of salts tried by each worker that counted any.
+    ///
+    /// Workers beyond the number of available cores share counters with the
+    /// first ones.
+    pub(super) fn per_worker(&self) -> Vec<u64> {
+        self.workers[..self.active.load(Ordering::Relaxed)]
+            .iter()
+            .map(|worker| worker.0.load(Ordering::Relaxed))
+            .collect()
+    }
+}
+
+/// Count of the salts tried by a worker job, published when dropped.
+pub(super) struct LocalCount<'a> {
+    /// The counters the count is added to.
+    stats: &'a Stats,
+    /// Number of salts tried by the job so far.
+    count: u64,
+}
+
+impl LocalCount<'_> {
+    /// Counts the given number of salts.
+    #[inline]
+    pub(super) fn add(&mut self, count: u64) {
+        self.count += count;
+    }
+}
+
+impl Drop for LocalCount<'_> {
+    fn drop(&mut self) {
+        // Threads outside the pool, or beyond the number of counters, share
+        // counters, which the atomic addition keeps correct
+        let index = rayon::current_thread_index().unwrap_or_default() % self.stats.workers.len();
+        self.stats.workers[index]
+            .0
+            .fetch_add(self.count, Ordering::Relaxed);
+        self.stats.active.fetch_max(index + 1, Ordering::Relaxed);
+    }
+}
+
+/// Prints the progress of a miner to stderr at a regular interval until
+/// mining is over.
+///
+/// # Arguments
+/// * `miner` - The miner whose attempts are reported.
+/// * `interval` - The time between two reports.
+/// * `per_thread` - Whether to also print the hashrate of each worker thread.
+/// * `done` - The flag raised once mining is over.
+pub(super) fn report<M: Miner>(miner: &M, interval: Duration, per_thread: bool, done: &AtomicBool) {
+    let start = Instant::now();
+    let (mut last_time, mut last_attempts) = (start, 0);
+    let mut last_workers = Vec::new();
+
+    while !done.load(Ordering::Relaxed) {
+        thread::sleep(POLL_INTERVAL);
+        if last_time.elapsed() < interval {
+            continue;
+        }
+
+        // Report the hashrate since the previous report, and the random
+        // segments searched without a match, if any
+        let (now, attempts) = (Instant::now(), miner.attempts());
+        let seconds = (now - last_time).as_secs_f64();
+        let hashrate = (attempts - last_attempts) as f64 / seconds;
+        let segments = match miner.exhausted_segments() {
+            0 => String::new(),
+            segments => format!(", {segments} random segments without a match"),
+        };
+        eprintln!(
+            "[{:>5}s] {attempts:.3e} attempts, {}{segments}",
+            start.elapsed().as_secs(),
+            format_hashrate(hashrate)
+        );
+
+        // Report each worker on one line, slow ones standing out by their
+        // lower hashrate
+        if per_thread {
+            let workers = miner.worker_attempts();
+            let hashrates: Vec<String> = workers
+                .iter()
+                .zip(last_workers.iter().chain(iter::repeat(&0)))
+                .map(|(attempts, last)| format!("{:.2}", (attempts - last) as f64 / seconds / 1e6))
+                .collect();
+            eprintln!("         per thread (MH/s): {}", hashrates.join(" "));
+            last_workers = workers;
+        }
+        (last_time, last_attempts) = (now, attempts);
+    }
+}
+
+#[test]
+fn test_per_worker() {
+    let stats = Stats::default();
+    assert!(stats.per_worker().is_empty());
+
+    // Threads outside the pool count as the first worker
+    stats.local().add(3);
+    stats.local().add(4);
+    assert_eq!(stats.per_worker(), [7]);
+    assert_eq!(stats.total(), 7);
+}
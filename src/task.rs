@@ -0,0 +1,105 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::{
+    sync::watch,
+    task,
+    time::{self, Instant},
+};
+
+use crate::{
+    cancel::CancellationToken,
+    error::Result,
+    mine::{Miner, MiningResult},
+    pattern::Pattern,
+};
+
+/// Time between two progress updates of an async search.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Progress of an async search, as published on its watch channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Progress {
+    /// Number of salts tried by the miner so far.
+    pub attempts: u64,
+    /// Number of salts tried per second since the previous update.
+    pub hashrate: f64,
+}
+
+/// Cancels a search once the future waiting for it is dropped.
+struct CancelOnDrop(CancellationToken);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Searches for a salt producing an address matching a pattern on the tokio
+/// blocking pool, resolving once it is found.
+///
+/// The search runs on the rayon thread pool from a blocking task, so it does
+/// not hold up the async workers. The progress is published on the given
+/// watch channel every second. Dropping the future cancels the search, which
+/// then stops within a chunk of work.
+///
+/// # Arguments
+/// * `miner` - The miner to search with.
+/// * `pattern` - The pattern the address must match.
+/// * `progress` - The channel the progress is published on.
+///
+/// # Errors
+///
+/// Returns an error if mining fails, see [`Miner::mine`].
+pub async fn mine_async<M>(
+    miner: M,
+    pattern: &Pattern,
+    progress: watch::Sender<Progress>,
+) -> Result<MiningResult>
+where
+    M: Miner + Send + Sync + 'static,
+{
+    let miner = Arc::new(miner);
+    let _cancel = CancelOnDrop(miner.cancellation().clone());
+    let search = task::spawn_blocking({
+        let (miner, pattern) = (miner.clone(), *pattern);
+        move || miner.mine(&pattern)
+    });
+    tokio::pin!(search);
+
+    // Publish the progress until the search is over
+    let mut ticks = time::interval_at(Instant::now() + PROGRESS_INTERVAL, PROGRESS_INTERVAL);
+    let (mut last_time, mut last_attempts) = (Instant::now(), 0);
+    loop {
+        tokio::select! {
+            result = &mut search => {
+                return result.expect("the search does not panic");
+            }
+            now = ticks.tick() => {
+                let attempts = miner.attempts();
+                let hashrate = (attempts - last_attempts) as f64 / (now - last_time).as_secs_f64();
+                progress.send_replace(Progress { attempts, hashrate });
+                (last_time, last_attempts) = (now, attempts);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_mine_async() {
+    use alloy_primitives::{Address, FixedBytes};
+
+    use crate::mine::Create2Miner;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let miner = Create2Miner::new(Address::ZERO, Address::ZERO, FixedBytes::ZERO);
+    let pattern = Pattern::parse("0x0080", "").unwrap();
+    let (sender, _) = watch::channel(Progress::default());
+    let result = runtime
+        .block_on(mine_async(miner.clone(), &pattern, sender))
+        .unwrap();
+    assert_eq!(miner.compute_address(&result.salt), result.address);
+    assert!(pattern.matcher().matches(&result.address));
+}
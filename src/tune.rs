@@ -0,0 +1,117 @@
+use std::{array, time::Duration};
+
+/// Unit sizes tried while calibrating, in nonces.
+const CALIBRATION_UNITS: [u64; 4] = [1 << 8, 1 << 10, 1 << 12, 1 << 14];
+
+/// Number of nonces each thread searches with every calibrated unit size.
+const CALIBRATION_NONCES: u64 = 1 << 15;
+
+/// Time a chunk should take once calibrated, which bounds how late the
+/// checkpoint is written and a cancellation is noticed.
+const CHUNK_DURATION: Duration = Duration::from_millis(250);
+
+/// Largest chunk, in nonces, whatever the hashrate.
+const MAX_CHUNK_SIZE: u64 = 1 << 28;
+
+/// Amounts of work, in nonces, handed out while searching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct WorkSizes {
+    /// Nonces searched in parallel between two checkpoints.
+    pub(super) chunk: u64,
+    /// Consecutive nonces a worker claims at once within a chunk.
+    pub(super) unit: u64,
+}
+
+/// Picks the work sizes maximizing the hashrate on the current machine.
+///
+/// The first chunks of a search are each searched with one of the candidate
+/// unit sizes, so calibrating costs no work. The fastest unit size is then
+/// kept, with chunks sized to take about [`CHUNK_DURATION`] at the measured
+/// hashrate.
+#[derive(Debug)]
+pub(super) struct Tuner {
+    /// Number of threads searching each chunk.
+    threads: u64,
+    /// Unit sizes still to be calibrated.
+    candidates: array::IntoIter<u64, 4>,
+    /// Fastest unit size so far, with its hashrate.
+    best: Option<(u64, f64)>,
+    /// Sizes picked once calibrated.
+    tuned: Option<WorkSizes>,
+}
+
+impl Tuner {
+    /// Creates a tuner for a search run by the given number of threads.
+    pub(super) fn new(threads: usize) -> Self {
+        Self {
+            threads: threads.max(1) as u64,
+            candidates: CALIBRATION_UNITS.into_iter(),
+            best: None,
+            tuned: None,
+        }
+    }
+
+    /// Returns the work sizes to search the next chunk with.
+    pub(super) fn sizes(&self) -> WorkSizes {
+        self.tuned.unwrap_or_else(|| {
+            // Calibrate the next candidate
+            let unit = *self
+                .candidates
+                .as_slice()
+                .first()
+                .expect("candidates remain until the sizes are tuned");
+            WorkSizes {
+                chunk: CALIBRATION_NONCES * self.threads,
+                unit,
+            }
+        })
+    }
+
+    /// Records how long a chunk searched with the given sizes took.
+    ///
+    /// # Arguments
+    /// * `sizes` - The sizes the chunk was searched with.
+    /// * `nonces` - The number of nonces of the chunk, which is smaller than
+    ///   the chunk size at the end of the nonce range.
+    /// * `elapsed` - The time the search took.
+    pub(super) fn record(&mut self, sizes: WorkSizes, nonces: u64, elapsed: Duration) {
+        if self.tuned.is_some() || self.candidates.next().is_none() {
+            return;
+        }
+
+        let hashrate = nonces as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        if self.best.is_none_or(|(_, best)| hashrate > best) {
+            self.best = Some((sizes.unit, hashrate));
+        }
+
+        // Size the chunks after the fastest unit once every one was tried
+        if self.candidates.len() == 0
+            && let Some((unit, hashrate)) = self.best
+        {
+            let chunk = (hashrate * CHUNK_DURATION.as_secs_f64()) as u64;
+            let chunk = chunk.clamp(unit * self.threads, MAX_CHUNK_SIZE);
+            self.tuned = Some(WorkSizes {
+                chunk: chunk.next_multiple_of(unit),
+                unit,
+            });
+        }
+    }
+}
+
+#[test]
+fn test_tuner() {
+    let mut tuner = Tuner::new(2);
+    let rates = [1.0, 4.0, 2.0, 3.0];
+    for (unit, rate) in CALIBRATION_UNITS.into_iter().zip(rates) {
+        let sizes = tuner.sizes();
+        assert_eq!(sizes.unit, unit);
+        assert_eq!(sizes.chunk, 2 * CALIBRATION_NONCES);
+        tuner.record(sizes, sizes.chunk, Duration::from_secs_f64(1.0 / rate));
+    }
+
+    // The second unit size is the fastest, at 4 calibration chunks per second,
+    // so a quarter of a second is one calibration chunk
+    let sizes = tuner.sizes();
+    assert_eq!(sizes.unit, CALIBRATION_UNITS[1]);
+    assert_eq!(sizes.chunk, 2 * CALIBRATION_NONCES);
+}
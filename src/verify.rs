@@ -0,0 +1,131 @@
+use alloy_primitives::Address;
+
+use crate::{
+    error::Result,
+    pattern::{FLAGS_MASK, flags_value, parse_flags, parse_prefix},
+};
+
+/// Compares a derived address against the expected one and prints a report.
+///
+/// Differing hex characters are marked below the addresses, and the flags and
+/// prefix of the derived address are checked when they are given.
+///
+/// Returns whether every check passed.
+///
+/// # Errors
+///
+/// Returns an error if the expected flags or prefix are malformed.
+///
+/// # Arguments
+/// * `derived` - The address derived from the salt.
+/// * `proxy` - The intermediate CREATE3 proxy address, if any.
+/// * `expected` - The address the salt is expected to produce.
+/// * `flags` - The flags the address is expected to carry.
+/// * `prefix` - The prefix the address is expected to start with.
+pub(super) fn verify(
+    derived: Address,
+    proxy: Option<Address>,
+    expected: Address,
+    flags: Option<&str>,
+    prefix: Option<&str>,
+) -> Result<bool> {
+    let mut valid = derived == expected;
+
+    if let Some(proxy) = proxy {
+        println!("Proxy address:    {proxy}");
+    }
+    println!("Derived address:  {derived}");
+    println!("Expected address: {expected}");
+    if derived == expected {
+        println!("Address matches");
+    } else {
+        // Mark every hex character that differs between the two addresses
+        let marker: String = hex::encode(derived)
+            .chars()
+            .zip(hex::encode(expected).chars())
+            .map(|(left, right)| if left == right { ' ' } else { '^' })
+            .collect();
+        println!("                    {marker}");
+        println!("Address MISMATCH");
+    }
+
+    if let Some(flags) = flags {
+        let flags = parse_flags(flags)?;
+        let actual = derived.bit_and(FLAGS_MASK);
+        if actual == flags {
+            println!("Flags {:#06x} match", flags_value(actual));
+        } else {
+            println!(
+                "Flags MISMATCH: derived {:#06x}, expected {:#06x}",
+                flags_value(actual),
+                flags_value(flags)
+            );
+            valid = false;
+        }
+    }
+
+    if let Some(prefix) = prefix {
+        let (prefix, prefix_mask) = parse_prefix(prefix)?;
+        let nibbles = prefix_mask
+            .iter()
+            .map(|byte| byte.count_ones())
+            .sum::<u32>() as usize
+            / 4;
+        let actual = &hex::encode(derived)[..nibbles];
+        let expected = &hex::encode(prefix)[..nibbles];
+        if derived.bit_and(prefix_mask) == prefix {
+            println!("Prefix {actual} matches");
+        } else {
+            println!("Prefix MISMATCH: derived {actual}, expected {expected}");
+            valid = false;
+        }
+    }
+
+    Ok(valid)
+}
+
+#[test]
+fn test_verify() {
+    use std::process::ExitCode;
+
+    use alloy_primitives::{FixedBytes, b256};
+    use clap::FromArgMatches;
+
+    use crate::{CREATE2_DEFAULT_FACTORY, cli::Piwi, error::Error, exit_code, run};
+
+    let init_code_hash =
+        b256!("0x2222222222222222222222222222222222222222222222222222222222222222");
+    let salt = FixedBytes::<32>::with_last_byte(1);
+    let derived = CREATE2_DEFAULT_FACTORY.create2(salt, init_code_hash);
+    let other = CREATE2_DEFAULT_FACTORY.create2(FixedBytes::<32>::ZERO, init_code_hash);
+
+    // Every check passes on the derived address, and any mismatch fails
+    let flags = format!("{:#06x}", flags_value(derived.bit_and(FLAGS_MASK)));
+    let prefix = &hex::encode(derived)[..4];
+    assert!(verify(derived, None, derived, Some(&flags), Some(prefix)).unwrap());
+    assert!(!verify(derived, None, other, None, None).unwrap());
+    assert!(!verify(derived, None, derived, None, Some(&hex::encode(other)[..8])).unwrap());
+    assert!(verify(derived, None, derived, Some("0xzz"), None).is_err());
+
+    // The command fails with a non-zero status on a mismatch
+    let command = |expected: Address| {
+        let matches = Piwi::command_with(false).get_matches_from([
+            "piwi".to_string(),
+            "verify".to_string(),
+            "create2".to_string(),
+            init_code_hash.to_string(),
+            "--salt".to_string(),
+            salt.to_string(),
+            "--address".to_string(),
+            expected.to_string(),
+        ]);
+        Piwi::from_arg_matches(&matches).unwrap()
+    };
+    assert!(run(command(derived)).is_ok());
+    assert!(matches!(
+        run(command(other)),
+        Err(Error::VerificationFailed)
+    ));
+    assert_eq!(exit_code(run(command(derived))), ExitCode::SUCCESS);
+    assert_eq!(exit_code(run(command(other))), ExitCode::FAILURE);
+}
@@ -0,0 +1,141 @@
+extern crate alloc;
+
+use ::core::{fmt::Display, str::FromStr};
+use alloc::{format, string::String};
+
+use alloy_primitives::{Address, B256};
+use wasm_bindgen::prelude::*;
+
+use crate::core::{self, Matcher, Pattern};
+
+/// CREATE2 salt search driven from JavaScript one chunk of salts at a time,
+/// e.g. from a web worker, instead of a thread pool.
+///
+/// Salts start with the deployer address, followed by 4 bytes of entropy
+/// picked by the caller, so that several workers explore different salts,
+/// and by an 8-byte nonce counting the salts tried.
+#[wasm_bindgen]
+pub struct Create2Search {
+    /// Address of the CREATE2 factory contract.
+    factory: Address,
+    /// Hash of the init code of the contract.
+    init_code_hash: B256,
+    /// Pattern the address has to match.
+    matcher: Matcher,
+    /// Salt with the deployer address and the entropy, whose nonce is set
+    /// for each salt tried.
+    salt: [u8; 32],
+    /// Nonce of the next salt to try.
+    nonce: u64,
+}
+
+#[wasm_bindgen]
+impl Create2Search {
+    /// Creates a search for the salts deploying a contract to an address
+    /// matching the flags and prefix.
+    ///
+    /// # Arguments
+    /// * `deployer` - The address starting the salt.
+    /// * `factory` - The address of the CREATE2 factory contract.
+    /// * `init_code_hash` - The keccak256 hash of the init code.
+    /// * `flags` - The flags expected in the lower 14 bits of the address.
+    /// * `prefix` - The prefix expected at the start of the address.
+    /// * `entropy` - The random bytes following the deployer in the salt.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an address, the hash or the pattern is malformed.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        deployer: &str,
+        factory: &str,
+        init_code_hash: &str,
+        flags: &str,
+        prefix: &str,
+        entropy: u32,
+    ) -> Result<Create2Search, JsError> {
+        let deployer: Address = parse("deployer", deployer)?;
+        let mut salt = [0; 32];
+        salt[..20].copy_from_slice(deployer.as_slice());
+        salt[20..24].copy_from_slice(&entropy.to_be_bytes());
+        Ok(Self {
+            factory: parse("factory", factory)?,
+            init_code_hash: parse("init code hash", init_code_hash)?,
+            matcher: Pattern::parse(flags, prefix)?.matcher(),
+            salt,
+            nonce: 0,
+        })
+    }
+
+    /// Tries the next salts, returning the first one deploying the contract
+    /// to a matching address, or nothing if none of them does.
+    ///
+    /// # Arguments
+    /// * `count` - The number of salts to try.
+    #[wasm_bindgen(js_name = mineChunk)]
+    pub fn mine_chunk(&mut self, count: u32) -> Option<String> {
+        for _ in 0..count {
+            self.salt[24..].copy_from_slice(&self.nonce.to_be_bytes());
+            self.nonce += 1;
+            let salt = B256::from(self.salt);
+            let address = core::create2_address(self.factory, salt, self.init_code_hash);
+            if self.matcher.matches(&address) {
+                return Some(format!("{salt}"));
+            }
+        }
+        None
+    }
+
+    /// Returns the number of salts tried so far.
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Computes the address a factory deploys a contract to with CREATE2, as a
+/// checksummed hex string.
+///
+/// # Errors
+///
+/// Returns an error if an argument is malformed.
+#[wasm_bindgen(js_name = computeCreate2Address)]
+pub fn compute_create2_address(
+    factory: &str,
+    salt: &str,
+    init_code_hash: &str,
+) -> Result<String, JsError> {
+    let address = core::create2_address(
+        parse("factory", factory)?,
+        parse("salt", salt)?,
+        parse("init code hash", init_code_hash)?,
+    );
+    Ok(format!("{address}"))
+}
+
+/// Parses a hex argument, naming it in the error.
+fn parse<T>(name: &str, value: &str) -> Result<T, JsError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .parse()
+        .map_err(|err| JsError::new(&format!("invalid {name} {value:?}: {err}")))
+}
+
+#[test]
+fn test_create2_search() {
+    let zero = "0x0000000000000000000000000000000000000000";
+    let hash = "0x0000000000000000000000000000000000000000000000000000000000000000";
+    let Ok(mut search) = Create2Search::new(zero, zero, hash, "0x0080", "", 7) else {
+        panic!("the arguments are valid");
+    };
+    let salt = (0..64).find_map(|_| search.mine_chunk(1 << 12)).unwrap();
+    let Ok(address) = compute_create2_address(zero, &salt, hash) else {
+        panic!("the salt is valid");
+    };
+    let address: Address = address.parse().unwrap();
+    assert!(search.matcher.matches(&address));
+    assert_eq!(&salt[42..50], "00000007");
+}
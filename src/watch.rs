@@ -0,0 +1,62 @@
+use std::{
+    fs, thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    cancel::CancellationToken,
+    error::{Error, Result},
+};
+
+/// How often the watched file is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Runs a mining operation again each time a file changes.
+///
+/// The operation is given a token cancelled as soon as the file changes, upon
+/// which it is expected to stop with [`Error::Cancelled`] so that it can be
+/// restarted with the new contents. Failures, e.g. from a half-written
+/// artifact, are reported and the next change is awaited.
+///
+/// # Arguments
+/// * `path` - The file to watch.
+/// * `run` - The operation to run for each version of the file.
+pub(super) fn watch(
+    path: &str,
+    mut run: impl FnMut(CancellationToken) -> Result<()>,
+) -> Result<()> {
+    loop {
+        let modified = modified_time(path);
+        let changed = CancellationToken::new();
+
+        thread::scope(|scope| {
+            // Poll the file from another thread while the operation runs
+            let poller = scope.spawn(|| {
+                while modified_time(path) == modified {
+                    thread::sleep(POLL_INTERVAL);
+                }
+                changed.cancel();
+            });
+
+            match run(changed.clone()) {
+                Ok(()) => eprintln!("Watching {path} for changes"),
+                Err(Error::Cancelled { .. }) => {}
+                Err(err) => eprintln!("error: {err}\nWatching {path} for changes"),
+            }
+
+            // Wait for the next change before running again
+            poller.join().expect("the poller does not panic");
+        });
+
+        // Let the compiler finish writing the file
+        thread::sleep(POLL_INTERVAL);
+        eprintln!("{path} changed, mining again");
+    }
+}
+
+/// Returns the last modification time of a file, if it can be read.
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,222 @@
+use std::{
+    io::{self, BufRead, Write},
+    str::FromStr,
+};
+
+use alloy_primitives::{Address, FixedBytes};
+
+use crate::{
+    error::{Error, Result},
+    pattern::{HOOK_FLAGS, Pattern, hook_flag, parse_flags},
+};
+
+/// Walks the user through the mining options and prints the equivalent
+/// non-interactive command.
+///
+/// # Errors
+///
+/// Returns an error if stdin is closed before every question is answered.
+pub(super) fn wizard() -> Result<()> {
+    let args = ask(&mut io::stdin().lock(), &mut io::stdout())?;
+
+    println!();
+    println!("Run the following command to start mining:");
+    println!();
+    println!("  piwi {}", args.join(" "));
+    Ok(())
+}
+
+/// Asks every question of the wizard and returns the arguments of the
+/// equivalent `piwi` command.
+fn ask(input: &mut impl BufRead, output: &mut impl Write) -> Result<Vec<String>> {
+    let mut prompter = Prompter { input, output };
+
+    // Step 1: Choose the deployment method
+    let method = prompter.ask(
+        "Deployment method (create2, create3)",
+        Some("create2"),
+        |answer| match answer {
+            "create2" | "create3" => Ok(answer.to_string()),
+            _ => Err("expected create2 or create3".to_string()),
+        },
+    )?;
+    let mut args = vec![method.clone()];
+
+    // Step 2: Choose the deployer and factory addresses
+    let deployer = prompter.ask("Deployer address", None, parse_address)?;
+    let factory = prompter.ask(
+        "Factory address, empty for the default",
+        Some(""),
+        |answer| {
+            if answer.is_empty() {
+                Ok(String::new())
+            } else {
+                parse_address(answer)
+            }
+        },
+    )?;
+    if !factory.is_empty() {
+        args.extend(["--factory".to_string(), factory]);
+    }
+
+    // Step 3: Choose the init code, which only affects CREATE2 addresses
+    if method == "create2" {
+        let source = prompter.ask("Artifact path or init code hash", None, |answer| {
+            Ok(answer.to_string())
+        })?;
+        if FixedBytes::<32>::from_str(&source).is_err() {
+            args.push("--artifact".to_string());
+        }
+        args.push(shell_quote(&source));
+    }
+
+    // Step 4: Choose the hook flags by name, index or value
+    prompter.say("Hook flags:")?;
+    for (index, name) in HOOK_FLAGS.iter().enumerate() {
+        prompter.say(&format!("  {:>2}. {name}", index + 1))?;
+    }
+    let flags = prompter.ask(
+        "Hook flags, as names or numbers separated by commas, or a hex value",
+        None,
+        parse_flags_answer,
+    )?;
+
+    // Step 5: Choose the prefix, which has to agree with the flags
+    let prefix = prompter.ask("Address prefix, empty for none", Some(""), |answer| {
+        Pattern::parse(&flags, answer)
+            .map(|_| answer.to_string())
+            .map_err(|err| err.to_string())
+    })?;
+
+    args.extend([deployer, flags]);
+    if !prefix.is_empty() {
+        args.extend(["--prefix".to_string(), prefix]);
+    }
+    Ok(args)
+}
+
+/// Asks questions on an output and reads the answers from an input.
+struct Prompter<'a, R, W> {
+    /// Input the answers are read from.
+    input: &'a mut R,
+    /// Output the questions are written to.
+    output: &'a mut W,
+}
+
+impl<R: BufRead, W: Write> Prompter<'_, R, W> {
+    /// Writes a line of text.
+    fn say(&mut self, text: &str) -> Result<()> {
+        writeln!(self.output, "{text}").map_err(Error::Prompt)
+    }
+
+    /// Asks a question until the answer is valid.
+    ///
+    /// # Arguments
+    /// * `question` - The question to ask.
+    /// * `default` - The answer used when the user answers with an empty line.
+    /// * `parse` - Validates an answer, returning why it is invalid otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input ends or cannot be read.
+    fn ask<T>(
+        &mut self,
+        question: &str,
+        default: Option<&str>,
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> Result<T> {
+        loop {
+            match default {
+                Some(default) if !default.is_empty() => {
+                    write!(self.output, "{question} [{default}]: ")
+                }
+                _ => write!(self.output, "{question}: "),
+            }
+            .and_then(|()| self.output.flush())
+            .map_err(Error::Prompt)?;
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line).map_err(Error::Prompt)? == 0 {
+                return Err(Error::Prompt(io::ErrorKind::UnexpectedEof.into()));
+            }
+
+            // Fall back to the default answer on an empty line
+            let answer = match (line.trim(), default) {
+                ("", Some(default)) => default,
+                (answer, _) => answer,
+            };
+            match parse(answer) {
+                Ok(value) => return Ok(value),
+                Err(reason) => self.say(&format!("  {reason}"))?,
+            }
+        }
+    }
+}
+
+/// Validates an address answer.
+fn parse_address(answer: &str) -> Result<String, String> {
+    Address::from_str(answer)
+        .map(|_| answer.to_string())
+        .map_err(|err| format!("invalid address: {err}"))
+}
+
+/// Converts a flags answer into a hex flags value.
+///
+/// The answer is either a hex value or a list of flag names and their 1-based
+/// numbers in [`HOOK_FLAGS`].
+fn parse_flags_answer(answer: &str) -> Result<String, String> {
+    if answer.starts_with("0x") {
+        return parse_flags(answer)
+            .map(|_| answer.to_string())
+            .map_err(|err| err.to_string());
+    }
+
+    let mut flags = 0u16;
+    for item in answer.split([',', ' ']).filter(|item| !item.is_empty()) {
+        let flag = match item.parse::<usize>() {
+            Ok(number @ 1..=14) => 1 << (HOOK_FLAGS.len() - number),
+            _ => hook_flag(item).ok_or_else(|| format!("unknown hook flag {item:?}"))?,
+        };
+        flags |= flag;
+    }
+    Ok(format!("{flags:#06x}"))
+}
+
+/// Quotes an argument for the shell if it contains special characters.
+fn shell_quote(arg: &str) -> String {
+    if arg
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_./:@".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[test]
+fn test_wizard() {
+    let answers = "\n\
+        0x0000000000000000000000000000000000000001\n\
+        \n\
+        out/My Hook.sol/MyHook.json\n\
+        beforeSwap, AFTER_SWAP,nope\n\
+        beforeSwap,AFTER_SWAP,1\n\
+        0xabc\n";
+    let args = ask(&mut answers.as_bytes(), &mut Vec::new()).unwrap();
+    assert_eq!(
+        args,
+        [
+            "create2",
+            "--artifact",
+            "'out/My Hook.sol/MyHook.json'",
+            "0x0000000000000000000000000000000000000001",
+            "0x20c0",
+            "--prefix",
+            "0xabc",
+        ]
+    );
+
+    // The wizard fails instead of looping once the input ends
+    assert!(ask(&mut "create3\n".as_bytes(), &mut Vec::new()).is_err());
+}